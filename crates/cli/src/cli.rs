@@ -4,6 +4,7 @@ use clap::{Parser, Subcommand};
 use cosmrs::{tendermint::chain::Id as ChainId, AccountId};
 use figment::{providers::Serialized, Figment};
 use quartz_common::enclave::types::Fmspc;
+use quartz_tm_prover::config::WitnessPolicy;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
@@ -63,7 +64,7 @@ pub enum Command {
     Init(InitArgs),
 
     /// Perform handshake
-    Handshake(HandshakeArgs),
+    Handshake(Box<HandshakeArgs>),
 
     /// Subcommands for handling the Quartz app contract
     Contract {
@@ -78,17 +79,16 @@ pub enum Command {
     },
 
     /// Build, deploy, perform handshake, and run quartz app while listening for changes
-    Dev(DevArgs),
+    Dev(Box<DevArgs>),
 
     /// Print the FMSPC of the current platform (SGX only)
     PrintFmspc(PrintFmspcArgs),
 }
 
-#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Subcommand, Serialize)]
 pub enum ContractCommand {
     Build(ContractBuildArgs),
-    Deploy(ContractDeployArgs),
+    Deploy(Box<ContractDeployArgs>),
 }
 
 #[derive(Debug, Clone, Subcommand, Serialize)]
@@ -96,7 +96,7 @@ pub enum EnclaveCommand {
     /// Build the Quartz app's enclave
     Build(EnclaveBuildArgs),
     /// Run the Quartz app's enclave
-    Start(EnclaveStartArgs),
+    Start(Box<EnclaveStartArgs>),
 }
 
 #[derive(Debug, Parser, Clone, Serialize, Deserialize)]
@@ -117,6 +117,11 @@ pub struct HandshakeArgs {
     #[arg(long, default_value_t = false)]
     pub unsafe_trust_latest: bool,
 
+    /// Reset the contract's current session first, so that a new enclave can complete the handshake
+    /// against an already deployed contract (the tx sender must be the contract admin)
+    #[arg(long, default_value_t = false)]
+    pub reset: bool,
+
     /// Name or address of private key with which to sign
     #[arg(long)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -133,6 +138,22 @@ pub struct HandshakeArgs {
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub node_url: Option<Url>,
 
+    /// Comma-separated list of tendermint RPC URLs of independent witness nodes
+    #[arg(long, value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub witnesses: Vec<Url>,
+
+    /// Minimum number of distinct witnesses that must be reachable when generating proofs
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_witnesses: Option<usize>,
+
+    /// What to do when fewer than `min_witnesses` distinct witnesses are reachable
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub witness_policy: Option<WitnessPolicy>,
+
     /// websocket URL
     #[arg(long)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -239,6 +260,22 @@ pub struct EnclaveStartArgs {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chain_id: Option<ChainId>,
 
+    /// Comma-separated list of tendermint RPC URLs of independent witness nodes
+    #[arg(long, value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub witnesses: Vec<Url>,
+
+    /// Minimum number of distinct witnesses that must be reachable when generating proofs
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_witnesses: Option<usize>,
+
+    /// What to do when fewer than `min_witnesses` distinct witnesses are reachable
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub witness_policy: Option<WitnessPolicy>,
+
     /// Fetch latest trusted hash and height from the chain instead of existing configuration
     #[arg(long, default_value_t = false)]
     pub unsafe_trust_latest: bool,
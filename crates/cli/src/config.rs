@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use cosmrs::tendermint::chain::Id as ChainId;
+use quartz_tm_prover::config::WitnessPolicy;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
@@ -26,6 +27,19 @@ pub struct Config {
     #[serde_as(as = "DisplayFromStr")]
     pub node_url: Url,
 
+    /// Tendermint RPC URLs of independent nodes that light client proofs are cross-checked against
+    #[serde(default)]
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub witnesses: Vec<Url>,
+
+    /// Minimum number of distinct witnesses that must be reachable when generating proofs
+    #[serde(default = "default_min_witnesses")]
+    pub min_witnesses: usize,
+
+    /// What to do when fewer than `min_witnesses` distinct witnesses are reachable
+    #[serde(default)]
+    pub witness_policy: WitnessPolicy,
+
     /// websocket URL
     #[serde(default = "default_ws_url")]
     #[serde_as(as = "DisplayFromStr")]
@@ -72,6 +86,10 @@ fn default_node_url() -> Url {
         .expect("valid hardcoded URL")
 }
 
+fn default_min_witnesses() -> usize {
+    1
+}
+
 fn default_ws_url() -> Url {
     "ws://127.0.0.1/websocket"
         .parse()
@@ -107,6 +125,9 @@ impl Default for Config {
             tx_sender: default_tx_sender(),
             chain_id: default_chain_id(),
             node_url: default_node_url(),
+            witnesses: vec![],
+            min_witnesses: default_min_witnesses(),
+            witness_policy: WitnessPolicy::default(),
             ws_url: default_ws_url(),
             grpc_url: default_grpc_url(),
             enclave_rpc_addr: default_rpc_addr(),
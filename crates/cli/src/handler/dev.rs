@@ -269,6 +269,7 @@ async fn deploy_and_handshake(
     let handshake = HandshakeRequest {
         contract: wasmaddr_to_id(&contract)?,
         unsafe_trust_latest: args.unsafe_trust_latest,
+        reset: false,
     };
 
     let h_res = handshake
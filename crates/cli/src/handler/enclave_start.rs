@@ -12,6 +12,7 @@ use color_eyre::{
 };
 use cosmrs::AccountId;
 use quartz_common::enclave::types::Fmspc;
+use quartz_tm_prover::config::WitnessPolicy;
 use reqwest::Url;
 use tendermint::chain::Id;
 use tokio::process::{Child, Command};
@@ -50,6 +51,10 @@ impl Handler for EnclaveStartRequest {
                 trusted_hash.to_string(),
                 "--node-url".to_string(),
                 config.node_url.to_string(),
+                "--min-witnesses".to_string(),
+                config.min_witnesses.to_string(),
+                "--witness-policy".to_string(),
+                config.witness_policy.to_string(),
                 "--ws-url".to_string(),
                 config.ws_url.to_string(),
                 "--grpc-url".to_string(),
@@ -58,6 +63,11 @@ impl Handler for EnclaveStartRequest {
                 config.tx_sender,
             ];
 
+            if !config.witnesses.is_empty() {
+                enclave_args.push("--witnesses".to_string());
+                enclave_args.push(join_urls(&config.witnesses));
+            }
+
             if self.no_backup {
                 enclave_args.push("--no-backup".to_string());
             }
@@ -115,6 +125,9 @@ impl Handler for EnclaveStartRequest {
                 tcbinfo_contract,
                 dcap_verifier_contract,
                 &config.node_url,
+                &config.witnesses,
+                config.min_witnesses,
+                config.witness_policy,
                 &config.ws_url,
                 &config.grpc_url,
                 self.no_backup,
@@ -207,6 +220,9 @@ async fn gramine_manifest(
     tcbinfo_contract: AccountId,
     dcap_verifier_contract: AccountId,
     node_url: &Url,
+    witnesses: &[Url],
+    min_witnesses: usize,
+    witness_policy: WitnessPolicy,
     ws_url: &Url,
     grpc_url: &Url,
     no_backup: bool,
@@ -234,6 +250,9 @@ async fn gramine_manifest(
         .arg(format!("-Dfmspc={}", hex::encode(fmspc)))
         .arg(format!("-Dpccs_url={}", pccs_url))
         .arg(format!("-Dnode_url={}", node_url))
+        .arg(format!("-Dwitnesses={}", join_urls(witnesses)))
+        .arg(format!("-Dmin_witnesses={}", min_witnesses))
+        .arg(format!("-Dwitness_policy={}", witness_policy))
         .arg(format!("-Dws_url={}", ws_url))
         .arg(format!("-Dgrpc_url={}", grpc_url))
         .arg(format!("-Dtcbinfo_contract={}", tcbinfo_contract))
@@ -292,3 +311,7 @@ async fn create_gramine_sgx_child(enclave_dir: &Path) -> Result<Child> {
 
     Ok(child)
 }
+
+fn join_urls(urls: &[Url]) -> String {
+    urls.iter().map(Url::as_str).collect::<Vec<_>>().join(",")
+}
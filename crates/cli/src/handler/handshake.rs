@@ -4,7 +4,10 @@ use async_trait::async_trait;
 use color_eyre::{eyre::eyre, owo_colors::OwoColorize, Report, Result};
 use cw_client::{CliClient, CwClient};
 use futures_util::stream::StreamExt;
-use quartz_tm_prover::{config::Config as TmProverConfig, prover::prove};
+use quartz_tm_prover::{
+    config::{Config as TmProverConfig, List},
+    prover::prove,
+};
 use serde_json::json;
 use tendermint_rpc::{query::EventType, HttpClient, SubscriptionClient, WebSocketClient};
 use tracing::{debug, info};
@@ -42,6 +45,29 @@ async fn handshake(args: HandshakeRequest, config: Config) -> Result<String> {
 
     let (trusted_height, trusted_hash) = read_cached_hash_height(&config).await?;
 
+    if args.reset {
+        info!("Running SessionReset");
+
+        let output: WasmdTxResponse = serde_json::from_str(
+            cw_client
+                .tx_execute(
+                    &args.contract.clone(),
+                    &config.chain_id,
+                    2000000,
+                    &config.tx_sender,
+                    iter::once(json!({ "quartz": { "session_reset": {} } })),
+                    "0untrn",
+                )
+                .await
+                .map_err(|err| eyre!(Box::new(err)))?
+                .as_str(),
+        )?;
+        debug!("\n\n SessionReset tx output: {:?}", output);
+
+        block_tx_commit(&tmrpc_client, output.txhash).await?;
+        info!("SessionReset tx committed");
+    }
+
     info!("Running SessionCreate");
 
     let res: serde_json::Value = RelayMessage::SessionCreate {
@@ -77,7 +103,15 @@ async fn handshake(args: HandshakeRequest, config: Config) -> Result<String> {
     // Call tm prover with trusted hash and height
     let prover_config = TmProverConfig {
         primary: config.node_url.as_str().parse()?,
-        witnesses: config.node_url.as_str().parse()?,
+        witnesses: List(
+            config
+                .witnesses
+                .iter()
+                .map(|w| w.as_str().parse())
+                .collect::<Result<_, _>>()?,
+        ),
+        min_witnesses: config.min_witnesses,
+        witness_policy: config.witness_policy,
         trusted_height,
         trusted_hash,
         verbose: "1".parse()?, // TODO: both tm-prover and cli define the same Verbosity struct. Need to define this once and import
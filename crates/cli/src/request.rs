@@ -40,6 +40,7 @@ impl TryFrom<Command> for Request {
             Command::Handshake(args) => Ok(HandshakeRequest {
                 contract: args.contract,
                 unsafe_trust_latest: args.unsafe_trust_latest,
+                reset: args.reset,
             }
             .into()),
             Command::Contract { contract_command } => contract_command.try_into(),
@@ -6,6 +6,8 @@ use crate::request::Request;
 pub struct HandshakeRequest {
     pub contract: AccountId,
     pub unsafe_trust_latest: bool,
+    /// Whether to reset the contract's current session before the handshake.
+    pub reset: bool,
 }
 
 impl From<HandshakeRequest> for Request {
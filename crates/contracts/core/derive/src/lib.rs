@@ -1,19 +1,411 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, AttributeArgs, Data, DeriveInput, Fields, ItemStruct, Lit, Meta, NestedMeta,
+    Path, Type,
+};
 
-#[proc_macro_derive(UserData)]
+/// Implements `HasUserData` by hashing the JSON serialization of the type. The type's name is used
+/// as the `msg_type` that the (domain-separated) user data commits to.
+///
+/// A `u64` field marked with `#[user_data(committed_height)]` is additionally committed to in the
+/// user data as the height of the chain state that the message was produced from, which allows the
+/// contract to reject stale messages (see `LightClientOpts::max_height_lag`).
+#[proc_macro_derive(UserData, attributes(user_data))]
 pub fn user_data_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident;
 
+    let committed_height = match committed_height_field(&input.data) {
+        Ok(field) => field,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let user_data = match committed_height {
+        Some(field) => quote! {
+            ::quartz_contract_core::msg::execute::attested::user_data_json_at_height(
+                self,
+                self.#field,
+            )
+        },
+        None => quote! {
+            ::quartz_contract_core::msg::execute::attested::user_data_json(self)
+        },
+    };
+
+    let msg_type = name.to_string();
+
     let expanded = quote! {
         impl ::quartz_contract_core::msg::execute::attested::HasUserData for #name {
             fn user_data(&self) -> ::quartz_contract_core::state::UserData {
-                ::quartz_contract_core::msg::execute::attested::user_data_json(self)
+                #user_data
+            }
+
+            fn msg_type(&self) -> &'static str {
+                #msg_type
             }
         }
     };
 
     TokenStream::from(expanded)
 }
+
+fn committed_height_field(data: &Data) -> syn::Result<Option<syn::Ident>> {
+    let Data::Struct(data) = data else {
+        return Ok(None);
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Ok(None);
+    };
+
+    let mut committed_height = None;
+    for field in &fields.named {
+        for attr in field.attrs.iter().filter(|a| a.path.is_ident("user_data")) {
+            let Meta::List(list) = attr.parse_meta()? else {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "expected `#[user_data(...)]`",
+                ));
+            };
+
+            for nested in &list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("committed_height") => {
+                        if committed_height.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                attr,
+                                "only one field can be the committed height",
+                            ));
+                        }
+                        committed_height = field.ident.clone();
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            nested,
+                            "unknown `user_data` attribute",
+                        ))
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(committed_height)
+}
+
+/// Generates a quartz message from a single definition of its (validated) domain type.
+///
+/// For a struct `Foo` with named fields, this generates -
+/// - the domain type `Foo` itself (deriving `Clone`, `Debug` and `PartialEq`) along with a `new()`
+///   constructor and a getter for every field,
+/// - a `#[cw_serde]` struct `RawFoo` with the same (public) fields,
+/// - `TryFrom<RawFoo> for Foo`, `From<Foo> for RawFoo` and `HasDomainType for RawFoo`.
+///
+/// A field can have a different raw type using `#[raw(ty = "HexBinary")]`. The field is then
+/// converted using `From`/`Into`, unless a fallible `try_from = "path::to::fn"` (returning a
+/// `StdResult`) and/or an `into = "path::to::fn"` conversion is specified. `#[serde(...)]`
+/// attributes of a field only apply to the raw field.
+///
+/// The attribute takes the following (optional) arguments -
+/// - `validate = "path::to::fn"`: a `fn(&Foo) -> StdResult<()>` that is called after converting a
+///   `RawFoo` (e.g. to check invariants between fields).
+/// - `user_data`: implements `HasUserData` for both `Foo` and `RawFoo` by hashing the JSON
+///   serialization of `RawFoo`, with the type's name as the `msg_type`. As with
+///   `#[derive(UserData)]`, a `u64` field can be marked with `#[user_data(committed_height)]`.
+/// - `msg_type = "foo"`: overrides the `msg_type` of the `user_data` impls.
+///
+/// ```ignore
+/// #[quartz_msg(user_data)]
+/// pub struct Update {
+///     #[raw(ty = "HexBinary")]
+///     ciphertext: Vec<u8>,
+///     #[user_data(committed_height)]
+///     height: u64,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn quartz_msg(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let input = parse_macro_input!(input as ItemStruct);
+
+    match quartz_msg_impl(args, input) {
+        Ok(expanded) => expanded.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+struct MsgArgs {
+    validate: Option<Path>,
+    user_data: bool,
+    msg_type: Option<syn::LitStr>,
+}
+
+impl MsgArgs {
+    fn parse(args: AttributeArgs) -> syn::Result<Self> {
+        let mut validate = None;
+        let mut user_data = false;
+        let mut msg_type = None;
+        for arg in args {
+            match &arg {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("user_data") => {
+                    user_data = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("validate") => {
+                    validate = Some(parse_lit(&nv.lit)?);
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("msg_type") => {
+                    let Lit::Str(lit) = &nv.lit else {
+                        return Err(syn::Error::new_spanned(
+                            &nv.lit,
+                            "expected a string literal",
+                        ));
+                    };
+                    msg_type = Some(lit.clone());
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        "unknown `quartz_msg` argument",
+                    ))
+                }
+            }
+        }
+
+        if msg_type.is_some() && !user_data {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`msg_type` requires the `user_data` argument",
+            ));
+        }
+
+        Ok(Self {
+            validate,
+            user_data,
+            msg_type,
+        })
+    }
+}
+
+#[derive(Default)]
+struct RawField {
+    ty: Option<Type>,
+    try_from: Option<Path>,
+    into: Option<Path>,
+}
+
+impl RawField {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut raw = Self::default();
+        for attr in field.attrs.iter().filter(|a| a.path.is_ident("raw")) {
+            let Meta::List(list) = attr.parse_meta()? else {
+                return Err(syn::Error::new_spanned(attr, "expected `#[raw(...)]`"));
+            };
+
+            for nested in &list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("ty") => {
+                        raw.ty = Some(parse_lit(&nv.lit)?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("try_from") => {
+                        raw.try_from = Some(parse_lit(&nv.lit)?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("into") => {
+                        raw.into = Some(parse_lit(&nv.lit)?);
+                    }
+                    _ => return Err(syn::Error::new_spanned(nested, "unknown `raw` attribute")),
+                }
+            }
+        }
+
+        Ok(raw)
+    }
+}
+
+fn parse_lit<T: syn::parse::Parse>(lit: &Lit) -> syn::Result<T> {
+    let Lit::Str(lit) = lit else {
+        return Err(syn::Error::new_spanned(lit, "expected a string literal"));
+    };
+    lit.parse()
+}
+
+fn quartz_msg_impl(
+    args: AttributeArgs,
+    input: ItemStruct,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let args = MsgArgs::parse(args)?;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`quartz_msg` does not support generic messages",
+        ));
+    }
+    let Fields::Named(named) = &input.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.fields,
+            "`quartz_msg` expects a struct with named fields",
+        ));
+    };
+
+    // `committed_height_field()` works on a `DeriveInput`, so reuse it for the `user_data` attrs
+    let committed_height = committed_height_field(&Data::Struct(syn::DataStruct {
+        struct_token: input.struct_token,
+        fields: input.fields.clone(),
+        semi_token: input.semi_token,
+    }))?;
+
+    let name = &input.ident;
+    let raw_name = format_ident!("Raw{}", name);
+    let vis = &input.vis;
+    let attrs = &input.attrs;
+    let docs: Vec<_> = attrs.iter().filter(|a| a.path.is_ident("doc")).collect();
+
+    let mut fields = Vec::new();
+    let mut raw_fields = Vec::new();
+    let mut getters = Vec::new();
+    let mut to_domain = Vec::new();
+    let mut to_raw = Vec::new();
+    let mut ctor_args = Vec::new();
+    let mut ctor_fields = Vec::new();
+
+    for field in &named.named {
+        let raw = RawField::parse(field)?;
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let field_vis = &field.vis;
+        let field_attrs: Vec<_> = field
+            .attrs
+            .iter()
+            .filter(|a| {
+                !a.path.is_ident("raw")
+                    && !a.path.is_ident("user_data")
+                    && !a.path.is_ident("serde")
+            })
+            .collect();
+        let raw_field_attrs: Vec<_> = field
+            .attrs
+            .iter()
+            .filter(|a| a.path.is_ident("doc") || a.path.is_ident("serde"))
+            .collect();
+        let raw_ty = raw.ty.as_ref().unwrap_or(ty);
+
+        fields.push(quote! { #(#field_attrs)* #field_vis #ident: #ty });
+        raw_fields.push(quote! { #(#raw_field_attrs)* pub #ident: #raw_ty });
+        getters.push(quote! {
+            pub fn #ident(&self) -> &#ty {
+                &self.#ident
+            }
+        });
+        ctor_args.push(quote! { #ident: #ty });
+        ctor_fields.push(quote! { #ident });
+
+        to_domain.push(match (&raw.ty, &raw.try_from) {
+            (_, Some(try_from)) => quote! { #ident: #try_from(value.#ident)? },
+            (Some(_), None) => quote! { #ident: value.#ident.into() },
+            (None, None) => quote! { #ident: value.#ident },
+        });
+        to_raw.push(match (&raw.ty, &raw.into) {
+            (_, Some(into)) => quote! { #ident: #into(value.#ident) },
+            (Some(_), None) => quote! { #ident: value.#ident.into() },
+            (None, None) => quote! { #ident: value.#ident },
+        });
+    }
+
+    let validate = args.validate.map(|validate| quote! { #validate(&msg)?; });
+
+    let user_data = args.user_data.then(|| {
+        let user_data = match &committed_height {
+            Some(field) => quote! {
+                ::quartz_contract_core::msg::execute::attested::user_data_json_at_height(
+                    &raw,
+                    raw.#field,
+                )
+            },
+            None => quote! {
+                ::quartz_contract_core::msg::execute::attested::user_data_json(&raw)
+            },
+        };
+        let msg_type = args
+            .msg_type
+            .as_ref()
+            .map_or_else(|| name.to_string(), syn::LitStr::value);
+
+        quote! {
+            #[allow(unused_qualifications)]
+            impl ::quartz_contract_core::msg::execute::attested::HasUserData for #raw_name {
+                fn user_data(&self) -> ::quartz_contract_core::state::UserData {
+                    let raw = self;
+                    #user_data
+                }
+
+                fn msg_type(&self) -> &'static str {
+                    #msg_type
+                }
+            }
+
+            #[allow(unused_qualifications)]
+            impl ::quartz_contract_core::msg::execute::attested::HasUserData for #name {
+                fn user_data(&self) -> ::quartz_contract_core::state::UserData {
+                    let raw = &#raw_name::from(self.clone());
+                    #user_data
+                }
+
+                fn msg_type(&self) -> &'static str {
+                    #msg_type
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #(#attrs)*
+        #[derive(Clone, Debug, PartialEq)]
+        #vis struct #name {
+            #(#fields,)*
+        }
+
+        impl #name {
+            #[allow(clippy::too_many_arguments)]
+            pub fn new(#(#ctor_args),*) -> Self {
+                Self { #(#ctor_fields),* }
+            }
+
+            #(#getters)*
+        }
+
+        #(#docs)*
+        #[::cosmwasm_schema::cw_serde]
+        #vis struct #raw_name {
+            #(#raw_fields,)*
+        }
+
+        // a message without fields doesn't use `value`
+        #[allow(unused_qualifications, unused_variables)]
+        impl ::core::convert::TryFrom<#raw_name> for #name {
+            type Error = ::cosmwasm_std::StdError;
+
+            fn try_from(value: #raw_name) -> ::core::result::Result<Self, Self::Error> {
+                let msg = Self {
+                    #(#to_domain,)*
+                };
+                #validate
+                Ok(msg)
+            }
+        }
+
+        #[allow(unused_qualifications, unused_variables)]
+        impl ::core::convert::From<#name> for #raw_name {
+            fn from(value: #name) -> Self {
+                Self {
+                    #(#to_raw,)*
+                }
+            }
+        }
+
+        #[allow(unused_qualifications)]
+        impl ::quartz_contract_core::msg::HasDomainType for #raw_name {
+            type DomainType = #name;
+        }
+
+        #user_data
+    })
+}
@@ -0,0 +1,85 @@
+use cosmwasm_std::{HexBinary, StdError, StdResult};
+use quartz_contract_core::msg::{
+    execute::attested::{committed_height, HasUserData},
+    HasDomainType,
+};
+use quartz_contract_core_derive::quartz_msg;
+
+fn to_nonce(raw: HexBinary) -> StdResult<[u8; 32]> {
+    raw.to_array()
+}
+
+fn non_empty(msg: &Update) -> StdResult<()> {
+    if msg.ciphertext().is_empty() {
+        return Err(StdError::generic_err("empty ciphertext"));
+    }
+    Ok(())
+}
+
+/// An attested update.
+#[quartz_msg(user_data, validate = "non_empty")]
+pub struct Update {
+    #[raw(ty = "HexBinary", try_from = "to_nonce")]
+    nonce: [u8; 32],
+    #[raw(ty = "HexBinary")]
+    ciphertext: Vec<u8>,
+    #[user_data(committed_height)]
+    height: u64,
+}
+
+fn raw_update(nonce: &[u8], ciphertext: &[u8]) -> RawUpdate {
+    RawUpdate {
+        nonce: nonce.to_vec().into(),
+        ciphertext: ciphertext.to_vec().into(),
+        height: 42,
+    }
+}
+
+#[test]
+fn test_raw_msg_round_trips_through_domain_type() {
+    let raw = raw_update(&[1; 32], b"secret");
+    let msg: <RawUpdate as HasDomainType>::DomainType =
+        raw.clone().try_into().expect("valid raw msg");
+
+    assert_eq!(msg, Update::new([1; 32], b"secret".to_vec(), 42));
+    assert_eq!(msg.nonce(), &[1; 32]);
+    assert_eq!(RawUpdate::from(msg), raw);
+}
+
+#[test]
+fn test_invalid_raw_msg_is_rejected() {
+    assert!(Update::try_from(raw_update(&[1; 31], b"secret")).is_err());
+    assert!(Update::try_from(raw_update(&[1; 32], b"")).is_err());
+}
+
+#[test]
+fn test_raw_and_domain_msg_have_the_same_user_data() {
+    let raw = raw_update(&[1; 32], b"secret");
+    let msg = Update::try_from(raw.clone()).expect("valid raw msg");
+
+    assert_eq!(msg.user_data(), raw.user_data());
+    assert_eq!(msg.msg_type(), "Update");
+    assert_eq!(committed_height(&msg.user_data()), Some(42));
+}
+
+/// A registration with an optional memo.
+#[quartz_msg(user_data, msg_type = "register")]
+pub struct Register {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+}
+
+#[test]
+fn test_msg_type_and_serde_attrs_apply() {
+    let raw: RawRegister = cosmwasm_std::from_json(r#"{"name":"alice"}"#).expect("memo defaults");
+    let msg = Register::try_from(raw.clone()).expect("valid raw msg");
+
+    assert_eq!(msg, Register::new("alice".to_string(), None));
+    assert_eq!(
+        cosmwasm_std::to_json_string(&raw).expect("serializable"),
+        r#"{"name":"alice"}"#
+    );
+    assert_eq!(msg.msg_type(), "register");
+    assert_eq!(raw.msg_type(), "register");
+}
@@ -11,6 +11,12 @@ pub enum Error {
     RaVerification(#[from] RaVerificationError),
     #[error("Signature verification error: {0}")]
     SignatureVerification(String),
+    #[error("invalid nonce: expected {expected}, got {got}")]
+    InvalidNonce { expected: u64, got: u64 },
+    #[error("envelope expired: sequence number {current} is past {max}")]
+    ExpiredEnvelope { current: u64, max: u64 },
+    #[error("envelope was already processed")]
+    DuplicateEnvelope,
     #[error("Not Secp256K1")]
     K256(K256Error),
     #[error("invalid session nonce or attempt to reset pub_key")]
@@ -19,10 +25,32 @@ pub enum Error {
     InvalidFmspc(String),
     #[error("TCB Info query error: {0}")]
     TcbInfoQueryError(String),
+    #[error("invalid collateral: {0}")]
+    InvalidCollateral(String),
     #[error("DCAP verification query error: {0}")]
     DcapVerificationQueryError(String),
     #[error("contract address mismatch")]
     ContractAddrMismatch,
+    #[error("unauthorized")]
+    Unauthorized,
+    #[error("trusted anchor height ({new}) must be greater than the current one ({current})")]
+    StaleTrustedAnchor { current: u64, new: u64 },
+    #[error("attested message was already processed")]
+    DuplicateAttestedMsg,
+    #[error("{0} is not a registered operator")]
+    UnregisteredOperator(String),
+    #[error("no quorum of operators is configured")]
+    QuorumNotConfigured,
+    #[error("operator's enclave pub key doesn't match the session's")]
+    OperatorPubKeyMismatch,
+    #[error("stale attested message: committed at height {committed}, current height {current} (max height lag {max_height_lag})")]
+    StaleAttestedMsg {
+        committed: u64,
+        current: u64,
+        max_height_lag: u64,
+    },
+    #[error("attested app output doesn't commit to the height it was produced at")]
+    MissingCommittedHeight,
 }
 
 impl From<K256Error> for Error {
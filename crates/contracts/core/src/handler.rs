@@ -1,5 +1,7 @@
 pub mod execute;
 pub mod instantiate;
+pub mod migrate;
+pub mod query;
 
 use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 
@@ -1,10 +1,17 @@
 pub mod attested;
+pub mod envelope;
+pub mod register_operator;
 pub mod sequenced;
 pub mod session_create;
+pub mod session_reset;
 pub mod session_set_pub_key;
+pub mod set_trusted_anchor;
 pub mod signed;
+pub mod update_enclave_identity;
+pub mod update_quorum;
+pub mod update_tcb_policy;
 
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response};
 
 use crate::{
     error::Error,
@@ -23,6 +30,51 @@ where
         match self {
             Execute::SessionCreate(msg) => msg.handle(deps, env, info),
             Execute::SessionSetPubKey(msg) => msg.handle(deps, env, info),
+            Execute::SessionReset(msg) => msg.handle(deps, env, info),
+            Execute::SetTrustedAnchor(msg) => msg.handle(deps, env, info),
+            Execute::UpdateEnclaveIdentity(msg) => msg.handle(deps, env, info),
+            Execute::UpdateTcbPolicy(msg) => msg.handle(deps, env, info),
+            Execute::UpdateQuorum(msg) => msg.handle(deps, env, info),
+            Execute::RegisterOperator(msg) => msg.handle(deps, env, info),
         }
     }
 }
+
+/// Fails unless the sender is the contract admin (i.e. the account allowed to migrate it).
+pub(crate) fn ensure_admin(deps: Deps<'_>, env: &Env, info: &MessageInfo) -> Result<(), Error> {
+    // ASSERT info.sender == contract admin
+    let admin = deps
+        .querier
+        .query_wasm_contract_info(env.contract.address.clone())?
+        .admin;
+    if admin.as_ref() != Some(&info.sender) {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(())
+}
+
+/// Mocks the contract info query so that `admin` is the admin (and creator) of the contract.
+#[cfg(test)]
+pub(crate) fn mock_admin(
+    querier: &mut cosmwasm_std::testing::MockQuerier,
+    admin: &cosmwasm_std::Addr,
+) {
+    use cosmwasm_std::{
+        to_json_binary, ContractInfoResponse, ContractResult, SystemError, SystemResult, WasmQuery,
+    };
+
+    let admin = admin.clone();
+    querier.update_wasm(move |query| match query {
+        WasmQuery::ContractInfo { .. } => {
+            let info =
+                ContractInfoResponse::new(1, admin.clone(), Some(admin.clone()), false, None);
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&info).expect("serializable"),
+            ))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: format!("{query:?}"),
+        }),
+    });
+}
@@ -1,11 +1,15 @@
 use ciborium::{from_reader as from_cbor_slice, into_writer as into_cbor, Value as CborValue};
 use cosmwasm_std::{
-    to_json_binary, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response, StdResult, WasmQuery,
+    to_json_binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, QueryRequest, Response,
+    StdResult, Storage, WasmQuery,
+};
+use cw_storage_plus::PrefixBound;
+use quartz_dcap_verifier_msgs::{QueryMsg as DcapVerifierQueryMsg, VerificationReport};
+use quartz_tcbinfo_msgs::{
+    GetCrlResponse, GetQeIdentityResponse, GetTcbInfoResponse, QueryMsg as TcbInfoQueryMsg,
 };
-use quartz_dcap_verifier_msgs::QueryMsg as DcapVerifierQueryMsg;
-use quartz_tcbinfo_msgs::{GetTcbInfoResponse, QueryMsg as TcbInfoQueryMsg};
 use quartz_tee_ra::{
-    intel_sgx::dcap::{Collateral, TrustedIdentity, TrustedMrEnclaveIdentity},
+    intel_sgx::dcap::{pck_crl_issuer, tcb_policy::TcbPolicy, Collateral, TrustedIdentity},
     Error as RaVerificationError,
 };
 use serde::{de::DeserializeOwned, Serialize};
@@ -14,9 +18,14 @@ use crate::{
     error::Error,
     handler::Handler,
     msg::execute::attested::{
-        Attestation, Attested, DcapAttestation, HasUserData, MockAttestation, Noop, Quote,
+        committed_height, Attestation, Attested, DcapAttestation, HasUserData, MockAttestation,
+        Noop, Quote, RawAttested, RawNoop, UserDataDomain,
+    },
+    msg::HasDomainType,
+    state::{
+        Config, EnclaveIdentity, PendingOutput, Quorum, UserData, ATTESTED_OUTPUTS, CONFIG,
+        OPERATOR_SESSIONS, PENDING_OUTPUTS, PRUNE_LIMIT, QUORUM, SESSION,
     },
-    state::CONFIG,
 };
 
 fn query_contract<T: DeserializeOwned>(
@@ -32,14 +41,16 @@ fn query_contract<T: DeserializeOwned>(
     deps.querier.query(&request)
 }
 
+fn tcbinfo_contract(deps: Deps<'_>) -> Result<String, Error> {
+    let config = CONFIG.load(deps.storage).map_err(Error::Std)?;
+    Ok(config
+        .tcbinfo_contract()
+        .expect("TcbInfo contract address is required for DCAP")
+        .to_string())
+}
+
 fn query_tcbinfo(deps: Deps<'_>, fmspc: String) -> Result<GetTcbInfoResponse, Error> {
-    let tcbinfo_addr = {
-        let config = CONFIG.load(deps.storage).map_err(Error::Std)?;
-        config
-            .tcbinfo_contract()
-            .expect("TcbInfo contract address is required for DCAP")
-            .to_string()
-    };
+    let tcbinfo_addr = tcbinfo_contract(deps)?;
 
     let fmspc_bytes =
         hex::decode(&fmspc).map_err(|_| Error::InvalidFmspc("Invalid FMSPC format".to_string()))?;
@@ -53,6 +64,28 @@ fn query_tcbinfo(deps: Deps<'_>, fmspc: String) -> Result<GetTcbInfoResponse, Er
         .map_err(|err| Error::TcbInfoQueryError(err.to_string()))
 }
 
+/// Queries the tcbinfo contract for the given collateral (i.e. the QE identity or one of the CRLs)
+fn query_collateral<T: DeserializeOwned>(
+    deps: Deps<'_>,
+    query_msg: TcbInfoQueryMsg,
+) -> Result<T, Error> {
+    query_contract(deps, tcbinfo_contract(deps)?, &query_msg)
+        .map_err(|err| Error::TcbInfoQueryError(err.to_string()))
+}
+
+/// Replaces the value of the `field` of the (CBOR serialized) collateral
+fn substitute(collateral: &mut CborValue, field: &str, value: CborValue) -> Result<(), Error> {
+    let CborValue::Map(map) = collateral else {
+        return Err(Error::InvalidCollateral("not a map".to_string()));
+    };
+    let (_, v) = map
+        .iter_mut()
+        .find(|(k, _)| k == &CborValue::Text(field.to_string()))
+        .ok_or_else(|| Error::InvalidCollateral(format!("missing {field}")))?;
+    *v = value;
+    Ok(())
+}
+
 fn to_cbor_vec<T: Serialize>(value: &T) -> Vec<u8> {
     let mut buffer = Vec::new();
     into_cbor(&value, &mut buffer).expect("Serialization failed");
@@ -62,13 +95,15 @@ fn to_cbor_vec<T: Serialize>(value: &T) -> Vec<u8> {
 fn query_dcap_verifier(
     deps: Deps<'_>,
     quote: Quote,
-    mr_enclave: impl Into<TrustedIdentity>,
+    identities: Vec<TrustedIdentity>,
+    tcb_policy: &TcbPolicy,
     updated_collateral: Collateral,
-) -> Result<(), Error> {
+) -> Result<VerificationReport, Error> {
     let query_msg = DcapVerifierQueryMsg::VerifyDcapAttestation {
         quote: quote.as_ref().to_vec().into(),
         collateral: to_cbor_vec(&updated_collateral).into(),
-        identities: Some(to_cbor_vec(&[mr_enclave.into()])),
+        identities: Some(to_cbor_vec(&identities)),
+        tcb_policy: Some(to_cbor_vec(tcb_policy)),
     };
 
     let dcap_verifier_contract = {
@@ -86,11 +121,20 @@ fn query_dcap_verifier(
 impl Handler for DcapAttestation {
     fn handle(self, deps: DepsMut<'_>, _env: &Env, _info: &MessageInfo) -> Result<Response, Error> {
         let (quote, collateral) = self.clone().into_tuple();
-        let mr_enclave = TrustedMrEnclaveIdentity::new(
-            self.mr_enclave().into(),
-            [""; 0],
-            ["INTEL-SA-00334", "INTEL-SA-00615"],
-        );
+
+        // verify against all identities that the config trusts, or just the attested MRENCLAVE
+        // if there's no config yet (i.e. InstantiateMsg, which checks the identity itself)
+        let (identities, tcb_policy) = match CONFIG.may_load(deps.storage)? {
+            Some(config) => {
+                let config = Config::try_from(config)?;
+                (config.trusted_identities(), config.tcb_policy().clone())
+            }
+            None => (
+                vec![EnclaveIdentity::MrEnclave(self.mr_enclave())],
+                TcbPolicy::default(),
+            ),
+        };
+        let identities = identities.into_iter().map(Into::into).collect();
 
         // Retrieve the FMSPC from the collateral
         let fmspc_hex = collateral.tcb_info().to_string();
@@ -98,6 +142,21 @@ impl Handler for DcapAttestation {
         // Query the tcbinfo contract with the FMSPC retrieved and validated
         let tcb_info_response = query_tcbinfo(deps.as_ref(), fmspc_hex)?;
 
+        // The QE identity and the CRLs are also taken from chain state, where they were verified
+        // against Intel's root CA, rather than trusting whatever the enclave shipped
+        let qe_identity_response: GetQeIdentityResponse =
+            query_collateral(deps.as_ref(), TcbInfoQueryMsg::GetQeIdentity {})?;
+        let root_crl_response: GetCrlResponse =
+            query_collateral(deps.as_ref(), TcbInfoQueryMsg::GetRootCrl {})?;
+        // The PCK CRL is looked up by the issuer of the quote's own PCK certificate, so an
+        // enclave can't dodge revocation by shipping the CRL of a different CA
+        let pck_crl_response: GetCrlResponse = query_collateral(
+            deps.as_ref(),
+            TcbInfoQueryMsg::GetPckCrl {
+                issuer: pck_crl_issuer(&quote)?,
+            },
+        )?;
+
         // Serialize the existing collateral
         let collateral_serialized = to_cbor_vec(&collateral);
         let mut collateral_value: CborValue = from_cbor_slice(collateral_serialized.as_slice())
@@ -105,19 +164,27 @@ impl Handler for DcapAttestation {
                 Error::TcbInfoQueryError(format!("Failed to serialize collateral: {}", e))
             })?;
 
-        // Update the tcb_info in the serialized data
-        fn try_get_tcb_info(collateral_value: &mut CborValue) -> Option<&mut CborValue> {
-            if let CborValue::Map(map) = collateral_value {
-                return map
-                    .iter_mut()
-                    .find(|(k, _)| k == &CborValue::Text("tcb_info".to_string()))
-                    .map(|(_, v)| v);
-            }
-            None
-        }
-
-        let tcb_info_value = try_get_tcb_info(&mut collateral_value).expect("infallible serde");
-        *tcb_info_value = CborValue::Text(tcb_info_response.tcb_info.to_string());
+        // Update the collateral in the serialized data
+        substitute(
+            &mut collateral_value,
+            "tcb_info",
+            CborValue::Text(tcb_info_response.tcb_info),
+        )?;
+        substitute(
+            &mut collateral_value,
+            "qe_identity",
+            CborValue::Text(qe_identity_response.qe_identity),
+        )?;
+        substitute(
+            &mut collateral_value,
+            "root_ca_crl",
+            CborValue::Bytes(root_crl_response.crl.into()),
+        )?;
+        substitute(
+            &mut collateral_value,
+            "pck_crl",
+            CborValue::Bytes(pck_crl_response.crl.into()),
+        )?;
 
         // Deserialize back into a Collateral
         let collateral_serialized = to_cbor_vec(&collateral_value);
@@ -126,8 +193,14 @@ impl Handler for DcapAttestation {
                 Error::TcbInfoQueryError(format!("Failed to deserialize updated collateral: {}", e))
             })?;
 
-        query_dcap_verifier(deps.as_ref(), quote, mr_enclave, updated_collateral)
-            .map(|_| Response::default())
+        query_dcap_verifier(
+            deps.as_ref(),
+            quote,
+            identities,
+            &tcb_policy,
+            updated_collateral,
+        )
+        .map(|_| Response::default())
     }
 }
 
@@ -142,28 +215,142 @@ impl Handler for MockAttestation {
     }
 }
 
-impl<M, A> Handler for Attested<M, A>
+/// The outcome of handling an attested app output (see [`Attested::handle_output`]).
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttestedOutcome<T> {
+    /// The output took effect (i.e. reached the quorum, if one is configured) and the app must
+    /// apply it.
+    Applied(Response, T),
+    /// The operator's submission was recorded, but the output hasn't reached the quorum yet and
+    /// must not be applied.
+    Pending(Response),
+}
+
+/// Records the sender's approval of the output and returns the number of approvals it has from the
+/// quorum's operators. The output must have been attested for the sender (see the `Attested`
+/// handler) and a replica only ever attests for the one operator it was first registered for, so
+/// every approval comes from a distinct replica. Approvals older than the quorum's window are
+/// discarded.
+fn approve_output(
+    deps: DepsMut<'_>,
+    env: &Env,
+    info: &MessageInfo,
+    quorum: &Quorum,
+    user_data: &UserData,
+) -> Result<u32, Error> {
+    // only operators whose replica holds the current session key count towards the quorum
+    let session_pub_key = SESSION
+        .may_load(deps.storage)?
+        .and_then(|session| session.pub_key());
+    let operator_pub_key = OPERATOR_SESSIONS.may_load(deps.storage, &info.sender)?;
+    if !quorum.is_operator(&info.sender)
+        || operator_pub_key.is_none()
+        || operator_pub_key != session_pub_key
+    {
+        return Err(Error::UnregisteredOperator(info.sender.to_string()));
+    }
+
+    let mut pending = PENDING_OUTPUTS
+        .may_load(deps.storage, user_data)?
+        .filter(|pending| !pending.is_expired(quorum.window(), env.block.height))
+        .unwrap_or_else(|| PendingOutput::new(env.block.height));
+    if !pending.approve(info.sender.clone()) {
+        return Err(Error::DuplicateAttestedMsg);
+    }
+
+    let approvals = pending.approvals_in(quorum) as u32;
+    if approvals >= quorum.threshold() {
+        PENDING_OUTPUTS.remove(deps.storage, user_data);
+    } else {
+        PENDING_OUTPUTS.save(deps.storage, user_data, &pending)?;
+    }
+
+    Ok(approvals)
+}
+
+impl<M, A> Attested<M, A>
 where
-    M: Handler + HasUserData,
+    M: HasUserData,
     A: Handler + HasUserData + Attestation,
 {
-    fn handle(
+    /// Verifies the attested message and records it (or the sender's approval, if it requires a
+    /// quorum). Unless the message is pending a quorum, it is then handed to `apply`.
+    fn handle_with<T>(
         self,
         mut deps: DepsMut<'_>,
         env: &Env,
         info: &MessageInfo,
-    ) -> Result<Response, Error> {
+        apply: impl FnOnce(M, DepsMut<'_>, &Env, &MessageInfo) -> Result<(Response, T), Error>,
+    ) -> Result<AttestedOutcome<T>, Error> {
         let (msg, attestation) = self.into_tuple();
-        if msg.user_data() != attestation.user_data() {
+        let quorum = QUORUM
+            .may_load(deps.storage)?
+            .filter(|_| msg.requires_quorum());
+
+        // the enclave attests to the user data bound to this chain and contract, so an attested
+        // message can't be replayed against another deployment
+        let domain = UserDataDomain::from_env(env);
+        // replicas share the session key and MRENCLAVE, so an output that requires a quorum must
+        // also be bound to the operator of the replica that produced it (i.e. the sender) - an
+        // operator can't relay another replica's output as its own approval
+        let attested_domain = match quorum {
+            Some(_) => domain.clone().with_operator(info.sender.to_string()),
+            None => domain.clone(),
+        };
+        if msg.user_data_for(&attested_domain) != attestation.user_data() {
             return Err(RaVerificationError::UserDataMismatch.into());
         }
+        // the output itself, which is the same for all replicas
+        let user_data = msg.user_data_for(&domain);
+
+        // the user data commits to the message, so a relayer can't submit the same attested
+        // message twice
+        let height = committed_height(&user_data);
+        let key = (height.unwrap_or_default(), user_data.as_slice());
+        if ATTESTED_OUTPUTS.has(deps.storage, key) {
+            return Err(Error::DuplicateAttestedMsg);
+        }
 
+        let mut min_height = None;
         if let Some(config) = CONFIG.may_load(deps.storage)? {
             // if we weren't able to load then the context was from InstantiateMsg so we don't fail
-            // in such cases, the InstantiateMsg handler will verify that the mr_enclave matches
-            if config.mr_enclave() != attestation.mr_enclave() {
+            // in such cases, the InstantiateMsg handler will verify that the enclave is trusted
+            let config: Config = config.try_into()?;
+            if !config.trusts(&attestation) {
                 return Err(RaVerificationError::MrEnclaveMismatch.into());
             }
+
+            // reject messages that were produced from chain state that is too old, app outputs
+            // (see `HasUserData::requires_quorum`) must commit to that height
+            let max_height_lag = config.light_client_opts().max_height_lag();
+            match height {
+                Some(committed) if env.block.height.saturating_sub(committed) > max_height_lag => {
+                    return Err(Error::StaleAttestedMsg {
+                        committed,
+                        current: env.block.height,
+                        max_height_lag,
+                    });
+                }
+                None if msg.requires_quorum() => return Err(Error::MissingCommittedHeight),
+                _ => {}
+            }
+            min_height = Some(env.block.height.saturating_sub(max_height_lag));
+        }
+
+        // app outputs only take effect once a quorum of operators submitted the same output
+        let approval = match quorum {
+            Some(quorum) => Some((
+                approve_output(deps.branch(), env, info, &quorum, &user_data)?,
+                quorum.threshold(),
+            )),
+            None => None,
+        };
+        let pending = matches!(approval, Some((approvals, threshold)) if approvals < threshold);
+        if !pending {
+            ATTESTED_OUTPUTS.save(deps.storage, key, &Empty {})?;
+            if let Some(min_height) = min_height {
+                prune_stale_outputs(deps.storage, min_height)?;
+            }
         }
 
         // handle message first, this has 2 benefits -
@@ -171,12 +358,101 @@ where
         // 2. we allow the message handler to make changes to the config so that the attestation
         //    handler can use those changes, e.g. InstantiateMsg
         // return response from msg handle to include pub_key attribute
-        let res_msg = Handler::handle(msg, deps.branch(), env, info)?;
+        let (mut res_msg, applied) = if pending {
+            (Response::new(), None)
+        } else {
+            let (res_msg, applied) = apply(msg, deps.branch(), env, info)?;
+            (res_msg, Some(applied))
+        };
+        if let Some((approvals, threshold)) = approval {
+            res_msg = res_msg
+                .add_attribute("approvals", approvals.to_string())
+                .add_attribute("threshold", threshold.to_string());
+        }
         let res_attest = Handler::handle(attestation, deps, env, info)?;
 
-        Ok(res_msg
+        let res = res_msg
             .add_events(res_attest.events)
-            .add_attributes(res_attest.attributes))
+            .add_attributes(res_attest.attributes);
+        Ok(match applied {
+            Some(applied) => AttestedOutcome::Applied(res, applied),
+            None => AttestedOutcome::Pending(res),
+        })
+    }
+}
+
+/// Forgets (up to [`PRUNE_LIMIT`]) accepted messages that committed to a height below
+/// `min_height`, which would be rejected as stale anyway. Messages that didn't commit to a height
+/// are kept.
+fn prune_stale_outputs(storage: &mut dyn Storage, min_height: u64) -> StdResult<()> {
+    let stale: Vec<_> = ATTESTED_OUTPUTS
+        .prefix_range(
+            storage,
+            Some(PrefixBound::inclusive(1)),
+            Some(PrefixBound::exclusive(min_height)),
+            Order::Ascending,
+        )
+        .take(PRUNE_LIMIT)
+        .map(|entry| entry.map(|(key, _)| key))
+        .collect::<StdResult<_>>()?;
+
+    for (height, user_data) in stale {
+        ATTESTED_OUTPUTS.remove(storage, (height, &user_data));
+    }
+    Ok(())
+}
+
+impl<T, A> Attested<Noop<T>, A>
+where
+    T: HasUserData,
+    A: Handler + HasUserData + Attestation,
+{
+    /// Handles an attested app output, which only takes effect once a quorum of operators
+    /// submitted it (if the contract has a `Quorum` configured). The app must only apply the
+    /// output if it is returned, i.e. [`AttestedOutcome::Applied`].
+    pub fn handle_output(
+        self,
+        deps: DepsMut<'_>,
+        env: &Env,
+        info: &MessageInfo,
+    ) -> Result<AttestedOutcome<T>, Error> {
+        self.handle_with(deps, env, info, |Noop(output), _, _, _| {
+            Ok((Response::default(), output))
+        })
+    }
+}
+
+impl<T, RA> RawAttested<RawNoop<T>, RA>
+where
+    T: Serialize + HasUserData,
+    RA: HasDomainType,
+    RA::DomainType: Handler + HasUserData + Attestation,
+{
+    /// Same as [`Attested::handle_output`] for the raw attested app output.
+    pub fn handle_output(
+        self,
+        deps: DepsMut<'_>,
+        env: &Env,
+        info: &MessageInfo,
+    ) -> Result<AttestedOutcome<T>, Error> {
+        let attested: Attested<Noop<T>, RA::DomainType> = self.try_into()?;
+        attested.handle_output(deps, env, info)
+    }
+}
+
+impl<M, A> Handler for Attested<M, A>
+where
+    M: Handler + HasUserData,
+    A: Handler + HasUserData + Attestation,
+{
+    fn handle(self, deps: DepsMut<'_>, env: &Env, info: &MessageInfo) -> Result<Response, Error> {
+        let outcome = self.handle_with(deps, env, info, |msg, deps, env, info| {
+            Ok((Handler::handle(msg, deps, env, info)?, ()))
+        })?;
+
+        match outcome {
+            AttestedOutcome::Applied(res, ()) | AttestedOutcome::Pending(res) => Ok(res),
+        }
     }
 }
 
@@ -190,3 +466,353 @@ impl<T> Handler for Noop<T> {
         Ok(Response::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        testing::{message_info, mock_dependencies, mock_env},
+        Addr,
+    };
+
+    use super::*;
+    use crate::{
+        msg::execute::{
+            attested::{user_data_json, user_data_json_at_height},
+            register_operator::RegisterOperator,
+        },
+        state::{LightClientOpts, Session, DEFAULT_MAX_HEIGHT_LAG},
+    };
+
+    #[derive(Debug)]
+    struct Output(UserData);
+
+    impl Handler for Output {
+        fn handle(
+            self,
+            _deps: DepsMut<'_>,
+            _env: &Env,
+            _info: &MessageInfo,
+        ) -> Result<Response, Error> {
+            Ok(Response::default())
+        }
+    }
+
+    impl HasUserData for Output {
+        fn user_data(&self) -> UserData {
+            self.0
+        }
+
+        fn msg_type(&self) -> &'static str {
+            "output"
+        }
+    }
+
+    fn attested_for(
+        user_data: UserData,
+        domain: &UserDataDomain,
+    ) -> Attested<Output, MockAttestation> {
+        let output = Output(user_data);
+        let attested_user_data = output.user_data_for(domain);
+        Attested::new(output, MockAttestation(attested_user_data))
+    }
+
+    fn attested(user_data: UserData) -> Attested<Output, MockAttestation> {
+        attested_for(user_data, &UserDataDomain::from_env(&mock_env()))
+    }
+
+    #[test]
+    fn test_attested_msg_replay_is_rejected() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&deps.api.addr_make("relayer"), &[]);
+
+        attested([1; 64])
+            .handle(deps.as_mut(), &env, &info)
+            .expect("first submission");
+        attested([2; 64])
+            .handle(deps.as_mut(), &env, &info)
+            .expect("different msg");
+
+        let err = attested([1; 64])
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("replayed msg");
+        assert!(matches!(err, Error::DuplicateAttestedMsg));
+    }
+
+    #[test]
+    fn test_stale_attested_msg_is_rejected() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = message_info(&deps.api.addr_make("relayer"), &[]);
+
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts")
+                .with_max_height_lag(10);
+        let config = Config::new(Default::default(), light_client_opts, None, None);
+        CONFIG
+            .save(deps.as_mut().storage, &config.into())
+            .expect("config saved");
+
+        env.block.height = 110;
+        attested(user_data_json_at_height(&"fresh", 100))
+            .handle(deps.as_mut(), &env, &info)
+            .expect("within max height lag");
+
+        let err = attested(user_data_json_at_height(&"stale", 99))
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("stale msg");
+        assert!(matches!(
+            err,
+            Error::StaleAttestedMsg {
+                committed: 99,
+                current: 110,
+                max_height_lag: 10
+            }
+        ));
+    }
+
+    #[test]
+    fn test_stale_attested_msgs_are_pruned() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = message_info(&deps.api.addr_make("relayer"), &[]);
+
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts")
+                .with_max_height_lag(10);
+        let config = Config::new(Default::default(), light_client_opts, None, None);
+        CONFIG
+            .save(deps.as_mut().storage, &config.into())
+            .expect("config saved");
+        let accepted = |storage: &dyn Storage| {
+            ATTESTED_OUTPUTS
+                .keys(storage, None, None, Order::Ascending)
+                .map(|key| key.expect("valid key").0)
+                .collect::<Vec<_>>()
+        };
+
+        env.block.height = 100;
+        attested(user_data_json(&"no height"))
+            .handle(deps.as_mut(), &env, &info)
+            .expect("msg without committed height");
+        for height in 95..=100 {
+            attested(user_data_json_at_height(&height, height))
+                .handle(deps.as_mut(), &env, &info)
+                .expect("within max height lag");
+        }
+        assert_eq!(accepted(&deps.storage), [0, 95, 96, 97, 98, 99, 100]);
+
+        // only the msgs that would be rejected as stale are pruned
+        env.block.height = 107;
+        attested(user_data_json_at_height(&"fresh", 107))
+            .handle(deps.as_mut(), &env, &info)
+            .expect("within max height lag");
+        assert_eq!(accepted(&deps.storage), [0, 97, 98, 99, 100, 107]);
+    }
+
+    #[test]
+    fn test_app_output_must_commit_to_a_fresh_height() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = message_info(&deps.api.addr_make("relayer"), &[]);
+
+        // the max height lag is enforced by default
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts");
+        let config = Config::new(Default::default(), light_client_opts, None, None);
+        CONFIG
+            .save(deps.as_mut().storage, &config.into())
+            .expect("config saved");
+        let app_output = |user_data| {
+            let output = Noop(Output(user_data));
+            let attested_user_data = output.user_data_for(&UserDataDomain::from_env(&mock_env()));
+            Attested::new(output, MockAttestation(attested_user_data))
+        };
+
+        env.block.height = 1000;
+        app_output(user_data_json_at_height(
+            &"fresh",
+            1000 - DEFAULT_MAX_HEIGHT_LAG,
+        ))
+        .handle(deps.as_mut(), &env, &info)
+        .expect("within default max height lag");
+
+        let err = app_output(user_data_json_at_height(
+            &"stale",
+            999 - DEFAULT_MAX_HEIGHT_LAG,
+        ))
+        .handle(deps.as_mut(), &env, &info)
+        .expect_err("stale app output");
+        assert!(matches!(err, Error::StaleAttestedMsg { .. }));
+
+        let err = app_output(user_data_json(&"no height"))
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("app output without committed height");
+        assert!(matches!(err, Error::MissingCommittedHeight));
+    }
+
+    #[test]
+    fn test_attested_msg_from_any_trusted_identity_is_accepted() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&deps.api.addr_make("relayer"), &[]);
+
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts");
+        let config = Config::new([1; 32], light_client_opts, None, None);
+        CONFIG
+            .save(deps.as_mut().storage, &config.clone().into())
+            .expect("config saved");
+
+        // the mock enclave's MRENCLAVE isn't trusted
+        let err = attested([1; 64])
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("untrusted enclave");
+        assert!(matches!(
+            err,
+            Error::RaVerification(RaVerificationError::MrEnclaveMismatch)
+        ));
+
+        // trusting the mock enclave's signer with a higher min SVN isn't enough
+        let signer = |min_isv_svn| EnclaveIdentity::MrSigner {
+            mr_signer: Default::default(),
+            isv_prod_id: 0,
+            min_isv_svn,
+        };
+        let config = config.with_identities(vec![signer(1)]);
+        CONFIG
+            .save(deps.as_mut().storage, &config.clone().into())
+            .expect("config saved");
+        attested([2; 64])
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("SVN below minimum");
+
+        let config = config.with_identities(vec![EnclaveIdentity::MrEnclave([2; 32]), signer(0)]);
+        CONFIG
+            .save(deps.as_mut().storage, &config.into())
+            .expect("config saved");
+        attested([3; 64])
+            .handle(deps.as_mut(), &env, &info)
+            .expect("trusted signer");
+    }
+
+    #[test]
+    fn test_attested_msg_for_another_deployment_is_rejected() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&deps.api.addr_make("relayer"), &[]);
+
+        let domain = UserDataDomain::from_env(&env);
+        let other_contract = UserDataDomain::new(
+            domain.chain_id(),
+            Some(deps.api.addr_make("other").to_string()),
+        );
+        let other_chain = UserDataDomain::new("other-chain", domain.contract().map(String::from));
+
+        for other in [
+            other_contract,
+            other_chain,
+            domain.clone().without_contract(),
+        ] {
+            let err = attested_for([1; 64], &other)
+                .handle(deps.as_mut(), &env, &info)
+                .expect_err("attested for another deployment");
+            assert!(matches!(
+                err,
+                Error::RaVerification(RaVerificationError::UserDataMismatch)
+            ));
+        }
+
+        attested_for([1; 64], &domain)
+            .handle(deps.as_mut(), &env, &info)
+            .expect("attested for this deployment");
+    }
+
+    #[test]
+    fn test_output_takes_effect_once_quorum_is_reached() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let [alice, bob, carol] = ["alice", "bob", "carol"].map(|name| deps.api.addr_make(name));
+
+        let session = Session::create([1; 32])
+            .with_pub_key([1; 32], vec![2; 33])
+            .expect("matching nonce");
+        SESSION
+            .save(deps.as_mut().storage, &session)
+            .expect("session saved");
+        let quorum = Quorum::new(
+            vec![alice.to_string(), bob.to_string(), carol.to_string()],
+            2,
+            10,
+        )
+        .expect("valid quorum");
+        QUORUM
+            .save(deps.as_mut().storage, &quorum)
+            .expect("quorum saved");
+
+        for (operator, pub_key) in [(&alice, vec![2; 33]), (&bob, vec![2; 33])] {
+            RegisterOperator::new(operator.to_string(), pub_key)
+                .handle(deps.as_mut(), &env, &message_info(operator, &[]))
+                .expect("operator registered");
+        }
+        let err = RegisterOperator::new(carol.to_string(), vec![3; 33])
+            .handle(deps.as_mut(), &env, &message_info(&carol, &[]))
+            .expect_err("replica without the session key");
+        assert!(matches!(err, Error::OperatorPubKeyMismatch));
+
+        // each replica attests to its output for its own operator
+        let output = |operator: &Addr, user_data: UserData| {
+            let output = Noop(Output(user_data));
+            let domain = UserDataDomain::from_env(&mock_env()).with_operator(operator.to_string());
+            let attested_user_data = output.user_data_for(&domain);
+            Attested::new(output, MockAttestation(attested_user_data))
+        };
+        let submit = |deps: DepsMut<'_>, env: &Env, operator: &Addr, user_data: UserData| {
+            output(operator, user_data).handle_output(deps, env, &message_info(operator, &[]))
+        };
+
+        let err = submit(deps.as_mut(), &env, &carol, [1; 64]).expect_err("not registered");
+        assert!(matches!(err, Error::UnregisteredOperator(_)));
+
+        let outcome = submit(deps.as_mut(), &env, &alice, [1; 64]).expect("first approval");
+        assert!(matches!(outcome, AttestedOutcome::Pending(_)));
+        let err = submit(deps.as_mut(), &env, &alice, [1; 64]).expect_err("same operator");
+        assert!(matches!(err, Error::DuplicateAttestedMsg));
+
+        // an operator can't approve an output that was attested for another operator's replica
+        let err = output(&alice, [1; 64])
+            .handle(deps.as_mut(), &env, &message_info(&bob, &[]))
+            .expect_err("relayed output");
+        assert!(matches!(
+            err,
+            Error::RaVerification(RaVerificationError::UserDataMismatch)
+        ));
+        // nor an output that isn't bound to any operator
+        let unbound_output = Noop(Output([1; 64]));
+        let unbound_user_data = unbound_output.user_data_for(&UserDataDomain::from_env(&env));
+        let err = Attested::new(unbound_output, MockAttestation(unbound_user_data))
+            .handle(deps.as_mut(), &env, &message_info(&bob, &[]))
+            .expect_err("unbound output");
+        assert!(matches!(
+            err,
+            Error::RaVerification(RaVerificationError::UserDataMismatch)
+        ));
+
+        let outcome = submit(deps.as_mut(), &env, &bob, [1; 64]).expect("second approval");
+        assert!(
+            matches!(outcome, AttestedOutcome::Applied(_, Output(user_data)) if user_data == [1; 64])
+        );
+        let err = submit(deps.as_mut(), &env, &alice, [1; 64]).expect_err("already took effect");
+        assert!(matches!(err, Error::DuplicateAttestedMsg));
+
+        // approvals outside of the window don't count
+        submit(deps.as_mut(), &env, &alice, [2; 64]).expect("first approval");
+        env.block.height += 11;
+        let outcome = submit(deps.as_mut(), &env, &bob, [2; 64]).expect("approval after window");
+        assert!(matches!(outcome, AttestedOutcome::Pending(_)));
+    }
+}
@@ -0,0 +1,152 @@
+use cosmwasm_std::{DepsMut, Empty, Env, MessageInfo, Order, Response, StdResult};
+use cw_storage_plus::PrefixBound;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::Error,
+    handler::Handler,
+    msg::execute::envelope::EncryptedEnvelope,
+    state::{ENVELOPES, PRUNE_LIMIT, SEQUENCE_NUM},
+};
+
+/// Checks that the envelope is meant for this contract and hasn't expired, i.e. that the sequence
+/// number that the request is being assigned doesn't exceed the envelope's. This is meant to be
+/// wrapped in `Sequenced` (which bumps the sequence number before handling the envelope).
+///
+/// Every envelope is only accepted once. Expired envelopes are forgotten (up to [`PRUNE_LIMIT`]
+/// per envelope), as they can't be accepted anymore anyway.
+impl<T> Handler for EncryptedEnvelope<T> {
+    fn handle(self, deps: DepsMut<'_>, env: &Env, _info: &MessageInfo) -> Result<Response, Error> {
+        let aad = self.aad();
+        if aad.contract != env.contract.address.as_str() {
+            return Err(Error::ContractAddrMismatch);
+        }
+
+        let seq_num = SEQUENCE_NUM.may_load(deps.storage)?.unwrap_or_default();
+        if seq_num > aad.seq_num {
+            return Err(Error::ExpiredEnvelope {
+                current: seq_num.u64(),
+                max: aad.seq_num.u64(),
+            });
+        }
+
+        // the ephemeral key is fresh for every sealed envelope, so this identifies replays
+        let digest = Sha256::new()
+            .chain_update(self.ephemeral_key())
+            .chain_update(self.ciphertext())
+            .finalize();
+        let key = (aad.seq_num.u64(), digest.as_slice());
+        if ENVELOPES.has(deps.storage, key) {
+            return Err(Error::DuplicateEnvelope);
+        }
+        ENVELOPES.save(deps.storage, key, &Empty {})?;
+
+        let expired: Vec<_> = ENVELOPES
+            .prefix_range(
+                deps.storage,
+                None,
+                Some(PrefixBound::exclusive(seq_num.u64())),
+                Order::Ascending,
+            )
+            .take(PRUNE_LIMIT)
+            .map(|entry| entry.map(|(key, _)| key))
+            .collect::<StdResult<_>>()?;
+        for (max_seq_num, digest) in expired {
+            ENVELOPES.remove(deps.storage, (max_seq_num, &digest));
+        }
+
+        Ok(Response::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        testing::{message_info, mock_dependencies, mock_env},
+        Addr, Storage, Uint64,
+    };
+
+    use super::*;
+    use crate::msg::execute::{
+        envelope::{EnvelopeAad, EnvelopeScheme},
+        sequenced::Sequenced,
+    };
+
+    fn envelope(contract: &Addr, seq_num: u64, key: u8) -> Sequenced<EncryptedEnvelope<String>> {
+        Sequenced(EncryptedEnvelope::new(
+            EnvelopeScheme::EciesSecp256k1,
+            vec![key; 65],
+            vec![1, 2, 3],
+            EnvelopeAad::new(contract.as_str(), seq_num),
+        ))
+    }
+
+    #[test]
+    fn test_envelope_is_bound_to_contract_and_seq_num() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&Addr::unchecked("user"), &[]);
+        let contract = env.contract.address.clone();
+        SEQUENCE_NUM
+            .save(&mut deps.storage, &Uint64::zero())
+            .expect("seq num initialized");
+
+        envelope(&contract, 1, 1)
+            .handle(deps.as_mut(), &env, &info)
+            .expect("first request gets seq num 1");
+
+        // envelopes may be sequenced later than their client expected
+        envelope(&contract, 5, 2)
+            .handle(deps.as_mut(), &env, &info)
+            .expect("second request gets seq num 2");
+
+        let err = envelope(&contract, 5, 2)
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("replayed envelope");
+        assert!(matches!(err, Error::DuplicateEnvelope));
+
+        let err = envelope(&contract, 2, 3)
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("envelope expired");
+        assert!(matches!(err, Error::ExpiredEnvelope { current: 4, max: 2 }));
+
+        let err = envelope(&Addr::unchecked("other-contract"), 5, 4)
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("envelope for another contract");
+        assert!(matches!(err, Error::ContractAddrMismatch));
+    }
+
+    #[test]
+    fn test_expired_envelopes_are_pruned() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&Addr::unchecked("user"), &[]);
+        let contract = env.contract.address.clone();
+        SEQUENCE_NUM
+            .save(&mut deps.storage, &Uint64::zero())
+            .expect("seq num initialized");
+        let accepted = |storage: &dyn Storage| {
+            ENVELOPES
+                .keys(storage, None, None, Order::Ascending)
+                .map(|key| key.expect("valid key").0)
+                .collect::<Vec<_>>()
+        };
+
+        envelope(&contract, 1, 1)
+            .handle(deps.as_mut(), &env, &info)
+            .expect("first request gets seq num 1");
+        assert_eq!(accepted(&deps.storage), [1]);
+
+        // the first envelope can't be sequenced anymore
+        envelope(&contract, 5, 2)
+            .handle(deps.as_mut(), &env, &info)
+            .expect("second request gets seq num 2");
+        assert_eq!(accepted(&deps.storage), [5]);
+
+        // the second envelope still could be
+        envelope(&contract, 3, 3)
+            .handle(deps.as_mut(), &env, &info)
+            .expect("third request gets seq num 3");
+        assert_eq!(accepted(&deps.storage), [3, 5]);
+    }
+}
@@ -0,0 +1,43 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+
+use crate::{
+    error::Error,
+    handler::Handler,
+    msg::execute::register_operator::RegisterOperator,
+    state::{OPERATOR_SESSIONS, QUORUM, SESSION},
+};
+
+impl Handler for RegisterOperator {
+    fn handle(self, deps: DepsMut<'_>, _env: &Env, info: &MessageInfo) -> Result<Response, Error> {
+        let quorum = QUORUM
+            .may_load(deps.storage)?
+            .ok_or(Error::QuorumNotConfigured)?;
+
+        // ASSERT msg.operator == info.sender, i.e. operators can only register their own replica
+        let operator = deps.api.addr_validate(self.operator())?;
+        if operator != info.sender {
+            return Err(Error::Unauthorized);
+        }
+        if !quorum.is_operator(&operator) {
+            return Err(Error::UnregisteredOperator(operator.to_string()));
+        }
+
+        // ASSERT SESSION.pubkey == msg.pub_key
+        let pub_key = SESSION
+            .load(deps.storage)?
+            .pub_key()
+            .ok_or(Error::BadSessionTransition)?;
+        if pub_key.as_slice() != self.pub_key().as_slice() {
+            return Err(Error::OperatorPubKeyMismatch);
+        }
+
+        // STORE OPERATOR_SESSIONS[msg.operator]: msg.pub_key
+        OPERATOR_SESSIONS
+            .save(deps.storage, &operator, &pub_key)
+            .map_err(Error::Std)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "register_operator")
+            .add_attribute("operator", operator))
+    }
+}
@@ -10,18 +10,21 @@ use crate::{
 impl Handler for SessionCreate {
     // Create new SESSION with msg.nonce and no pubkey.
     fn handle(self, deps: DepsMut<'_>, env: &Env, _info: &MessageInfo) -> Result<Response, Error> {
-        // TODO(hu55a1n1): overwrite previous session?
-
         // ASSERT msg.contract == env.contract.address
         let addr = deps.api.addr_validate(self.contract())?;
         if addr != env.contract.address {
             return Err(Error::ContractAddrMismatch);
         }
 
+        // ASSERT SESSION.pubkey == None, i.e. an active session must be reset by the admin first
         // STORE in SESSION: (msg.nonce, None)
-        SESSION
-            .save(deps.storage, &Session::create(self.nonce()))
-            .map_err(Error::Std)?;
+        let session = match SESSION.may_load(deps.storage)? {
+            Some(session) => session
+                .recreate(self.nonce())
+                .ok_or(Error::BadSessionTransition)?,
+            None => Session::create(self.nonce()),
+        };
+        SESSION.save(deps.storage, &session).map_err(Error::Std)?;
 
         Ok(Response::new().add_attribute("action", "session_create"))
     }
@@ -0,0 +1,84 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+
+use crate::{
+    error::Error,
+    handler::{execute::ensure_admin, Handler},
+    msg::execute::session_reset::SessionReset,
+    state::SESSION,
+};
+
+impl Handler for SessionReset {
+    // Revoke SESSION.pubkey so that a new handshake can be started with `SessionCreate`.
+    fn handle(self, deps: DepsMut<'_>, env: &Env, info: &MessageInfo) -> Result<Response, Error> {
+        ensure_admin(deps.as_ref(), env, info)?;
+
+        // STORE SESSION: (SESSION.nonce, None) as revoked
+        let session = SESSION.load(deps.storage).map_err(Error::Std)?;
+        SESSION
+            .save(deps.storage, &session.revoke())
+            .map_err(Error::Std)?;
+
+        Ok(Response::new().add_attribute("action", "session_reset"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+
+    use super::*;
+    use crate::handler::execute::mock_admin;
+    use crate::{
+        msg::execute::{session_create::SessionCreate, session_set_pub_key::SessionSetPubKey},
+        state::SessionStatus,
+    };
+
+    #[test]
+    fn test_session_reset_allows_a_new_handshake() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        mock_admin(&mut deps.querier, &admin);
+        let info = message_info(&admin, &[]);
+        let contract = env.contract.address.to_string();
+
+        SessionCreate::new([1; 32], contract.clone())
+            .handle(deps.as_mut(), &env, &info)
+            .expect("session created");
+        SessionSetPubKey::new([1; 32], vec![2; 33])
+            .handle(deps.as_mut(), &env, &info)
+            .expect("pub key set");
+
+        // an active session can't be overwritten
+        let err = SessionCreate::new([3; 32], contract.clone())
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("active session");
+        assert!(matches!(err, Error::BadSessionTransition));
+
+        let mallory = message_info(&deps.api.addr_make("mallory"), &[]);
+        let err = SessionReset::new()
+            .handle(deps.as_mut(), &env, &mallory)
+            .expect_err("not the admin");
+        assert!(matches!(err, Error::Unauthorized));
+
+        SessionReset::new()
+            .handle(deps.as_mut(), &env, &info)
+            .expect("admin reset");
+        let session = SESSION.load(deps.as_ref().storage).expect("session exists");
+        assert_eq!(session.status(), SessionStatus::Revoked);
+        assert_eq!(session.pub_key(), None);
+
+        SessionCreate::new([3; 32], contract)
+            .handle(deps.as_mut(), &env, &info)
+            .expect("new session created");
+        let session = SESSION.load(deps.as_ref().storage).expect("session exists");
+        assert_eq!(session.status(), SessionStatus::PendingReplacement);
+
+        SessionSetPubKey::new([3; 32], vec![4; 33])
+            .handle(deps.as_mut(), &env, &info)
+            .expect("new pub key set");
+        let session = SESSION.load(deps.as_ref().storage).expect("session exists");
+        assert_eq!(session.status(), SessionStatus::Active);
+        assert_eq!(session.pub_key(), Some(vec![4; 33].into()));
+    }
+}
@@ -0,0 +1,39 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+
+use crate::{
+    error::Error,
+    handler::{execute::ensure_admin, Handler},
+    msg::execute::set_trusted_anchor::SetTrustedAnchor,
+    state::{Config, TrustedAnchor, CONFIG, TRUSTED_ANCHOR},
+};
+
+impl Handler for SetTrustedAnchor {
+    fn handle(self, deps: DepsMut<'_>, env: &Env, info: &MessageInfo) -> Result<Response, Error> {
+        ensure_admin(deps.as_ref(), env, info)?;
+
+        // ASSERT new anchor is above the current one (or the initial one from the config)
+        let current_height = match TRUSTED_ANCHOR.may_load(deps.storage)? {
+            Some(anchor) => anchor.height(),
+            None => {
+                let config: Config = CONFIG.load(deps.storage)?.try_into()?;
+                config.light_client_opts().trusted_height()
+            }
+        };
+        if *self.height() <= current_height {
+            return Err(Error::StaleTrustedAnchor {
+                current: current_height,
+                new: *self.height(),
+            });
+        }
+
+        // STORE in TRUSTED_ANCHOR: (msg.height, msg.hash)
+        let height = *self.height();
+        TRUSTED_ANCHOR
+            .save(deps.storage, &TrustedAnchor::from(self))
+            .map_err(Error::Std)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_trusted_anchor")
+            .add_attribute("trusted_height", height.to_string()))
+    }
+}
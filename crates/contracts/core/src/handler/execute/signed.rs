@@ -3,7 +3,7 @@ use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 use crate::{
     error::Error,
     handler::Handler,
-    msg::execute::signed::{Auth, MsgVerifier, Signed},
+    msg::execute::signed::{nonced::Nonced, Auth, MsgVerifier, Signed},
 };
 
 impl<M, A, P, S> Handler for Signed<M, A>
@@ -19,7 +19,13 @@ where
     ) -> Result<Response, Error> {
         let (msg, auth) = self.into_tuple();
         let pub_key = auth.pub_key();
-        msg.verify(pub_key, auth.sig())?;
+        msg.verify(deps.branch(), env, pub_key, auth.sig())?;
         Handler::handle(msg, deps.branch(), env, info)
     }
 }
+
+impl<M: Handler> Handler for Nonced<M> {
+    fn handle(self, deps: DepsMut<'_>, env: &Env, info: &MessageInfo) -> Result<Response, Error> {
+        Handler::handle(self.into_msg(), deps, env, info)
+    }
+}
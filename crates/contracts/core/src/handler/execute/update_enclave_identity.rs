@@ -0,0 +1,88 @@
+use cosmwasm_std::{DepsMut, Env, HexBinary, MessageInfo, Response, Storage};
+
+use crate::{
+    error::Error,
+    handler::{execute::ensure_admin, Handler},
+    msg::execute::update_enclave_identity::UpdateEnclaveIdentity,
+    state::{Config, CONFIG},
+};
+
+impl Handler for UpdateEnclaveIdentity {
+    fn handle(self, deps: DepsMut<'_>, env: &Env, info: &MessageInfo) -> Result<Response, Error> {
+        ensure_admin(deps.as_ref(), env, info)?;
+        update_enclave_identity(deps.storage, self)
+    }
+}
+
+/// Replaces the MRENCLAVE (and, if specified, the additional trusted identities) in the config,
+/// leaving the rest of the contract state untouched.
+///
+/// This is also meant to be called from the app's `migrate` entry point (see
+/// [`handle_migrate`](crate::handler::migrate::handle_migrate)), which only the admin can execute.
+pub(crate) fn update_enclave_identity(
+    storage: &mut dyn Storage,
+    msg: UpdateEnclaveIdentity,
+) -> Result<Response, Error> {
+    let config: Config = CONFIG.load(storage)?.try_into()?;
+    let old_mr_enclave = config.mr_enclave();
+
+    // STORE in CONFIG: msg.mr_enclave
+    let mut config = config.with_mr_enclave(*msg.mr_enclave());
+    if let Some(identities) = msg.identities() {
+        config = config.with_identities(identities.to_vec());
+    }
+    let trusted_identities = config.trusted_identities().len();
+    CONFIG.save(storage, &config.into()).map_err(Error::Std)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_enclave_identity")
+        .add_attribute("old_mr_enclave", HexBinary::from(old_mr_enclave).to_hex())
+        .add_attribute(
+            "new_mr_enclave",
+            HexBinary::from(*msg.mr_enclave()).to_hex(),
+        )
+        .add_attribute("trusted_identities", trusted_identities.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+
+    use super::*;
+    use crate::handler::execute::mock_admin;
+    use crate::state::LightClientOpts;
+
+    #[test]
+    fn test_only_admin_can_update_enclave_identity() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        mock_admin(&mut deps.querier, &admin);
+
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts");
+        let config = Config::new([1; 32], light_client_opts, None, None);
+        CONFIG
+            .save(deps.as_mut().storage, &config.into())
+            .expect("config saved");
+
+        let info = message_info(&deps.api.addr_make("mallory"), &[]);
+        let err = UpdateEnclaveIdentity::new([2; 32], None)
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("not the admin");
+        assert!(matches!(err, Error::Unauthorized));
+
+        let info = message_info(&admin, &[]);
+        UpdateEnclaveIdentity::new([2; 32], None)
+            .handle(deps.as_mut(), &env, &info)
+            .expect("admin update");
+
+        let config: Config = CONFIG
+            .load(deps.as_ref().storage)
+            .expect("config exists")
+            .try_into()
+            .expect("valid config");
+        assert_eq!(config.mr_enclave(), [2; 32]);
+    }
+}
@@ -0,0 +1,35 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+
+use crate::{
+    error::Error,
+    handler::{execute::ensure_admin, Handler},
+    msg::execute::update_quorum::UpdateQuorum,
+    state::QUORUM,
+};
+
+impl Handler for UpdateQuorum {
+    fn handle(self, deps: DepsMut<'_>, env: &Env, info: &MessageInfo) -> Result<Response, Error> {
+        ensure_admin(deps.as_ref(), env, info)?;
+
+        let Some(quorum) = self.quorum() else {
+            // REMOVE QUORUM, i.e. single enclave outputs take effect immediately
+            QUORUM.remove(deps.storage);
+            return Ok(Response::new()
+                .add_attribute("action", "update_quorum")
+                .add_attribute("threshold", "0"));
+        };
+
+        for operator in quorum.operators() {
+            deps.api.addr_validate(operator)?;
+        }
+
+        // STORE QUORUM: msg.quorum
+        QUORUM.save(deps.storage, quorum).map_err(Error::Std)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_quorum")
+            .add_attribute("operators", quorum.operators().join(","))
+            .add_attribute("threshold", quorum.threshold().to_string())
+            .add_attribute("window", quorum.window().to_string()))
+    }
+}
@@ -0,0 +1,90 @@
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+
+use crate::{
+    error::Error,
+    handler::{execute::ensure_admin, Handler},
+    msg::execute::update_tcb_policy::UpdateTcbPolicy,
+    state::{Config, CONFIG},
+};
+
+impl Handler for UpdateTcbPolicy {
+    fn handle(self, deps: DepsMut<'_>, env: &Env, info: &MessageInfo) -> Result<Response, Error> {
+        ensure_admin(deps.as_ref(), env, info)?;
+
+        // STORE in CONFIG: msg.tcb_policy
+        let config: Config = CONFIG.load(deps.storage)?.try_into()?;
+        let config = config.with_tcb_policy(self.tcb_policy().clone());
+        CONFIG
+            .save(deps.storage, &config.into())
+            .map_err(Error::Std)?;
+
+        let tcb_policy = self.tcb_policy();
+        let accepted_statuses = tcb_policy
+            .accepted_statuses()
+            .iter()
+            .map(|status| format!("{status:?}"))
+            .collect::<Vec<_>>();
+        let allowed_advisories = tcb_policy
+            .allowed_advisories()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        let grace_period = tcb_policy
+            .out_of_date_grace_period()
+            .map(|grace_period| grace_period.to_string())
+            .unwrap_or_default();
+
+        Ok(Response::new()
+            .add_attribute("action", "update_tcb_policy")
+            .add_attribute("accepted_statuses", accepted_statuses.join(","))
+            .add_attribute("allowed_advisories", allowed_advisories.join(","))
+            .add_attribute("out_of_date_grace_period", grace_period))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+    use quartz_tee_ra::intel_sgx::dcap::tcb_policy::{TcbPolicy, TcbStatus};
+
+    use super::*;
+    use crate::handler::execute::mock_admin;
+    use crate::state::{LightClientOpts, RawConfig};
+
+    #[test]
+    fn test_only_admin_can_update_tcb_policy() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        mock_admin(&mut deps.querier, &admin);
+
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts");
+        let config = Config::new([1; 32], light_client_opts, None, None);
+        CONFIG
+            .save(deps.as_mut().storage, &config.into())
+            .expect("config saved");
+
+        let tcb_policy = TcbPolicy::new(
+            [TcbStatus::UpToDate],
+            ["INTEL-SA-00615"],
+            Some(30 * 24 * 60 * 60),
+        );
+
+        let info = message_info(&deps.api.addr_make("mallory"), &[]);
+        let err = UpdateTcbPolicy::new(tcb_policy.clone())
+            .handle(deps.as_mut(), &env, &info)
+            .expect_err("not the admin");
+        assert!(matches!(err, Error::Unauthorized));
+
+        let info = message_info(&admin, &[]);
+        UpdateTcbPolicy::new(tcb_policy.clone())
+            .handle(deps.as_mut(), &env, &info)
+            .expect("admin update");
+
+        let raw_config: RawConfig = CONFIG.load(deps.as_ref().storage).expect("config exists");
+        let config: Config = raw_config.try_into().expect("valid config");
+        assert_eq!(config.tcb_policy(), &tcb_policy);
+    }
+}
@@ -16,7 +16,7 @@ where
     A: Attestation + Handler + HasUserData,
 {
     fn handle(self, deps: DepsMut<'_>, env: &Env, info: &MessageInfo) -> Result<Response, Error> {
-        if self.0.msg().config().mr_enclave() != self.0.attestation().mr_enclave() {
+        if !self.0.msg().config().trusts(self.0.attestation()) {
             return Err(RaVerificationError::MrEnclaveMismatch.into());
         }
         self.0.handle(deps, env, info)
@@ -0,0 +1,21 @@
+use cosmwasm_std::{DepsMut, Env, Response};
+
+use crate::{
+    error::Error,
+    handler::execute::update_enclave_identity::update_enclave_identity,
+    msg::execute::update_enclave_identity::{RawUpdateEnclaveIdentity, UpdateEnclaveIdentity},
+};
+
+/// Handles an enclave upgrade from the app's `migrate` entry point.
+///
+/// Only the contract admin can migrate a contract, so no further authorization is needed. All
+/// contract state (including the session and the encrypted app state) is preserved, the new enclave
+/// is expected to receive the old enclave's keys via the enclave-core hand-over.
+pub fn handle_migrate(
+    deps: DepsMut<'_>,
+    _env: &Env,
+    msg: RawUpdateEnclaveIdentity,
+) -> Result<Response, Error> {
+    let msg: UpdateEnclaveIdentity = msg.try_into()?;
+    update_enclave_identity(deps.storage, msg)
+}
@@ -0,0 +1,252 @@
+use cosmwasm_std::{to_json_binary, Binary, Deps, Env, HexBinary, StdResult};
+
+use crate::{
+    msg::query::{
+        ConfigResponse, EnclaveIdentityResponse, HandshakeStatus, Query, QuorumResponse,
+        SequenceNumResponse, SessionResponse,
+    },
+    state::{Config, SessionStatus, CONFIG, OPERATOR_SESSIONS, QUORUM, SEQUENCE_NUM, SESSION},
+};
+
+/// Handles a [`Query`], meant to be called from the app's `query` entry point.
+pub fn handle_query(deps: Deps<'_>, _env: &Env, msg: Query) -> StdResult<Binary> {
+    match msg {
+        Query::Config {} => to_json_binary(&query_config(deps)?),
+        Query::Session {} => to_json_binary(&query_session(deps)?),
+        Query::SequenceNum {} => to_json_binary(&query_seq_num(deps)?),
+        Query::EnclaveIdentity {} => to_json_binary(&query_enclave_identity(deps)?),
+        Query::Quorum {} => to_json_binary(&query_quorum(deps)?),
+    }
+}
+
+fn query_config(deps: Deps<'_>) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse { config })
+}
+
+fn query_session(deps: Deps<'_>) -> StdResult<SessionResponse> {
+    let response = match SESSION.may_load(deps.storage)? {
+        Some(session) => {
+            let nonce = Some(session.nonce().into());
+            let session_status = session.status();
+            let pub_key = session.pub_key();
+            let status = match (session_status, &pub_key) {
+                (SessionStatus::Revoked, _) => HandshakeStatus::NotStarted,
+                (_, Some(_)) => HandshakeStatus::Complete,
+                (_, None) => HandshakeStatus::SessionCreated,
+            };
+
+            SessionResponse {
+                nonce,
+                pub_key,
+                status,
+                session_status: Some(session_status),
+            }
+        }
+        None => SessionResponse {
+            nonce: None,
+            pub_key: None,
+            status: HandshakeStatus::NotStarted,
+            session_status: None,
+        },
+    };
+
+    Ok(response)
+}
+
+fn query_seq_num(deps: Deps<'_>) -> StdResult<SequenceNumResponse> {
+    // the sequence number is only initialized once the handshake completes
+    let seq_num = SEQUENCE_NUM.may_load(deps.storage)?.unwrap_or_default();
+    Ok(SequenceNumResponse { seq_num })
+}
+
+fn query_enclave_identity(deps: Deps<'_>) -> StdResult<EnclaveIdentityResponse> {
+    let config: Config = CONFIG.load(deps.storage)?.try_into()?;
+    let pub_key = SESSION
+        .may_load(deps.storage)?
+        .and_then(|session| session.pub_key());
+
+    Ok(EnclaveIdentityResponse {
+        mr_enclave: HexBinary::from(config.mr_enclave()),
+        trusted_identities: config
+            .trusted_identities()
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        pub_key,
+    })
+}
+
+fn query_quorum(deps: Deps<'_>) -> StdResult<QuorumResponse> {
+    let Some(quorum) = QUORUM.may_load(deps.storage)? else {
+        return Ok(QuorumResponse {
+            quorum: None,
+            registered_operators: vec![],
+        });
+    };
+
+    let pub_key = SESSION
+        .may_load(deps.storage)?
+        .and_then(|session| session.pub_key());
+    let mut registered_operators = vec![];
+    for operator in quorum.operators() {
+        let operator = deps.api.addr_validate(operator)?;
+        let operator_pub_key = OPERATOR_SESSIONS.may_load(deps.storage, &operator)?;
+        if operator_pub_key.is_some() && operator_pub_key == pub_key {
+            registered_operators.push(operator.into_string());
+        }
+    }
+
+    Ok(QuorumResponse {
+        quorum: Some(quorum.into()),
+        registered_operators,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        from_json,
+        testing::{mock_dependencies, mock_env},
+        Uint64,
+    };
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+    use crate::state::{EnclaveIdentity, LightClientOpts, Quorum, RawEnclaveIdentity, Session};
+
+    fn query<T: DeserializeOwned>(deps: Deps<'_>, msg: Query) -> T {
+        from_json(handle_query(deps, &mock_env(), msg).expect("query succeeds"))
+            .expect("valid response")
+    }
+
+    fn config(mr_enclave: [u8; 32]) -> Config {
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts");
+        Config::new(mr_enclave, light_client_opts, None, None)
+    }
+
+    fn session_with_pub_key(pub_key: Vec<u8>) -> Session {
+        Session::create([1; 32])
+            .with_pub_key([1; 32], pub_key)
+            .expect("matching nonce")
+    }
+
+    #[test]
+    fn test_config_query_returns_the_stored_config() {
+        let mut deps = mock_dependencies();
+        handle_query(deps.as_ref(), &mock_env(), Query::Config {}).expect_err("no config stored");
+
+        let config = config([1; 32]);
+        CONFIG
+            .save(deps.as_mut().storage, &config.clone().into())
+            .expect("config saved");
+        let response: ConfigResponse = query(deps.as_ref(), Query::Config {});
+        assert_eq!(response.config, config.into());
+    }
+
+    #[test]
+    fn test_seq_num_query_defaults_to_zero() {
+        let mut deps = mock_dependencies();
+        let response: SequenceNumResponse = query(deps.as_ref(), Query::SequenceNum {});
+        assert_eq!(response.seq_num, Uint64::zero());
+
+        SEQUENCE_NUM
+            .save(deps.as_mut().storage, &Uint64::new(5))
+            .expect("seq num saved");
+        let response: SequenceNumResponse = query(deps.as_ref(), Query::SequenceNum {});
+        assert_eq!(response.seq_num, Uint64::new(5));
+    }
+
+    #[test]
+    fn test_enclave_identity_query_lists_all_trusted_identities() {
+        let mut deps = mock_dependencies();
+        let mr_signer = EnclaveIdentity::MrSigner {
+            mr_signer: [2; 32],
+            isv_prod_id: 1,
+            min_isv_svn: 3,
+        };
+        let config = config([1; 32]).with_identities(vec![mr_signer.clone()]);
+        CONFIG
+            .save(deps.as_mut().storage, &config.into())
+            .expect("config saved");
+
+        let response: EnclaveIdentityResponse = query(deps.as_ref(), Query::EnclaveIdentity {});
+        assert_eq!(response.mr_enclave, HexBinary::from([1; 32]));
+        assert_eq!(
+            response.trusted_identities,
+            vec![
+                RawEnclaveIdentity::from(EnclaveIdentity::MrEnclave([1; 32])),
+                RawEnclaveIdentity::from(mr_signer),
+            ]
+        );
+        assert_eq!(response.pub_key, None);
+
+        SESSION
+            .save(deps.as_mut().storage, &session_with_pub_key(vec![2; 33]))
+            .expect("session saved");
+        let response: EnclaveIdentityResponse = query(deps.as_ref(), Query::EnclaveIdentity {});
+        assert_eq!(response.pub_key, Some(HexBinary::from(vec![2; 33])));
+    }
+
+    #[test]
+    fn test_quorum_query_lists_the_operators_registered_for_the_session() {
+        let mut deps = mock_dependencies();
+        let response: QuorumResponse = query(deps.as_ref(), Query::Quorum {});
+        assert_eq!(response.quorum, None);
+        assert!(response.registered_operators.is_empty());
+
+        let alice = deps.api.addr_make("alice");
+        let bob = deps.api.addr_make("bob");
+        let quorum =
+            Quorum::new(vec![alice.to_string(), bob.to_string()], 1, 10).expect("valid quorum");
+        QUORUM
+            .save(deps.as_mut().storage, &quorum)
+            .expect("quorum saved");
+        SESSION
+            .save(deps.as_mut().storage, &session_with_pub_key(vec![2; 33]))
+            .expect("session saved");
+        OPERATOR_SESSIONS
+            .save(deps.as_mut().storage, &alice, &HexBinary::from(vec![2; 33]))
+            .expect("operator session saved");
+        // bob's replica was registered for a previous session
+        OPERATOR_SESSIONS
+            .save(deps.as_mut().storage, &bob, &HexBinary::from(vec![3; 33]))
+            .expect("operator session saved");
+
+        let response: QuorumResponse = query(deps.as_ref(), Query::Quorum {});
+        assert_eq!(response.quorum, Some(quorum.into()));
+        assert_eq!(response.registered_operators, vec![alice.to_string()]);
+    }
+
+    #[test]
+    fn test_session_query_tracks_handshake_status() {
+        let mut deps = mock_dependencies();
+        let session_response =
+            |deps: Deps<'_>| -> SessionResponse { query(deps, Query::Session {}) };
+
+        assert_eq!(
+            session_response(deps.as_ref()).status,
+            HandshakeStatus::NotStarted
+        );
+
+        let session = Session::create([1; 32]);
+        SESSION
+            .save(deps.as_mut().storage, &session)
+            .expect("session saved");
+        let response = session_response(deps.as_ref());
+        assert_eq!(response.status, HandshakeStatus::SessionCreated);
+        assert_eq!(response.nonce, Some(HexBinary::from([1; 32])));
+
+        let session = session
+            .with_pub_key([1; 32], vec![2; 33])
+            .expect("matching nonce");
+        SESSION
+            .save(deps.as_mut().storage, &session)
+            .expect("session saved");
+        let response = session_response(deps.as_ref());
+        assert_eq!(response.status, HandshakeStatus::Complete);
+        assert_eq!(response.pub_key, Some(HexBinary::from(vec![2; 33])));
+    }
+}
@@ -15,6 +15,9 @@
 )]
 #![forbid(unsafe_code)]
 
+// lets `quartz_msg` generated code refer to this crate by name
+extern crate self as quartz_contract_core;
+
 pub mod error;
 pub mod handler;
 pub mod msg;
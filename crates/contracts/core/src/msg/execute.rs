@@ -1,8 +1,15 @@
 pub mod attested;
+pub mod envelope;
+pub mod register_operator;
 pub mod sequenced;
 pub mod session_create;
+pub mod session_reset;
 pub mod session_set_pub_key;
+pub mod set_trusted_anchor;
 pub mod signed;
+pub mod update_enclave_identity;
+pub mod update_quorum;
+pub mod update_tcb_policy;
 
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::StdError;
@@ -10,8 +17,14 @@ use cosmwasm_std::StdError;
 use crate::msg::{
     execute::{
         attested::{Attested, DefaultAttestation, RawAttested, RawDefaultAttestation},
+        register_operator::{RawRegisterOperator, RegisterOperator},
         session_create::{RawSessionCreate, SessionCreate},
+        session_reset::{RawSessionReset, SessionReset},
         session_set_pub_key::{RawSessionSetPubKey, SessionSetPubKey},
+        set_trusted_anchor::{RawSetTrustedAnchor, SetTrustedAnchor},
+        update_enclave_identity::{RawUpdateEnclaveIdentity, UpdateEnclaveIdentity},
+        update_quorum::{RawUpdateQuorum, UpdateQuorum},
+        update_tcb_policy::{RawUpdateTcbPolicy, UpdateTcbPolicy},
     },
     HasDomainType,
 };
@@ -20,6 +33,12 @@ use crate::msg::{
 pub enum Execute<Attestation = DefaultAttestation> {
     SessionCreate(Attested<SessionCreate, Attestation>),
     SessionSetPubKey(Attested<SessionSetPubKey, Attestation>),
+    SessionReset(SessionReset),
+    SetTrustedAnchor(SetTrustedAnchor),
+    UpdateEnclaveIdentity(UpdateEnclaveIdentity),
+    UpdateTcbPolicy(UpdateTcbPolicy),
+    UpdateQuorum(UpdateQuorum),
+    RegisterOperator(Attested<RegisterOperator, Attestation>),
 }
 
 #[cw_serde]
@@ -28,6 +47,18 @@ pub enum RawExecute<RawAttestation = RawDefaultAttestation> {
     RawSessionCreate(RawAttested<RawSessionCreate, RawAttestation>),
     #[serde(rename = "session_set_pub_key")]
     RawSessionSetPubKey(RawAttested<RawSessionSetPubKey, RawAttestation>),
+    #[serde(rename = "session_reset")]
+    RawSessionReset(RawSessionReset),
+    #[serde(rename = "set_trusted_anchor")]
+    RawSetTrustedAnchor(RawSetTrustedAnchor),
+    #[serde(rename = "update_enclave_identity")]
+    RawUpdateEnclaveIdentity(RawUpdateEnclaveIdentity),
+    #[serde(rename = "update_tcb_policy")]
+    RawUpdateTcbPolicy(RawUpdateTcbPolicy),
+    #[serde(rename = "update_quorum")]
+    RawUpdateQuorum(RawUpdateQuorum),
+    #[serde(rename = "register_operator")]
+    RawRegisterOperator(RawAttested<RawRegisterOperator, RawAttestation>),
 }
 
 impl<RA> TryFrom<RawExecute<RA>> for Execute<RA::DomainType>
@@ -44,6 +75,20 @@ where
             RawExecute::RawSessionSetPubKey(msg) => {
                 Ok(Execute::SessionSetPubKey(TryFrom::try_from(msg)?))
             }
+            RawExecute::RawSessionReset(msg) => Ok(Execute::SessionReset(TryFrom::try_from(msg)?)),
+            RawExecute::RawSetTrustedAnchor(msg) => {
+                Ok(Execute::SetTrustedAnchor(TryFrom::try_from(msg)?))
+            }
+            RawExecute::RawUpdateEnclaveIdentity(msg) => {
+                Ok(Execute::UpdateEnclaveIdentity(TryFrom::try_from(msg)?))
+            }
+            RawExecute::RawUpdateTcbPolicy(msg) => {
+                Ok(Execute::UpdateTcbPolicy(TryFrom::try_from(msg)?))
+            }
+            RawExecute::RawUpdateQuorum(msg) => Ok(Execute::UpdateQuorum(TryFrom::try_from(msg)?)),
+            RawExecute::RawRegisterOperator(msg) => {
+                Ok(Execute::RegisterOperator(TryFrom::try_from(msg)?))
+            }
         }
     }
 }
@@ -56,6 +101,14 @@ where
         match value {
             Execute::SessionCreate(msg) => RawExecute::RawSessionCreate(From::from(msg)),
             Execute::SessionSetPubKey(msg) => RawExecute::RawSessionSetPubKey(From::from(msg)),
+            Execute::SessionReset(msg) => RawExecute::RawSessionReset(From::from(msg)),
+            Execute::SetTrustedAnchor(msg) => RawExecute::RawSetTrustedAnchor(From::from(msg)),
+            Execute::UpdateEnclaveIdentity(msg) => {
+                RawExecute::RawUpdateEnclaveIdentity(From::from(msg))
+            }
+            Execute::UpdateTcbPolicy(msg) => RawExecute::RawUpdateTcbPolicy(From::from(msg)),
+            Execute::UpdateQuorum(msg) => RawExecute::RawUpdateQuorum(From::from(msg)),
+            Execute::RegisterOperator(msg) => RawExecute::RawRegisterOperator(From::from(msg)),
         }
     }
 }
@@ -1,7 +1,7 @@
 use std::{convert::Into, default::Default};
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{HexBinary, StdError};
+use cosmwasm_std::{Env, HexBinary, StdError};
 use quartz_tee_ra::intel_sgx::dcap::{Collateral, Quote3, Quote3Error};
 use serde::Serialize;
 
@@ -21,7 +21,7 @@ pub type RawDefaultAttestation = RawMockAttestation;
 
 use crate::{
     msg::HasDomainType,
-    state::{MrEnclave, UserData},
+    state::{MrEnclave, MrSigner, UserData},
 };
 
 /// A wrapper struct for holding a message and it's attestation.
@@ -95,8 +95,130 @@ where
 /// A trait that defines how to extract user data from a given type.
 pub trait HasUserData {
     fn user_data(&self) -> UserData;
+
+    /// The type of the message, which the domain-separated user data commits to (see
+    /// [`HasUserData::user_data_for`]). Must be unique among the messages a contract accepts, so
+    /// that an attestation to one message type can't be passed off as another.
+    fn msg_type(&self) -> &'static str;
+
+    /// The user data bound to the deployment (i.e. chain and contract) that the message is meant
+    /// for. This is what enclaves attest to and what the `Attested` handler checks, so that an
+    /// attested message can't be replayed on another chain or contract.
+    fn user_data_for(&self, domain: &UserDataDomain) -> UserData {
+        domain.bind(self.msg_type(), self.user_data())
+    }
+
+    /// Whether the message is an app output that only takes effect once a quorum of operators
+    /// submitted it (if the contract has a `Quorum` configured).
+    fn requires_quorum(&self) -> bool {
+        false
+    }
+}
+
+impl HasUserData for UserData {
+    fn user_data(&self) -> UserData {
+        *self
+    }
+
+    fn msg_type(&self) -> &'static str {
+        "user_data"
+    }
+}
+
+/// The deployment that an attested message is meant for.
+///
+/// The contract is `None` only for messages that are attested before the contract exists (i.e.
+/// `CoreInstantiate`). The operator is only set for app outputs of a replica (see the contract's
+/// `Quorum`), since all replicas share the session key and MRENCLAVE.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserDataDomain {
+    chain_id: String,
+    contract: Option<String>,
+    operator: Option<String>,
+}
+
+impl UserDataDomain {
+    const TAG: &'static [u8] = b"quartz-user-data-v1";
+
+    pub fn new(chain_id: impl Into<String>, contract: Option<String>) -> Self {
+        Self {
+            chain_id: chain_id.into(),
+            contract,
+            operator: None,
+        }
+    }
+
+    /// The domain of the contract that is executing.
+    pub fn from_env(env: &Env) -> Self {
+        Self::new(
+            env.block.chain_id.clone(),
+            Some(env.contract.address.to_string()),
+        )
+    }
+
+    pub fn without_contract(self) -> Self {
+        Self {
+            contract: None,
+            ..self
+        }
+    }
+
+    /// Binds the user data to the operator of the replica that produced the message.
+    pub fn with_operator(self, operator: impl Into<String>) -> Self {
+        Self {
+            operator: Some(operator.into()),
+            ..self
+        }
+    }
+
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    pub fn contract(&self) -> Option<&str> {
+        self.contract.as_deref()
+    }
+
+    pub fn operator(&self) -> Option<&str> {
+        self.operator.as_deref()
+    }
+
+    /// Replaces the digest (bytes `0..32`) of the user data with the SHA-256 digest of -
+    /// `TAG || chain_id || contract || msg_type || [operator] || digest`
+    /// where the variable length fields are prefixed with their (big-endian `u64`) length, and
+    /// the operator is only included if it is set. The rest of the user data is left as is.
+    pub fn bind(&self, msg_type: &str, user_data: UserData) -> UserData {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        hasher.update(Self::TAG);
+        for field in [
+            self.chain_id.as_bytes(),
+            self.contract.as_deref().unwrap_or_default().as_bytes(),
+            msg_type.as_bytes(),
+        ] {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        }
+        if let Some(operator) = &self.operator {
+            hasher.update((operator.len() as u64).to_be_bytes());
+            hasher.update(operator.as_bytes());
+        }
+        hasher.update(&user_data[0..32]);
+        let digest: [u8; 32] = hasher.finalize().into();
+
+        let mut bound = user_data;
+        bound[0..32].copy_from_slice(&digest);
+        bound
+    }
 }
 
+/// The user data layout is:
+/// - bytes `0..32` - the SHA-256 digest of the message's JSON serialization (which is bound to a
+///   domain when attested, see [`UserDataDomain::bind`])
+/// - bytes `32..40` - the (big-endian) height that the message was committed to, zero if none
+///   (see [`committed_height`])
+/// - bytes `40..64` - unused (zero)
 pub fn user_data_json<T: Serialize>(value: &T) -> UserData {
     use serde_json::to_string;
     use sha2::{Digest, Sha256};
@@ -110,8 +232,28 @@ pub fn user_data_json<T: Serialize>(value: &T) -> UserData {
     user_data
 }
 
+/// Same as [`user_data_json`] but also commits to the height of the chain state (i.e. the proof
+/// target height) that the message was produced from.
+pub fn user_data_json_at_height<T: Serialize>(value: &T, height: u64) -> UserData {
+    let mut user_data = user_data_json(value);
+    user_data[32..40].copy_from_slice(&height.to_be_bytes());
+    user_data
+}
+
+/// Returns the height committed to in the user data, if any.
+pub fn committed_height(user_data: &UserData) -> Option<u64> {
+    let height = u64::from_be_bytes(user_data[32..40].try_into().expect("fixed size slice"));
+    (height != 0).then_some(height)
+}
+
 pub trait Attestation {
     fn mr_enclave(&self) -> MrEnclave;
+
+    fn mr_signer(&self) -> MrSigner;
+
+    fn isv_prod_id(&self) -> u16;
+
+    fn isv_svn(&self) -> u16;
 }
 
 /// A verifiable DCAP attestation generated by an enclave.
@@ -179,6 +321,10 @@ impl HasUserData for DcapAttestation {
             .try_into()
             .expect("fixed size array")
     }
+
+    fn msg_type(&self) -> &'static str {
+        "dcap_attestation"
+    }
 }
 
 impl Attestation for DcapAttestation {
@@ -190,6 +336,23 @@ impl Attestation for DcapAttestation {
             .try_into()
             .expect("fixed size array")
     }
+
+    fn mr_signer(&self) -> MrSigner {
+        let mr_signer = self.quote.app_report_body().mr_signer();
+        let mr_signer_slice: &[u8] = mr_signer.as_ref();
+        mr_signer_slice
+            .to_owned()
+            .try_into()
+            .expect("fixed size array")
+    }
+
+    fn isv_prod_id(&self) -> u16 {
+        self.quote.app_report_body().isv_product_id().into()
+    }
+
+    fn isv_svn(&self) -> u16 {
+        self.quote.app_report_body().isv_svn().into()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -226,14 +389,32 @@ impl HasUserData for MockAttestation {
     fn user_data(&self) -> UserData {
         self.0
     }
+
+    fn msg_type(&self) -> &'static str {
+        "mock_attestation"
+    }
 }
 
 impl Attestation for MockAttestation {
     fn mr_enclave(&self) -> MrEnclave {
         Default::default()
     }
+
+    fn mr_signer(&self) -> MrSigner {
+        Default::default()
+    }
+
+    fn isv_prod_id(&self) -> u16 {
+        Default::default()
+    }
+
+    fn isv_svn(&self) -> u16 {
+        Default::default()
+    }
 }
 
+/// An app's enclave output, i.e. a message that the app handles itself once the `Attested` handler
+/// accepted it (see `Attested::handle_output`).
 #[derive(Clone, Debug, PartialEq)]
 pub struct Noop<T>(pub T);
 
@@ -251,6 +432,18 @@ where
     fn user_data(&self) -> UserData {
         self.0.user_data()
     }
+
+    fn msg_type(&self) -> &'static str {
+        self.0.msg_type()
+    }
+
+    fn user_data_for(&self, domain: &UserDataDomain) -> UserData {
+        self.0.user_data_for(domain)
+    }
+
+    fn requires_quorum(&self) -> bool {
+        true
+    }
 }
 
 impl<T> TryFrom<RawNoop<T>> for Noop<T> {
@@ -0,0 +1,170 @@
+use std::marker::PhantomData;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{HexBinary, StdError, Uint64};
+use serde::{Deserialize, Serialize};
+
+use crate::msg::HasDomainType;
+
+/// The current version of the [`EncryptedEnvelope`] format.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Size of an uncompressed secp256k1 pub key, which `ecies` uses for its ephemeral keys.
+pub const ECIES_EPHEMERAL_KEY_SIZE: usize = 65;
+
+/// The scheme used to encrypt the payload of an [`EncryptedEnvelope`] to the enclave's pub key.
+#[cw_serde]
+pub enum EnvelopeScheme {
+    /// ECIES over secp256k1 with AES-256-GCM, as implemented by the `ecies` crate. The ephemeral key
+    /// is an uncompressed secp256k1 pub key.
+    EciesSecp256k1,
+}
+
+/// The associated data of an [`EncryptedEnvelope`]. It is sent in the clear (so that the contract
+/// can check it) and also sealed along with the plaintext (so that the enclave can check that it
+/// wasn't tampered with).
+#[cw_serde]
+pub struct EnvelopeAad {
+    /// The address of the contract that the request is meant for.
+    pub contract: String,
+    /// The highest sequence number that the contract may assign to the request, i.e. the envelope
+    /// expires once the contract has sequenced this many requests. Since other requests may be
+    /// sequenced before this one, clients would use the current sequence number plus a margin.
+    pub seq_num: Uint64,
+}
+
+impl EnvelopeAad {
+    pub fn new(contract: impl Into<String>, seq_num: u64) -> Self {
+        Self {
+            contract: contract.into(),
+            seq_num: seq_num.into(),
+        }
+    }
+}
+
+/// The plaintext that is sealed in an [`EncryptedEnvelope`], i.e. the msg along with the envelope's
+/// [`EnvelopeAad`], so that the enclave can make sure that the (cleartext) AAD that the contract
+/// checked is the one that the user sealed.
+#[derive(Serialize, Deserialize)]
+pub struct SealedPayload<T> {
+    pub aad: EnvelopeAad,
+    pub msg: T,
+}
+
+/// Encrypts `msg` to the enclave's (SEC1-encoded) pub key. This is meant to be used by clients.
+#[cfg(feature = "client")]
+pub fn seal_envelope<T: Serialize>(
+    enclave_pk: &[u8],
+    aad: EnvelopeAad,
+    msg: T,
+) -> Result<EncryptedEnvelope<T>, String> {
+    let sealed = SealedPayload {
+        aad: aad.clone(),
+        msg,
+    };
+    let plaintext = serde_json::to_vec(&sealed).map_err(|e| e.to_string())?;
+    let mut ciphertext = ecies::encrypt(enclave_pk, &plaintext).map_err(|e| e.to_string())?;
+    let payload = ciphertext.split_off(ECIES_EPHEMERAL_KEY_SIZE);
+
+    Ok(EncryptedEnvelope::new(
+        EnvelopeScheme::EciesSecp256k1,
+        ciphertext,
+        payload,
+        aad,
+    ))
+}
+
+/// An encrypted user request for the enclave, carrying a plaintext of type `T`.
+///
+/// The contract can only check the envelope's [`EnvelopeAad`] (see the `Handler` impl), whereas
+/// the enclave opens the envelope (using its key-manager) and gets the `T`. Apps only need to
+/// declare the plaintext type, e.g. `Sequenced<EncryptedEnvelope<Transfer>>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncryptedEnvelope<T> {
+    scheme: EnvelopeScheme,
+    ephemeral_key: Vec<u8>,
+    ciphertext: Vec<u8>,
+    aad: EnvelopeAad,
+    plaintext: PhantomData<fn() -> T>,
+}
+
+impl<T> EncryptedEnvelope<T> {
+    pub fn new(
+        scheme: EnvelopeScheme,
+        ephemeral_key: Vec<u8>,
+        ciphertext: Vec<u8>,
+        aad: EnvelopeAad,
+    ) -> Self {
+        Self {
+            scheme,
+            ephemeral_key,
+            ciphertext,
+            aad,
+            plaintext: PhantomData,
+        }
+    }
+
+    pub fn scheme(&self) -> &EnvelopeScheme {
+        &self.scheme
+    }
+
+    pub fn ephemeral_key(&self) -> &[u8] {
+        &self.ephemeral_key
+    }
+
+    pub fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    pub fn aad(&self) -> &EnvelopeAad {
+        &self.aad
+    }
+}
+
+#[cw_serde]
+pub struct RawEncryptedEnvelope<T> {
+    pub version: u8,
+    pub scheme: EnvelopeScheme,
+    pub ephemeral_key: HexBinary,
+    pub ciphertext: HexBinary,
+    pub aad: EnvelopeAad,
+    #[serde(skip)]
+    pub plaintext: PhantomData<fn() -> T>,
+}
+
+impl<T> TryFrom<RawEncryptedEnvelope<T>> for EncryptedEnvelope<T> {
+    type Error = StdError;
+
+    fn try_from(value: RawEncryptedEnvelope<T>) -> Result<Self, Self::Error> {
+        if value.version != ENVELOPE_VERSION {
+            return Err(StdError::generic_err(format!(
+                "unsupported envelope version {} (expected {ENVELOPE_VERSION})",
+                value.version
+            )));
+        }
+
+        Ok(Self::new(
+            value.scheme,
+            value.ephemeral_key.into(),
+            value.ciphertext.into(),
+            value.aad,
+        ))
+    }
+}
+
+impl<T> From<EncryptedEnvelope<T>> for RawEncryptedEnvelope<T> {
+    fn from(value: EncryptedEnvelope<T>) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            scheme: value.scheme,
+            ephemeral_key: value.ephemeral_key.into(),
+            ciphertext: value.ciphertext.into(),
+            aad: value.aad,
+            plaintext: PhantomData,
+        }
+    }
+}
+
+impl<T> HasDomainType for RawEncryptedEnvelope<T> {
+    type DomainType = EncryptedEnvelope<T>;
+}
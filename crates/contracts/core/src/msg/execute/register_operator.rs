@@ -0,0 +1,11 @@
+use cosmwasm_std::HexBinary;
+use quartz_contract_core_derive::quartz_msg;
+
+/// Registers the session of an operator's enclave replica. The replica must hold the session key
+/// (e.g. via an enclave hand-over), so that all replicas compute over the same state.
+#[quartz_msg(user_data, msg_type = "register_operator")]
+pub struct RegisterOperator {
+    operator: String,
+    #[raw(ty = "HexBinary")]
+    pub_key: Vec<u8>,
+}
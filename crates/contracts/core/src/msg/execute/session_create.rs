@@ -69,4 +69,8 @@ impl HasUserData for SessionCreate {
         user_data[0..32].copy_from_slice(&digest);
         user_data
     }
+
+    fn msg_type(&self) -> &'static str {
+        "session_create"
+    }
 }
@@ -0,0 +1,8 @@
+use quartz_contract_core_derive::quartz_msg;
+
+/// Revokes the session's pub key so that a new enclave can complete the handshake against this
+/// contract, e.g. after the enclave lost its key.
+/// Only the contract admin is allowed to execute this msg.
+#[quartz_msg]
+#[derive(Default)]
+pub struct SessionReset {}
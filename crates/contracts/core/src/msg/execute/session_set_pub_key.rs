@@ -73,4 +73,8 @@ impl HasUserData for SessionSetPubKey {
         user_data[0..32].copy_from_slice(&digest);
         user_data
     }
+
+    fn msg_type(&self) -> &'static str {
+        "session_set_pub_key"
+    }
 }
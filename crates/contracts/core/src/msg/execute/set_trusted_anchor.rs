@@ -0,0 +1,54 @@
+use cosmwasm_std::{to_json_vec, HexBinary, StdError, StdResult};
+use quartz_contract_core_derive::quartz_msg;
+use serde::Serialize;
+
+use crate::{
+    msg::execute::attested::UserDataDomain,
+    state::{Hash, Height, TrustedAnchor},
+};
+
+fn to_hash(raw: HexBinary) -> StdResult<Hash> {
+    raw.to_array()
+}
+
+fn valid_height(msg: &SetTrustedAnchor) -> StdResult<()> {
+    i64::try_from(msg.height)
+        .map(|_| ())
+        .map_err(|_| StdError::generic_err("trusted_height too large"))
+}
+
+/// Sets a new light client trusted height and hash for the enclave to re-anchor to.
+/// Only the contract admin is allowed to execute this msg.
+#[quartz_msg(validate = "valid_height")]
+pub struct SetTrustedAnchor {
+    height: Height,
+    #[raw(ty = "HexBinary", try_from = "to_hash")]
+    hash: Hash,
+}
+
+impl From<SetTrustedAnchor> for TrustedAnchor {
+    fn from(value: SetTrustedAnchor) -> Self {
+        TrustedAnchor::new(value.height, value.hash)
+    }
+}
+
+#[derive(Serialize)]
+struct AnchorSignDoc<'a> {
+    chain_id: &'a str,
+    contract: Option<&'a str>,
+    anchor: &'a TrustedAnchor,
+}
+
+/// Returns the bytes that the config's anchor signer (see `Config::with_anchor_signer`) must sign
+/// for the enclaves of the contract described by `domain` to adopt `anchor`. The signature is a
+/// secp256k1 signature over the SHA-256 of the sign bytes.
+pub fn anchor_sign_bytes(
+    domain: &UserDataDomain,
+    anchor: &TrustedAnchor,
+) -> Result<Vec<u8>, StdError> {
+    to_json_vec(&AnchorSignDoc {
+        chain_id: domain.chain_id(),
+        contract: domain.contract(),
+        anchor,
+    })
+}
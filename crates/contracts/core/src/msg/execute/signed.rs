@@ -1,7 +1,10 @@
+pub mod nonced;
+
 use std::fmt::Debug;
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::StdError;
+use cosmwasm_std::{DepsMut, Env, StdError};
+use serde::Serialize;
 
 use super::attested::Noop;
 use crate::{error::Error, msg::HasDomainType};
@@ -81,11 +84,19 @@ where
     }
 }
 
+/// Verifies the signature over a user msg. Verifiers get mutable access to the contract state so
+/// that they can keep track of per-signer nonces (see [`nonced::Nonced`]).
 pub trait MsgVerifier {
     type PubKey;
     type Sig;
 
-    fn verify(&self, pub_key: &Self::PubKey, sig: &Self::Sig) -> Result<(), Error>;
+    fn verify(
+        &self,
+        deps: DepsMut<'_>,
+        env: &Env,
+        pub_key: &Self::PubKey,
+        sig: &Self::Sig,
+    ) -> Result<(), Error>;
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -100,6 +111,33 @@ impl<P, S> AnyAuth<P, S> {
     }
 }
 
+#[cw_serde]
+pub struct RawAnyAuth<P, S> {
+    pub pub_key: P,
+    pub sig: S,
+}
+
+impl<P, S> TryFrom<RawAnyAuth<P, S>> for AnyAuth<P, S> {
+    type Error = StdError;
+
+    fn try_from(value: RawAnyAuth<P, S>) -> Result<Self, Self::Error> {
+        Ok(Self::new(value.pub_key, value.sig))
+    }
+}
+
+impl<P, S> From<AnyAuth<P, S>> for RawAnyAuth<P, S> {
+    fn from(value: AnyAuth<P, S>) -> Self {
+        Self {
+            pub_key: value.pub_key,
+            sig: value.sig,
+        }
+    }
+}
+
+impl<P: Serialize, S: Serialize> HasDomainType for RawAnyAuth<P, S> {
+    type DomainType = AnyAuth<P, S>;
+}
+
 pub trait Auth<P, S> {
     fn pub_key(&self) -> &P;
     fn sig(&self) -> &S;
@@ -119,7 +157,13 @@ impl<M: MsgVerifier> MsgVerifier for Noop<M> {
     type PubKey = M::PubKey;
     type Sig = M::Sig;
 
-    fn verify(&self, pub_key: &Self::PubKey, sig: &Self::Sig) -> Result<(), Error> {
-        self.0.verify(pub_key, sig)
+    fn verify(
+        &self,
+        deps: DepsMut<'_>,
+        env: &Env,
+        pub_key: &Self::PubKey,
+        sig: &Self::Sig,
+    ) -> Result<(), Error> {
+        self.0.verify(deps, env, pub_key, sig)
     }
 }
@@ -0,0 +1,335 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_json_vec, Binary, CanonicalAddr, DepsMut, Env, HexBinary, StdError, Uint64};
+use ripemd::Ripemd160;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::Error,
+    msg::{
+        execute::{attested::UserDataDomain, signed::MsgVerifier},
+        HasDomainType,
+    },
+    state::SIGNER_NONCES,
+};
+
+/// The public key of the signer of a [`Nonced`] user msg, tagged with the signature scheme used.
+#[cw_serde]
+pub enum SignerPubKey {
+    /// A compressed secp256k1 pub key; the signature is over the SHA-256 of the sign bytes.
+    Secp256k1(HexBinary),
+    /// An ed25519 pub key; the signature is over the sign bytes.
+    Ed25519(HexBinary),
+    /// A compressed secp256k1 pub key of a wallet signing the sign bytes as an ADR-036 arbitrary
+    /// message (e.g. Keplr's `signArbitrary`).
+    Adr036(HexBinary),
+}
+
+impl SignerPubKey {
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Secp256k1(pk) | Self::Ed25519(pk) | Self::Adr036(pk) => pk.as_slice(),
+        }
+    }
+}
+
+/// A user msg along with the signer's next nonce. The signature covers the msg, the nonce and the
+/// chain-id and address of the contract it is meant for, so it cannot be replayed against this or
+/// any other deployment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Nonced<M> {
+    msg: M,
+    nonce: u64,
+}
+
+impl<M> Nonced<M> {
+    pub fn new(msg: M, nonce: u64) -> Self {
+        Self { msg, nonce }
+    }
+
+    pub fn into_msg(self) -> M {
+        self.msg
+    }
+
+    pub fn msg(&self) -> &M {
+        &self.msg
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+#[cw_serde]
+pub struct RawNonced<RM> {
+    pub msg: RM,
+    pub nonce: Uint64,
+}
+
+impl<RM: HasDomainType> TryFrom<RawNonced<RM>> for Nonced<RM::DomainType> {
+    type Error = StdError;
+
+    fn try_from(value: RawNonced<RM>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            msg: value.msg.try_into()?,
+            nonce: value.nonce.u64(),
+        })
+    }
+}
+
+impl<RM: HasDomainType> From<Nonced<RM::DomainType>> for RawNonced<RM> {
+    fn from(value: Nonced<RM::DomainType>) -> Self {
+        Self {
+            msg: value.msg.into(),
+            nonce: value.nonce.into(),
+        }
+    }
+}
+
+impl<RM: HasDomainType> HasDomainType for RawNonced<RM> {
+    type DomainType = Nonced<RM::DomainType>;
+}
+
+#[derive(Serialize)]
+struct SignDoc<'a, M> {
+    chain_id: &'a str,
+    contract: Option<&'a str>,
+    nonce: Uint64,
+    msg: &'a M,
+}
+
+/// Returns the bytes that a signer must sign for `msg` to be accepted with `nonce` by the contract
+/// described by `domain`.
+pub fn sign_bytes<M: Serialize>(
+    domain: &UserDataDomain,
+    nonce: u64,
+    msg: &M,
+) -> Result<Vec<u8>, StdError> {
+    to_json_vec(&SignDoc {
+        chain_id: domain.chain_id(),
+        contract: domain.contract(),
+        nonce: nonce.into(),
+        msg,
+    })
+}
+
+/// Wraps `data` in the amino JSON sign doc that ADR-036 wallets sign for `signer`.
+pub fn adr036_sign_doc(signer: &str, data: &[u8]) -> Vec<u8> {
+    format!(
+        r#"{{"account_number":"0","chain_id":"","fee":{{"amount":[],"gas":"0"}},"memo":"","msgs":[{{"type":"sign/MsgSignData","value":{{"data":"{}","signer":"{}"}}}}],"sequence":"0"}}"#,
+        Binary::from(data).to_base64(),
+        signer
+    )
+    .into_bytes()
+}
+
+impl<M: Serialize> MsgVerifier for Nonced<M> {
+    type PubKey = SignerPubKey;
+    type Sig = HexBinary;
+
+    fn verify(
+        &self,
+        deps: DepsMut<'_>,
+        env: &Env,
+        pub_key: &Self::PubKey,
+        sig: &Self::Sig,
+    ) -> Result<(), Error> {
+        let msg = sign_bytes(&UserDataDomain::from_env(env), self.nonce, &self.msg)?;
+
+        let verified = match pub_key {
+            SignerPubKey::Secp256k1(pk) => {
+                deps.api.secp256k1_verify(&Sha256::digest(&msg), sig, pk)
+            }
+            SignerPubKey::Ed25519(pk) => deps.api.ed25519_verify(&msg, sig, pk),
+            SignerPubKey::Adr036(pk) => {
+                let addr_bytes = Ripemd160::digest(Sha256::digest(pk.as_slice()));
+                let signer = deps
+                    .api
+                    .addr_humanize(&CanonicalAddr::from(addr_bytes.as_slice()))?;
+                let doc = adr036_sign_doc(signer.as_str(), &msg);
+                deps.api.secp256k1_verify(&Sha256::digest(doc), sig, pk)
+            }
+        }
+        .map_err(|e| Error::SignatureVerification(e.to_string()))?;
+        if !verified {
+            return Err(Error::SignatureVerification(
+                "signature does not match the signed msg".to_string(),
+            ));
+        }
+
+        let expected = SIGNER_NONCES
+            .may_load(deps.storage, pub_key.as_bytes())?
+            .unwrap_or_default()
+            .u64();
+        if self.nonce != expected {
+            return Err(Error::InvalidNonce {
+                expected,
+                got: self.nonce,
+            });
+        }
+        SIGNER_NONCES.save(deps.storage, pub_key.as_bytes(), &(expected + 1).into())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        testing::{message_info, mock_dependencies, mock_env},
+        Addr, Api, MessageInfo, Response,
+    };
+    use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+
+    use super::*;
+    use crate::{
+        handler::Handler,
+        msg::execute::signed::{AnyAuth, Signed},
+    };
+
+    #[cw_serde]
+    struct Transfer {
+        amount: u64,
+    }
+
+    impl Handler for Transfer {
+        fn handle(
+            self,
+            _deps: DepsMut<'_>,
+            _env: &Env,
+            _info: &MessageInfo,
+        ) -> Result<Response, Error> {
+            Ok(Response::new().add_attribute("amount", self.amount.to_string()))
+        }
+    }
+
+    fn k256_key() -> SigningKey {
+        SigningKey::from_slice(&[7; 32]).expect("valid secret key")
+    }
+
+    fn k256_pub_key(sk: &SigningKey) -> HexBinary {
+        sk.verifying_key().to_sec1_bytes().to_vec().into()
+    }
+
+    fn k256_sign(sk: &SigningKey, msg: &[u8]) -> HexBinary {
+        let sig: Signature = sk.sign_prehash(&Sha256::digest(msg)).expect("signable");
+        sig.to_bytes().to_vec().into()
+    }
+
+    fn secp256k1_signed(
+        env: &Env,
+        nonce: u64,
+    ) -> Signed<Nonced<Transfer>, AnyAuth<SignerPubKey, HexBinary>> {
+        let sk = k256_key();
+        let msg = Nonced::new(Transfer { amount: 10 }, nonce);
+        let bytes =
+            sign_bytes(&UserDataDomain::from_env(env), nonce, msg.msg()).expect("serializable");
+        let auth = AnyAuth::new(
+            SignerPubKey::Secp256k1(k256_pub_key(&sk)),
+            k256_sign(&sk, &bytes),
+        );
+        Signed::new(msg, auth)
+    }
+
+    fn handle(
+        deps: DepsMut<'_>,
+        env: &Env,
+        signed: Signed<Nonced<Transfer>, AnyAuth<SignerPubKey, HexBinary>>,
+    ) -> Result<Response, Error> {
+        let info = message_info(&Addr::unchecked("relayer"), &[]);
+        signed.handle(deps, env, &info)
+    }
+
+    #[test]
+    fn test_secp256k1_signed_msg_is_accepted_once() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let res =
+            handle(deps.as_mut(), &env, secp256k1_signed(&env, 0)).expect("first use of nonce");
+        assert_eq!(res.attributes[0].value, "10");
+
+        let err =
+            handle(deps.as_mut(), &env, secp256k1_signed(&env, 0)).expect_err("replayed nonce");
+        assert!(matches!(
+            err,
+            Error::InvalidNonce {
+                expected: 1,
+                got: 0
+            }
+        ));
+
+        handle(deps.as_mut(), &env, secp256k1_signed(&env, 1)).expect("next nonce");
+    }
+
+    #[test]
+    fn test_signed_msg_for_another_deployment_is_rejected() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let mut other = mock_env();
+        other.contract.address = Addr::unchecked("other-contract");
+
+        let err =
+            handle(deps.as_mut(), &env, secp256k1_signed(&other, 0)).expect_err("wrong domain");
+        assert!(matches!(err, Error::SignatureVerification(_)));
+    }
+
+    #[test]
+    fn test_ed25519_signed_msg_is_accepted() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let sk = ed25519_consensus::SigningKey::from([3; 32]);
+        let msg = Nonced::new(Transfer { amount: 5 }, 0);
+        let bytes =
+            sign_bytes(&UserDataDomain::from_env(&env), 0, msg.msg()).expect("serializable");
+        let auth = AnyAuth::new(
+            SignerPubKey::Ed25519(sk.verification_key().to_bytes().to_vec().into()),
+            sk.sign(&bytes).to_bytes().to_vec().into(),
+        );
+
+        handle(deps.as_mut(), &env, Signed::new(msg.clone(), auth.clone()))
+            .expect("first use of nonce");
+        let err = handle(deps.as_mut(), &env, Signed::new(msg, auth)).expect_err("replayed nonce");
+        assert!(matches!(err, Error::InvalidNonce { .. }));
+    }
+
+    /// The response of a Keplr-style `signArbitrary(chain_id, signer, data)` for the
+    /// `Nonced::new(Transfer { amount: 1 }, 0)` msg sent to the `mock_env()` contract, i.e. a
+    /// signature of the sorted (amino JSON) ADR-036 sign doc with `sign/MsgSignData` (as built by
+    /// `makeADR36AminoSignDoc` and `serializeSignDoc`). It is fixed here, so that it catches any
+    /// divergence of the contract's sign doc from what wallets sign.
+    const ADR036_SIGNER: &str = "cosmwasm150rtrmj2f8vl9tem8qpfw36ylw5jg9j2p9jnnn";
+    const ADR036_PUB_KEY: &str = "ApicC3bLVjlx/cm+8x7AbDVg8ySdbunl2DxXYlWW4F9v";
+    const ADR036_SIGNATURE: &str =
+        "ZehZyO0anBj8mOsjWlyopMkkaV/uJDQVsr6jHWWl8ypwPCPvXk9sDFftbZvdHrnU3C4N3XfwsBOKE8AoizKu8Q==";
+
+    #[test]
+    fn test_adr036_signed_msg_is_accepted() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let pk = Binary::from_base64(ADR036_PUB_KEY).expect("valid base64");
+        let sig = Binary::from_base64(ADR036_SIGNATURE).expect("valid base64");
+        let addr = deps
+            .api
+            .addr_humanize(&CanonicalAddr::from(
+                Ripemd160::digest(Sha256::digest(pk.as_slice())).as_slice(),
+            ))
+            .expect("valid canonical addr");
+        assert_eq!(addr.as_str(), ADR036_SIGNER);
+
+        let auth = AnyAuth::new(
+            SignerPubKey::Adr036(pk.to_vec().into()),
+            sig.to_vec().into(),
+        );
+        let msg = Nonced::new(Transfer { amount: 1 }, 0);
+        handle(deps.as_mut(), &env, Signed::new(msg, auth.clone()))
+            .expect("valid ADR-036 signature");
+
+        let other_msg = Nonced::new(Transfer { amount: 2 }, 1);
+        let err = handle(deps.as_mut(), &env, Signed::new(other_msg, auth))
+            .expect_err("signature of another msg");
+        assert!(matches!(err, Error::SignatureVerification(_)));
+    }
+}
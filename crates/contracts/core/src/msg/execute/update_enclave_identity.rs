@@ -0,0 +1,42 @@
+use cosmwasm_std::{HexBinary, StdResult};
+use quartz_contract_core_derive::quartz_msg;
+
+use crate::state::{EnclaveIdentity, MrEnclave, RawEnclaveIdentity};
+
+fn to_mr_enclave(raw: HexBinary) -> StdResult<MrEnclave> {
+    raw.to_array()
+}
+
+fn to_identities(raw: Option<Vec<RawEnclaveIdentity>>) -> StdResult<Option<Vec<EnclaveIdentity>>> {
+    raw.map(|identities| identities.into_iter().map(TryInto::try_into).collect())
+        .transpose()
+}
+
+fn to_raw_identities(identities: Option<Vec<EnclaveIdentity>>) -> Option<Vec<RawEnclaveIdentity>> {
+    identities.map(|identities| identities.into_iter().map(Into::into).collect())
+}
+
+/// Replaces the MRENCLAVE that attestations are verified against, e.g. to ship an enclave upgrade
+/// without redeploying the contract (and losing its encrypted state).
+/// The additional trusted identities are only replaced if specified, which allows rolling
+/// upgrades (i.e. trusting the old and new enclave at the same time).
+/// Only the contract admin is allowed to execute this msg.
+#[quartz_msg]
+pub struct UpdateEnclaveIdentity {
+    #[raw(ty = "HexBinary", try_from = "to_mr_enclave")]
+    mr_enclave: MrEnclave,
+    #[raw(
+        ty = "Option<Vec<RawEnclaveIdentity>>",
+        try_from = "to_identities",
+        into = "to_raw_identities"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    identities: Option<Vec<EnclaveIdentity>>,
+}
+
+impl UpdateEnclaveIdentity {
+    pub fn with_identities(mut self, identities: Vec<EnclaveIdentity>) -> Self {
+        self.identities = Some(identities);
+        self
+    }
+}
@@ -0,0 +1,25 @@
+use cosmwasm_std::StdResult;
+use quartz_contract_core_derive::quartz_msg;
+
+use crate::state::{Quorum, RawQuorum};
+
+fn to_quorum(raw: Option<RawQuorum>) -> StdResult<Option<Quorum>> {
+    raw.map(TryInto::try_into).transpose()
+}
+
+fn to_raw_quorum(quorum: Option<Quorum>) -> Option<RawQuorum> {
+    quorum.map(Into::into)
+}
+
+/// Sets (or removes) the quorum of operators that must agree on attested outputs before they take
+/// effect. Without a quorum, any single trusted enclave's attested output takes effect immediately.
+/// Only the contract admin is allowed to execute this msg.
+#[quartz_msg]
+pub struct UpdateQuorum {
+    #[raw(
+        ty = "Option<RawQuorum>",
+        try_from = "to_quorum",
+        into = "to_raw_quorum"
+    )]
+    quorum: Option<Quorum>,
+}
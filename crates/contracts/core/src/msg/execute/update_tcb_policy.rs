@@ -0,0 +1,13 @@
+use quartz_contract_core_derive::quartz_msg;
+use quartz_tee_ra::intel_sgx::dcap::tcb_policy::TcbPolicy;
+
+use crate::state::RawTcbPolicy;
+
+/// Replaces the policy for the TCB status (and advisories) of the platforms that the enclave is
+/// allowed to run on.
+/// Only the contract admin is allowed to execute this msg.
+#[quartz_msg]
+pub struct UpdateTcbPolicy {
+    #[raw(ty = "RawTcbPolicy")]
+    tcb_policy: TcbPolicy,
+}
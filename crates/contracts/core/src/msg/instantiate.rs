@@ -6,6 +6,7 @@ use crate::{
     msg::{
         execute::attested::{
             Attested, DefaultAttestation, HasUserData, RawAttested, RawDefaultAttestation,
+            UserDataDomain,
         },
         HasDomainType,
     },
@@ -100,4 +101,16 @@ impl HasUserData for CoreInstantiate {
         user_data[0..32].copy_from_slice(&digest);
         user_data
     }
+
+    fn msg_type(&self) -> &'static str {
+        "instantiate"
+    }
+
+    /// The contract doesn't exist when the enclave attests to this msg, so only the chain is bound.
+    fn user_data_for(&self, domain: &UserDataDomain) -> UserData {
+        domain
+            .clone()
+            .without_contract()
+            .bind(self.msg_type(), self.user_data())
+    }
 }
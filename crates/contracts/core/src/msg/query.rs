@@ -1 +1,67 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{HexBinary, Uint64};
 
+use crate::state::{RawConfig, RawEnclaveIdentity, RawQuorum, SessionStatus};
+
+/// Quartz queries that apps can embed in their own `QueryMsg` and dispatch to
+/// [`handle_query`](crate::handler::query::handle_query).
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum Query {
+    #[returns(ConfigResponse)]
+    Config {},
+    #[returns(SessionResponse)]
+    Session {},
+    #[returns(SequenceNumResponse)]
+    SequenceNum {},
+    #[returns(EnclaveIdentityResponse)]
+    EnclaveIdentity {},
+    #[returns(QuorumResponse)]
+    Quorum {},
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub config: RawConfig,
+}
+
+/// The progress of the handshake between the contract and the enclave.
+#[cw_serde]
+pub enum HandshakeStatus {
+    /// No session was created yet (or it was revoked).
+    NotStarted,
+    /// The session was created, but the enclave's pubkey hasn't been set yet.
+    SessionCreated,
+    /// The enclave's pubkey was set, so the contract is ready to be used.
+    Complete,
+}
+
+#[cw_serde]
+pub struct SessionResponse {
+    pub nonce: Option<HexBinary>,
+    pub pub_key: Option<HexBinary>,
+    pub status: HandshakeStatus,
+    /// The session's lifecycle status, `None` if no session was created yet.
+    pub session_status: Option<SessionStatus>,
+}
+
+#[cw_serde]
+pub struct SequenceNumResponse {
+    pub seq_num: Uint64,
+}
+
+/// The identity of the enclave that this contract is paired with.
+#[cw_serde]
+pub struct EnclaveIdentityResponse {
+    pub mr_enclave: HexBinary,
+    /// All identities that the contract trusts attestations from (including `mr_enclave`).
+    pub trusted_identities: Vec<RawEnclaveIdentity>,
+    pub pub_key: Option<HexBinary>,
+}
+
+#[cw_serde]
+pub struct QuorumResponse {
+    pub quorum: Option<RawQuorum>,
+    /// The operators whose replica is registered for the current session.
+    pub registered_operators: Vec<String>,
+}
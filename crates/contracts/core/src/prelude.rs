@@ -1,7 +1,14 @@
 pub use crate::{
-    handler::RawHandler,
+    handler::{
+        execute::attested::AttestedOutcome, migrate::handle_migrate, query::handle_query,
+        RawHandler,
+    },
     msg::{
-        execute::RawExecute as QuartzExecuteMsg,
+        execute::{
+            update_enclave_identity::RawUpdateEnclaveIdentity as QuartzMigrateMsg,
+            RawExecute as QuartzExecuteMsg,
+        },
         instantiate::RawInstantiate as QuartzInstantiateMsg,
+        query::Query as QuartzQueryMsg,
     },
 };
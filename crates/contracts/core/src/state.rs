@@ -1,9 +1,16 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{HexBinary, StdError, Uint64};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Empty, HexBinary, StdError, Uint64};
+use cw_storage_plus::{Item, Map};
+use quartz_tee_ra::intel_sgx::dcap::{
+    tcb_policy::{TcbPolicy, TcbStatus},
+    TrustedIdentity, TrustedMrEnclaveIdentity, TrustedMrSignerIdentity,
+};
 use serde::{Deserialize, Serialize};
 
+use crate::msg::execute::attested::Attestation;
+
 pub type MrEnclave = [u8; 32];
+pub type MrSigner = [u8; 32];
 pub type Nonce = [u8; 32];
 pub type UserData = [u8; 64];
 pub type Hash = [u8; 32];
@@ -13,9 +20,38 @@ pub type TrustThreshold = (u64, u64);
 pub const CONFIG_KEY: &str = "quartz_config";
 pub const SESSION_KEY: &str = "quartz_session";
 pub const SEQUENCE_NUM_KEY: &str = "quartz_seq_num";
+pub const TRUSTED_ANCHOR_KEY: &str = "quartz_trusted_anchor";
+pub const ATTESTED_OUTPUTS_KEY: &str = "quartz_attested_outputs";
+pub const QUORUM_KEY: &str = "quartz_quorum";
+pub const OPERATOR_SESSIONS_KEY: &str = "quartz_operator_sessions";
+pub const PENDING_OUTPUTS_KEY: &str = "quartz_pending_outputs";
+pub const SIGNER_NONCES_KEY: &str = "quartz_signer_nonces";
+pub const ENVELOPES_KEY: &str = "quartz_envelopes";
 pub const CONFIG: Item<RawConfig> = Item::new(CONFIG_KEY);
+/// The default max number of blocks between the height of the chain state that an app output was
+/// produced from and the height at which it is handled (see [`LightClientOpts::max_height_lag`]).
+pub const DEFAULT_MAX_HEIGHT_LAG: u64 = 100;
 pub const SESSION: Item<Session> = Item::new(SESSION_KEY);
 pub const SEQUENCE_NUM: Item<Uint64> = Item::new(SEQUENCE_NUM_KEY);
+pub const TRUSTED_ANCHOR: Item<TrustedAnchor> = Item::new(TRUSTED_ANCHOR_KEY);
+/// The user data of every accepted attested message (for replay protection), keyed by the height
+/// that it committed to (zero if none). Messages that committed to a height that would be rejected
+/// as stale anyway (see [`LightClientOpts::max_height_lag`]) are pruned.
+pub const ATTESTED_OUTPUTS: Map<(u64, &[u8]), Empty> = Map::new(ATTESTED_OUTPUTS_KEY);
+pub const QUORUM: Item<Quorum> = Item::new(QUORUM_KEY);
+/// The session pub key that each operator's enclave registered (see `RegisterOperator`)
+pub const OPERATOR_SESSIONS: Map<&Addr, HexBinary> = Map::new(OPERATOR_SESSIONS_KEY);
+/// The approvals of every attested output (by its user data) that hasn't reached the quorum yet
+pub const PENDING_OUTPUTS: Map<&[u8], PendingOutput> = Map::new(PENDING_OUTPUTS_KEY);
+/// The next nonce that each signer (by pub key) of a `Nonced` user msg must use
+pub const SIGNER_NONCES: Map<&[u8], Uint64> = Map::new(SIGNER_NONCES_KEY);
+/// The digest of every accepted `EncryptedEnvelope` (for replay protection), keyed by the max
+/// sequence number of the envelope. Envelopes that would be rejected as expired anyway are pruned.
+pub const ENVELOPES: Map<(u64, &[u8]), Empty> = Map::new(ENVELOPES_KEY);
+/// The max number of entries pruned from [`ATTESTED_OUTPUTS`] or [`ENVELOPES`] per accepted
+/// message, which keeps the gas cost of a message bounded while still pruning faster than entries
+/// are added.
+pub const PRUNE_LIMIT: usize = 10;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Config {
@@ -23,6 +59,12 @@ pub struct Config {
     light_client_opts: LightClientOpts,
     tcbinfo_contract: Option<String>,
     dcap_verifier_contract: Option<String>,
+    #[serde(default)]
+    identities: Vec<EnclaveIdentity>,
+    #[serde(default)]
+    tcb_policy: TcbPolicy,
+    #[serde(default)]
+    anchor_signer: Option<HexBinary>,
 }
 
 impl Config {
@@ -37,9 +79,30 @@ impl Config {
             light_client_opts,
             tcbinfo_contract,
             dcap_verifier_contract,
+            identities: vec![],
+            tcb_policy: TcbPolicy::default(),
+            anchor_signer: None,
         }
     }
 
+    pub fn with_tcb_policy(mut self, tcb_policy: TcbPolicy) -> Self {
+        self.tcb_policy = tcb_policy;
+        self
+    }
+
+    /// Trusts the given enclave identities in addition to `mr_enclave`.
+    pub fn with_identities(mut self, identities: Vec<EnclaveIdentity>) -> Self {
+        self.identities = identities;
+        self
+    }
+
+    /// Lets the holder of the given (compressed secp256k1) pub key re-anchor the enclave to a
+    /// published trusted anchor, even once its trusted state is past the trusting period.
+    pub fn with_anchor_signer(mut self, anchor_signer: impl Into<HexBinary>) -> Self {
+        self.anchor_signer = Some(anchor_signer.into());
+        self
+    }
+
     pub fn light_client_opts(&self) -> &LightClientOpts {
         &self.light_client_opts
     }
@@ -48,9 +111,44 @@ impl Config {
         self.mr_enclave
     }
 
+    pub fn with_mr_enclave(mut self, mr_enclave: MrEnclave) -> Self {
+        self.mr_enclave = mr_enclave;
+        self
+    }
+
+    /// The policy for the TCB status of the platforms that enclaves are allowed to run on.
+    pub fn tcb_policy(&self) -> &TcbPolicy {
+        &self.tcb_policy
+    }
+
+    /// The additional identities trusted by the config (see [`Config::trusted_identities`]).
+    pub fn identities(&self) -> &[EnclaveIdentity] {
+        &self.identities
+    }
+
+    /// All trusted enclave identities - `mr_enclave` followed by the additional identities.
+    pub fn trusted_identities(&self) -> Vec<EnclaveIdentity> {
+        let mut identities = vec![EnclaveIdentity::MrEnclave(self.mr_enclave)];
+        identities.extend(self.identities.iter().cloned());
+        identities
+    }
+
+    /// Returns true if the attestation was generated by any of the trusted enclaves.
+    pub fn trusts(&self, attestation: &impl Attestation) -> bool {
+        self.trusted_identities()
+            .iter()
+            .any(|identity| identity.matches(attestation))
+    }
+
     pub fn tcbinfo_contract(&self) -> Option<&str> {
         self.tcbinfo_contract.as_deref()
     }
+
+    /// The pub key that must sign a trusted anchor for the enclave to adopt it without verifying it
+    /// from its trusted state (see [`Config::with_anchor_signer`]).
+    pub fn anchor_signer(&self) -> Option<&HexBinary> {
+        self.anchor_signer.as_ref()
+    }
 }
 
 #[cw_serde]
@@ -59,6 +157,12 @@ pub struct RawConfig {
     light_client_opts: RawLightClientOpts,
     tcbinfo_contract: Option<String>,
     dcap_verifier_contract: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    identities: Vec<RawEnclaveIdentity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tcb_policy: Option<RawTcbPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    anchor_signer: Option<HexBinary>,
 }
 
 impl RawConfig {
@@ -78,6 +182,11 @@ impl TryFrom<RawConfig> for Config {
     type Error = StdError;
 
     fn try_from(value: RawConfig) -> Result<Self, Self::Error> {
+        if let Some(anchor_signer) = &value.anchor_signer {
+            k256::PublicKey::from_sec1_bytes(anchor_signer.as_slice())
+                .map_err(|e| StdError::parse_err("anchor_signer", e))?;
+        }
+
         Ok(Self {
             mr_enclave: value.mr_enclave.to_array()?,
             light_client_opts: value
@@ -86,6 +195,13 @@ impl TryFrom<RawConfig> for Config {
                 .map_err(|e| StdError::parse_err("light_client_opts", e))?,
             tcbinfo_contract: value.tcbinfo_contract,
             dcap_verifier_contract: value.dcap_verifier_contract,
+            identities: value
+                .identities
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            tcb_policy: value.tcb_policy.map(Into::into).unwrap_or_default(),
+            anchor_signer: value.anchor_signer,
         })
     }
 }
@@ -97,6 +213,191 @@ impl From<Config> for RawConfig {
             light_client_opts: value.light_client_opts.into(),
             tcbinfo_contract: value.tcbinfo_contract,
             dcap_verifier_contract: value.dcap_verifier_contract,
+            identities: value.identities.into_iter().map(Into::into).collect(),
+            // the default policy is omitted, so that existing configs remain unchanged
+            tcb_policy: (value.tcb_policy != TcbPolicy::default()).then(|| value.tcb_policy.into()),
+            anchor_signer: value.anchor_signer,
+        }
+    }
+}
+
+/// The TCB status of a platform, as reported by Intel's TCB info.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum RawTcbStatus {
+    UpToDate,
+    SwHardeningNeeded,
+    ConfigurationNeeded,
+    ConfigurationAndSwHardeningNeeded,
+    OutOfDate,
+    OutOfDateConfigurationNeeded,
+    Revoked,
+}
+
+impl From<RawTcbStatus> for TcbStatus {
+    fn from(value: RawTcbStatus) -> Self {
+        match value {
+            RawTcbStatus::UpToDate => TcbStatus::UpToDate,
+            RawTcbStatus::SwHardeningNeeded => TcbStatus::SWHardeningNeeded,
+            RawTcbStatus::ConfigurationNeeded => TcbStatus::ConfigurationNeeded,
+            RawTcbStatus::ConfigurationAndSwHardeningNeeded => {
+                TcbStatus::ConfigurationAndSWHardeningNeeded
+            }
+            RawTcbStatus::OutOfDate => TcbStatus::OutOfDate,
+            RawTcbStatus::OutOfDateConfigurationNeeded => TcbStatus::OutOfDateConfigurationNeeded,
+            RawTcbStatus::Revoked => TcbStatus::Revoked,
+        }
+    }
+}
+
+impl From<TcbStatus> for RawTcbStatus {
+    fn from(value: TcbStatus) -> Self {
+        match value {
+            TcbStatus::UpToDate => RawTcbStatus::UpToDate,
+            TcbStatus::SWHardeningNeeded => RawTcbStatus::SwHardeningNeeded,
+            TcbStatus::ConfigurationNeeded => RawTcbStatus::ConfigurationNeeded,
+            TcbStatus::ConfigurationAndSWHardeningNeeded => {
+                RawTcbStatus::ConfigurationAndSwHardeningNeeded
+            }
+            TcbStatus::OutOfDate => RawTcbStatus::OutOfDate,
+            TcbStatus::OutOfDateConfigurationNeeded => RawTcbStatus::OutOfDateConfigurationNeeded,
+            TcbStatus::Revoked => RawTcbStatus::Revoked,
+        }
+    }
+}
+
+/// See [`TcbPolicy`] - the grace period for out-of-date platforms is in seconds.
+#[cw_serde]
+pub struct RawTcbPolicy {
+    accepted_statuses: Vec<RawTcbStatus>,
+    allowed_advisories: Vec<String>,
+    out_of_date_grace_period: Option<u64>,
+}
+
+impl From<RawTcbPolicy> for TcbPolicy {
+    fn from(value: RawTcbPolicy) -> Self {
+        TcbPolicy::new(
+            value.accepted_statuses.into_iter().map(Into::into),
+            value.allowed_advisories,
+            value.out_of_date_grace_period,
+        )
+    }
+}
+
+impl From<TcbPolicy> for RawTcbPolicy {
+    fn from(value: TcbPolicy) -> Self {
+        Self {
+            accepted_statuses: value
+                .accepted_statuses()
+                .iter()
+                .map(|status| (*status).into())
+                .collect(),
+            allowed_advisories: value.allowed_advisories().iter().cloned().collect(),
+            out_of_date_grace_period: value.out_of_date_grace_period(),
+        }
+    }
+}
+
+/// An enclave identity that the contract trusts attestations from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EnclaveIdentity {
+    /// Any enclave with exactly this measurement.
+    MrEnclave(MrEnclave),
+    /// Any enclave signed by this key with the given product ID and (at least) the given security
+    /// version.
+    MrSigner {
+        mr_signer: MrSigner,
+        isv_prod_id: u16,
+        min_isv_svn: u16,
+    },
+}
+
+impl EnclaveIdentity {
+    pub fn matches(&self, attestation: &impl Attestation) -> bool {
+        match self {
+            EnclaveIdentity::MrEnclave(mr_enclave) => attestation.mr_enclave() == *mr_enclave,
+            EnclaveIdentity::MrSigner {
+                mr_signer,
+                isv_prod_id,
+                min_isv_svn,
+            } => {
+                attestation.mr_signer() == *mr_signer
+                    && attestation.isv_prod_id() == *isv_prod_id
+                    && attestation.isv_svn() >= *min_isv_svn
+            }
+        }
+    }
+}
+
+/// The identities carry no advisories, since those are covered by the [`TcbPolicy`].
+impl From<EnclaveIdentity> for TrustedIdentity {
+    fn from(value: EnclaveIdentity) -> Self {
+        match value {
+            EnclaveIdentity::MrEnclave(mr_enclave) => {
+                TrustedMrEnclaveIdentity::new(mr_enclave.into(), [""; 0], [""; 0]).into()
+            }
+            EnclaveIdentity::MrSigner {
+                mr_signer,
+                isv_prod_id,
+                min_isv_svn,
+            } => TrustedMrSignerIdentity::new(
+                mr_signer.into(),
+                isv_prod_id.into(),
+                min_isv_svn.into(),
+                [""; 0],
+                [""; 0],
+            )
+            .into(),
+        }
+    }
+}
+
+#[cw_serde]
+pub enum RawEnclaveIdentity {
+    MrEnclave(HexBinary),
+    MrSigner {
+        mr_signer: HexBinary,
+        isv_prod_id: u16,
+        min_isv_svn: u16,
+    },
+}
+
+impl TryFrom<RawEnclaveIdentity> for EnclaveIdentity {
+    type Error = StdError;
+
+    fn try_from(value: RawEnclaveIdentity) -> Result<Self, Self::Error> {
+        match value {
+            RawEnclaveIdentity::MrEnclave(mr_enclave) => {
+                Ok(EnclaveIdentity::MrEnclave(mr_enclave.to_array()?))
+            }
+            RawEnclaveIdentity::MrSigner {
+                mr_signer,
+                isv_prod_id,
+                min_isv_svn,
+            } => Ok(EnclaveIdentity::MrSigner {
+                mr_signer: mr_signer.to_array()?,
+                isv_prod_id,
+                min_isv_svn,
+            }),
+        }
+    }
+}
+
+impl From<EnclaveIdentity> for RawEnclaveIdentity {
+    fn from(value: EnclaveIdentity) -> Self {
+        match value {
+            EnclaveIdentity::MrEnclave(mr_enclave) => {
+                RawEnclaveIdentity::MrEnclave(mr_enclave.into())
+            }
+            EnclaveIdentity::MrSigner {
+                mr_signer,
+                isv_prod_id,
+                min_isv_svn,
+            } => RawEnclaveIdentity::MrSigner {
+                mr_signer: mr_signer.into(),
+                isv_prod_id,
+                min_isv_svn,
+            },
         }
     }
 }
@@ -107,9 +408,20 @@ pub struct LightClientOpts {
     trusted_height: Height,
     trusted_hash: Hash,
     trust_threshold: TrustThreshold,
+    /// In seconds
     trusting_period: u64,
+    /// In seconds
     max_clock_drift: u64,
+    /// In seconds (used for misbehaviour detection against the witnesses)
     max_block_lag: u64,
+    /// In blocks - the max number of blocks between the height of the chain state that an attested
+    /// message was produced from and the height at which it is handled by the contract
+    #[serde(default = "default_max_height_lag")]
+    max_height_lag: u64,
+}
+
+fn default_max_height_lag() -> u64 {
+    DEFAULT_MAX_HEIGHT_LAG
 }
 
 impl LightClientOpts {
@@ -146,9 +458,17 @@ impl LightClientOpts {
             trusting_period,
             max_clock_drift,
             max_block_lag,
+            max_height_lag: DEFAULT_MAX_HEIGHT_LAG,
         })
     }
 
+    /// Makes the contract reject attested messages produced from chain state that is more than
+    /// `max_height_lag` blocks old (instead of [`DEFAULT_MAX_HEIGHT_LAG`]).
+    pub fn with_max_height_lag(mut self, max_height_lag: u64) -> Self {
+        self.max_height_lag = max_height_lag;
+        self
+    }
+
     pub fn chain_id(&self) -> &String {
         &self.chain_id
     }
@@ -176,6 +496,10 @@ impl LightClientOpts {
     pub fn max_block_lag(&self) -> u64 {
         self.max_block_lag
     }
+
+    pub fn max_height_lag(&self) -> u64 {
+        self.max_height_lag
+    }
 }
 
 #[cw_serde]
@@ -187,13 +511,15 @@ pub struct RawLightClientOpts {
     trusting_period: u64,
     max_clock_drift: u64,
     max_block_lag: u64,
+    /// Defaults to [`DEFAULT_MAX_HEIGHT_LAG`]
+    max_height_lag: Option<u64>,
 }
 
 impl TryFrom<RawLightClientOpts> for LightClientOpts {
     type Error = StdError;
 
     fn try_from(value: RawLightClientOpts) -> Result<Self, Self::Error> {
-        Self::new(
+        let opts = Self::new(
             value.chain_id,
             value.trusted_height,
             value.trusted_hash.to_array()?,
@@ -201,7 +527,12 @@ impl TryFrom<RawLightClientOpts> for LightClientOpts {
             value.trusting_period,
             value.max_clock_drift,
             value.max_block_lag,
-        )
+        )?;
+
+        Ok(Self {
+            max_height_lag: value.max_height_lag.unwrap_or(DEFAULT_MAX_HEIGHT_LAG),
+            ..opts
+        })
     }
 }
 
@@ -215,14 +546,30 @@ impl From<LightClientOpts> for RawLightClientOpts {
             trusting_period: value.trusting_period,
             max_clock_drift: value.max_clock_drift,
             max_block_lag: value.max_block_lag,
+            max_height_lag: Some(value.max_height_lag),
         }
     }
 }
 
+/// The lifecycle of the session between the contract and the enclave.
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum SessionStatus {
+    /// The (initial) handshake is in progress, or complete if the session has a pub key.
+    #[default]
+    Active,
+    /// The admin revoked the session's pub key (see `SessionReset`), so a new handshake is needed.
+    Revoked,
+    /// A new handshake was started after the session was revoked, but hasn't completed yet.
+    PendingReplacement,
+}
+
 #[cw_serde]
 pub struct Session {
     nonce: HexBinary,
     pub_key: Option<HexBinary>,
+    #[serde(default)]
+    status: SessionStatus,
 }
 
 impl Session {
@@ -230,23 +577,199 @@ impl Session {
         Self {
             nonce: nonce.into(),
             pub_key: None,
+            status: SessionStatus::Active,
         }
     }
 
+    /// Starts a new handshake for this session, returns `None` if the session's pub key is still
+    /// in use (i.e. it must be revoked first).
+    pub fn recreate(self, nonce: Nonce) -> Option<Self> {
+        let status = match (self.status, &self.pub_key) {
+            (SessionStatus::Active, None) => SessionStatus::Active,
+            (SessionStatus::Active, Some(_)) => return None,
+            (SessionStatus::Revoked | SessionStatus::PendingReplacement, _) => {
+                SessionStatus::PendingReplacement
+            }
+        };
+
+        Some(Self {
+            status,
+            ..Self::create(nonce)
+        })
+    }
+
     pub fn with_pub_key(mut self, nonce: Nonce, pub_key: Vec<u8>) -> Option<Self> {
-        if self.nonce == nonce && self.pub_key.is_none() {
+        if self.nonce == nonce && self.pub_key.is_none() && self.status != SessionStatus::Revoked {
             self.pub_key = Some(pub_key.into());
+            self.status = SessionStatus::Active;
             Some(self)
         } else {
             None
         }
     }
 
+    /// Drops the session's pub key, e.g. because the enclave lost it.
+    pub fn revoke(self) -> Self {
+        Self {
+            pub_key: None,
+            status: SessionStatus::Revoked,
+            ..self
+        }
+    }
+
     pub fn nonce(&self) -> Nonce {
         self.nonce.to_array().expect("correct by construction")
     }
 
+    pub fn status(&self) -> SessionStatus {
+        self.status
+    }
+
     pub fn pub_key(self) -> Option<HexBinary> {
         self.pub_key
     }
 }
+
+/// A governance approved light client trusted height and hash that the enclave can re-anchor to
+/// (e.g. after being offline for longer than the trusting period).
+#[cw_serde]
+pub struct TrustedAnchor {
+    height: Height,
+    hash: HexBinary,
+}
+
+impl TrustedAnchor {
+    pub fn new(height: Height, hash: Hash) -> Self {
+        Self {
+            height,
+            hash: hash.into(),
+        }
+    }
+
+    pub fn height(&self) -> Height {
+        self.height
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.hash.to_array().expect("correct by construction")
+    }
+}
+
+/// The operators that run the enclave replicas and the number of them that must submit the same
+/// attested output (within `window` blocks of the first submission) for it to take effect.
+#[cw_serde]
+pub struct Quorum {
+    operators: Vec<String>,
+    threshold: u32,
+    window: u64,
+}
+
+impl Quorum {
+    pub fn new(operators: Vec<String>, threshold: u32, window: u64) -> Result<Self, StdError> {
+        let mut operators = operators;
+        operators.sort();
+        operators.dedup();
+
+        if threshold == 0 || threshold as usize > operators.len() {
+            return Err(StdError::generic_err(format!(
+                "quorum threshold must be between 1 and the number of operators ({})",
+                operators.len()
+            )));
+        }
+
+        Ok(Self {
+            operators,
+            threshold,
+            window,
+        })
+    }
+
+    pub fn operators(&self) -> &[String] {
+        &self.operators
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub fn window(&self) -> u64 {
+        self.window
+    }
+
+    pub fn is_operator(&self, addr: &Addr) -> bool {
+        self.operators
+            .iter()
+            .any(|operator| operator == addr.as_str())
+    }
+}
+
+#[cw_serde]
+pub struct RawQuorum {
+    pub operators: Vec<String>,
+    pub threshold: u32,
+    pub window: u64,
+}
+
+impl TryFrom<RawQuorum> for Quorum {
+    type Error = StdError;
+
+    fn try_from(value: RawQuorum) -> Result<Self, Self::Error> {
+        Self::new(value.operators, value.threshold, value.window)
+    }
+}
+
+impl From<Quorum> for RawQuorum {
+    fn from(value: Quorum) -> Self {
+        Self {
+            operators: value.operators,
+            threshold: value.threshold,
+            window: value.window,
+        }
+    }
+}
+
+/// An attested output that was submitted by some, but not (yet) by a quorum of operators.
+#[cw_serde]
+pub struct PendingOutput {
+    since: Height,
+    approvals: Vec<Addr>,
+}
+
+impl PendingOutput {
+    pub fn new(since: Height) -> Self {
+        Self {
+            since,
+            approvals: vec![],
+        }
+    }
+
+    /// The height at which the output was first submitted.
+    pub fn since(&self) -> Height {
+        self.since
+    }
+
+    pub fn approvals(&self) -> &[Addr] {
+        &self.approvals
+    }
+
+    pub fn is_expired(&self, window: u64, height: Height) -> bool {
+        height > self.since.saturating_add(window)
+    }
+
+    /// Records the operator's approval, returns `false` if it had already approved.
+    pub fn approve(&mut self, operator: Addr) -> bool {
+        if self.approvals.contains(&operator) {
+            return false;
+        }
+        self.approvals.push(operator);
+        true
+    }
+
+    /// The number of approvals from operators that are (still) part of the quorum.
+    pub fn approvals_in(&self, quorum: &Quorum) -> usize {
+        self.approvals
+            .iter()
+            .filter(|operator| quorum.is_operator(operator))
+            .count()
+    }
+}
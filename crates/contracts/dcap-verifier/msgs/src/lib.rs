@@ -1,20 +1,83 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::HexBinary;
+use cosmwasm_std::{HexBinary, Timestamp};
 
 #[cw_serde]
 pub struct InstantiateMsg;
 
 #[cw_serde]
-pub enum ExecuteMsg {}
+pub enum ExecuteMsg {
+    /// Verify a DCAP attestation (like the query) and record the [`VerificationReport`] keyed by
+    /// the quote's hash and the hash of the inputs it was verified with (see
+    /// [`VerificationReport::inputs_hash`]). The report is also set as the response's data.
+    VerifyDcapAttestation {
+        quote: HexBinary,
+        collateral: HexBinary,
+        identities: Option<Vec<u8>>,
+        tcb_policy: Option<Vec<u8>>,
+    },
+}
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     /// Verify a DCAP attestation
-    #[returns(())]
+    ///
+    /// If a (CBOR encoded) `tcb_policy` is specified, the TCB status and advisories of the platform
+    /// are checked against it instead of the advisories of the `identities`. The certificates, CRLs,
+    /// TCB info and QE identity of the collateral must be valid at the block time.
+    #[returns(VerificationReport)]
     VerifyDcapAttestation {
         quote: HexBinary,
         collateral: HexBinary,
         identities: Option<Vec<u8>>,
+        tcb_policy: Option<Vec<u8>>,
     },
+    /// Returns the recorded report for the quote with the given (SHA-256) hash, verified with the
+    /// inputs with the given hash (see [`VerificationReport::inputs_hash`]).
+    #[returns(VerificationReport)]
+    GetVerificationReport {
+        quote_hash: HexBinary,
+        inputs_hash: HexBinary,
+    },
+}
+
+/// The outcome of a successful DCAP verification.
+#[cw_serde]
+pub struct VerificationReport {
+    /// The SHA-256 hash of the quote
+    pub quote_hash: HexBinary,
+    /// The SHA-256 hash of the JSON encoded `[collateral, identities, tcb_policy]` that the quote
+    /// was verified with
+    pub inputs_hash: HexBinary,
+    /// The TCB status of the platform, e.g. `UpToDate` or `SWHardeningNeeded`
+    pub tcb_status: String,
+    pub advisory_ids: Vec<String>,
+    /// The TCB level of the TCB info that the platform matched, if it could be determined
+    pub tcb_level: Option<TcbLevel>,
+    pub fmspc: HexBinary,
+    pub mr_enclave: HexBinary,
+    pub mr_signer: HexBinary,
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+    /// The index of the first trusted identity that the enclave matched
+    pub matched_identity: Option<u32>,
+    pub pck_certificate: PckCertificate,
+    /// The block time of the verification
+    pub verified_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct TcbLevel {
+    pub sgx_tcb_components: Vec<u32>,
+    pub pce_svn: u32,
+    pub tcb_date: String,
+}
+
+#[cw_serde]
+pub struct PckCertificate {
+    pub subject: String,
+    pub issuer: String,
+    pub serial_number: String,
+    pub not_before: Timestamp,
+    pub not_after: Timestamp,
 }
@@ -1,13 +1,22 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    to_json_binary, to_json_vec, Binary, Deps, DepsMut, Env, HexBinary, MessageInfo, Response,
+    StdError, StdResult, Timestamp,
+};
+use quartz_dcap_verifier_msgs::{
+    ExecuteMsg, InstantiateMsg, PckCertificate, QueryMsg, TcbLevel, VerificationReport,
 };
-use quartz_dcap_verifier_msgs::{ExecuteMsg, InstantiateMsg, QueryMsg};
 use quartz_tee_ra::{
-    intel_sgx::dcap::{Collateral, Quote3, TrustedIdentity},
+    intel_sgx::dcap::{
+        report, tcb_policy::TcbPolicy, valid_until, verify_with_policy, Collateral, Quote3,
+        TrustedIdentity, VerificationReport as DcapReport,
+    },
     verify_dcap_attestation, Error,
 };
+use sha2::{Digest, Sha256};
+
+use crate::state::{VerificationRecord, REPORTS};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -21,44 +30,509 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    _deps: DepsMut,
-    _env: Env,
+    deps: DepsMut,
+    env: Env,
     _info: MessageInfo,
-    _msg: ExecuteMsg,
+    msg: ExecuteMsg,
 ) -> StdResult<Response> {
-    unimplemented!()
+    match msg {
+        ExecuteMsg::VerifyDcapAttestation {
+            quote,
+            collateral,
+            identities,
+            tcb_policy,
+        } => {
+            let inputs = Inputs::new(quote, collateral, identities, tcb_policy)?;
+            let (report, recorded) = match inputs.recorded(deps.as_ref(), &env)? {
+                Some(report) => (report, true),
+                None => {
+                    let (report, valid_until) = inputs.verify(&env)?;
+                    REPORTS.save(
+                        deps.storage,
+                        inputs.key(),
+                        &VerificationRecord {
+                            valid_until,
+                            report: report.clone(),
+                        },
+                    )?;
+                    (report, false)
+                }
+            };
+
+            Ok(Response::new()
+                .set_data(to_json_binary(&report)?)
+                .add_attribute("action", "verify_dcap_attestation")
+                .add_attribute("quote_hash", report.quote_hash.to_hex())
+                .add_attribute("inputs_hash", report.inputs_hash.to_hex())
+                .add_attribute("tcb_status", &report.tcb_status)
+                .add_attribute("fmspc", report.fmspc.to_hex())
+                .add_attribute("mr_enclave", report.mr_enclave.to_hex())
+                .add_attribute("recorded", recorded.to_string()))
+        }
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::VerifyDcapAttestation {
             quote,
             collateral,
             identities,
+            tcb_policy,
         } => {
-            let quote = Quote3::<Vec<u8>>::try_from(Vec::<u8>::from(quote))
-                .map_err(|e| StdError::generic_err(format!("Quote parse error: {e}")))?;
-            let collateral: Collateral = ciborium::from_reader(collateral.as_slice())
-                .map_err(|e| StdError::generic_err(format!("Collateral deserialize error: {e}")))?;
-            let identities: Vec<TrustedIdentity> = if let Some(identities) = identities {
-                ciborium::from_reader(identities.as_slice())
-                    .map_err(|e| StdError::generic_err(format!("Identities parse error: {e}")))?
-            } else {
-                vec![]
+            let inputs = Inputs::new(quote, collateral, identities, tcb_policy)?;
+            let report = match inputs.recorded(deps, &env)? {
+                Some(report) => report,
+                None => inputs.verify(&env)?.0,
             };
+            to_json_binary(&report)
+        }
+        QueryMsg::GetVerificationReport {
+            quote_hash,
+            inputs_hash,
+        } => to_json_binary(
+            &REPORTS
+                .load(
+                    deps.storage,
+                    (quote_hash.as_slice(), inputs_hash.as_slice()),
+                )?
+                .report,
+        ),
+    }
+}
+
+/// The (raw) inputs of a verification, along with their hashes.
+struct Inputs {
+    quote: HexBinary,
+    collateral: HexBinary,
+    identities: Option<Vec<u8>>,
+    tcb_policy: Option<Vec<u8>>,
+    quote_hash: HexBinary,
+    hash: HexBinary,
+}
+
+impl Inputs {
+    fn new(
+        quote: HexBinary,
+        collateral: HexBinary,
+        identities: Option<Vec<u8>>,
+        tcb_policy: Option<Vec<u8>>,
+    ) -> StdResult<Self> {
+        let quote_hash = Sha256::digest(quote.as_slice()).to_vec().into();
+        let hash = Sha256::digest(to_json_vec(&(&collateral, &identities, &tcb_policy))?)
+            .to_vec()
+            .into();
+        Ok(Self {
+            quote,
+            collateral,
+            identities,
+            tcb_policy,
+            quote_hash,
+            hash,
+        })
+    }
+
+    /// The key of the quote's record for these inputs in [`REPORTS`].
+    fn key(&self) -> (&[u8], &[u8]) {
+        (self.quote_hash.as_slice(), self.hash.as_slice())
+    }
+
+    /// Returns the recorded report for the quote, if it was verified with the same inputs and the
+    /// collateral hasn't expired since.
+    fn recorded(&self, deps: Deps, env: &Env) -> StdResult<Option<VerificationReport>> {
+        Ok(REPORTS
+            .may_load(deps.storage, self.key())?
+            .filter(|record| matches!(record.valid_until, Some(until) if env.block.time < until))
+            .map(|record| record.report))
+    }
+
+    /// Verifies the quote and returns its report, along with the time until which the report may
+    /// be reused (see [`VerificationRecord`]).
+    fn verify(&self, env: &Env) -> StdResult<(VerificationReport, Option<Timestamp>)> {
+        let quote = Quote3::<Vec<u8>>::try_from(self.quote.to_vec())
+            .map_err(|e| StdError::generic_err(format!("Quote parse error: {e}")))?;
+        let collateral: Collateral = ciborium::from_reader(self.collateral.as_slice())
+            .map_err(|e| StdError::generic_err(format!("Collateral deserialize error: {e}")))?;
+        let identities: Vec<TrustedIdentity> = if let Some(identities) = &self.identities {
+            ciborium::from_reader(identities.as_slice())
+                .map_err(|e| StdError::generic_err(format!("Identities parse error: {e}")))?
+        } else {
+            vec![]
+        };
+
+        let mut valid_until = valid_until(&quote, &collateral)
+            .map(Timestamp::from_seconds)
+            .filter(|until| env.block.time < *until);
+
+        let report = if let Some(tcb_policy) = &self.tcb_policy {
+            let tcb_policy: TcbPolicy = ciborium::from_reader(tcb_policy.as_slice())
+                .map_err(|e| StdError::generic_err(format!("TCB policy parse error: {e}")))?;
 
             // attestation handler MUST verify that the user_data and mr_enclave match the config/msg
-            let verification_output =
-                verify_dcap_attestation(quote, collateral, identities.as_slice());
+            let report = verify_with_policy(
+                quote,
+                collateral,
+                identities.as_slice(),
+                &tcb_policy,
+                Some(env.block.time.seconds()),
+            )
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
 
+            // a status that was only accepted within the policy's grace period must be checked
+            // again later
+            if !tcb_policy.accepted_statuses().contains(&report.tcb_status) {
+                valid_until = None;
+            }
+            report
+        } else {
             // attestation handler MUST verify that the user_data and mr_enclave match the config/msg
-            if verification_output.is_success().into() {
-                to_json_binary(&())
-            } else {
-                Err(StdError::generic_err(
+            let verification_output = verify_dcap_attestation(
+                quote.clone(),
+                collateral.clone(),
+                identities.as_slice(),
+                Some(env.block.time.seconds()),
+            )
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+            if !bool::from(verification_output.is_success()) {
+                return Err(StdError::generic_err(
                     Error::Dcap(Box::new(verification_output)).to_string(),
-                ))
+                ));
+            }
+
+            report(quote, collateral, identities.as_slice())
+                .map_err(|e| StdError::generic_err(e.to_string()))?
+        };
+
+        let report = to_msg_report(
+            report,
+            self.quote_hash.clone(),
+            self.hash.clone(),
+            env.block.time,
+        );
+        Ok((report, valid_until))
+    }
+}
+
+fn to_msg_report(
+    report: DcapReport,
+    quote_hash: HexBinary,
+    inputs_hash: HexBinary,
+    verified_at: Timestamp,
+) -> VerificationReport {
+    VerificationReport {
+        quote_hash,
+        inputs_hash,
+        tcb_status: format!("{:?}", report.tcb_status),
+        advisory_ids: report.advisory_ids.into_iter().collect(),
+        tcb_level: report.tcb_level.map(|level| TcbLevel {
+            sgx_tcb_components: level.sgx_tcb_components,
+            pce_svn: level.pce_svn,
+            tcb_date: level.tcb_date,
+        }),
+        fmspc: report.fmspc.to_vec().into(),
+        mr_enclave: report.mr_enclave.to_vec().into(),
+        mr_signer: report.mr_signer.to_vec().into(),
+        isv_prod_id: report.isv_prod_id,
+        isv_svn: report.isv_svn,
+        matched_identity: report
+            .matched_identity
+            .and_then(|index| u32::try_from(index).ok()),
+        pck_certificate: PckCertificate {
+            subject: report.pck_certificate.subject,
+            issuer: report.pck_certificate.issuer,
+            serial_number: report.pck_certificate.serial_number,
+            not_before: Timestamp::from_seconds(report.pck_certificate.not_before),
+            not_after: Timestamp::from_seconds(report.pck_certificate.not_after),
+        },
+        verified_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ciborium::Value as CborValue;
+    use cosmwasm_schema::serde::Serialize;
+    use cosmwasm_std::{
+        from_json,
+        testing::{message_info, mock_dependencies, mock_env},
+        Addr,
+    };
+    use quartz_tee_ra::intel_sgx::dcap::TrustedMrEnclaveIdentity;
+    use x509_cert::{
+        der::{DecodePem, Encode},
+        Certificate,
+    };
+
+    use super::*;
+
+    const MR_ENCLAVE: &str = "840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4";
+    // the (test PKI) collateral is valid from 2023-07-12T21:00:00Z to 2023-08-01T00:00:00Z, when
+    // its PCK CRL goes stale, and its TCB info until 2023-08-11T19:56:44Z
+    const VALID_TIME: u64 = 1689206400; // 2023-07-13
+    const PCK_CRL_NEXT_UPDATE: u64 = 1690848000; // 2023-08-01
+    const STALE_CRL_TIME: u64 = 1691193600; // 2023-08-05
+    const EXPIRED_TIME: u64 = 1691884800; // 2023-08-13
+    const OTHER_MR_ENCLAVE: &str =
+        "0000000000000000000000000000000000000000000000000000000000000000";
+
+    fn cbor<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        ciborium::into_writer(value, &mut buffer).expect("serializable");
+        buffer
+    }
+
+    fn der_chain(pems: &[&str]) -> CborValue {
+        CborValue::Array(
+            pems.iter()
+                .map(|pem| {
+                    let cert = Certificate::from_pem(pem).expect("valid certificate");
+                    CborValue::Bytes(cert.to_der().expect("encodable certificate"))
+                })
+                .collect(),
+        )
+    }
+
+    // Intel's collateral is never valid all at once, so this is the same collateral re-signed by a
+    // test PKI (see the `quartz-tee-ra` tests)
+    fn collateral() -> HexBinary {
+        let root_ca = include_str!("../../tee-ra/data/test_pki/root_ca.pem");
+        let processor_ca = include_str!("../../tee-ra/data/test_pki/processor_ca.pem");
+        let tcb_signer = include_str!("../../tee-ra/data/test_pki/tcb_signer.pem");
+        let field = |name: &str, value| (CborValue::Text(name.to_string()), value);
+
+        cbor(&CborValue::Map(vec![
+            field(
+                "root_ca_crl",
+                CborValue::Bytes(
+                    include_bytes!("../../tee-ra/data/test_pki/root_crl.der").to_vec(),
+                ),
+            ),
+            field("pck_crl_issuer_chain", der_chain(&[processor_ca, root_ca])),
+            field(
+                "pck_crl",
+                CborValue::Bytes(
+                    include_bytes!("../../tee-ra/data/test_pki/processor_crl.der").to_vec(),
+                ),
+            ),
+            field("tcb_issuer_chain", der_chain(&[tcb_signer, root_ca])),
+            field(
+                "tcb_info",
+                CborValue::Text(
+                    include_str!("../../tee-ra/data/test_pki/tcb_info.json").to_string(),
+                ),
+            ),
+            field(
+                "qe_identity_issuer_chain",
+                der_chain(&[tcb_signer, root_ca]),
+            ),
+            field(
+                "qe_identity",
+                CborValue::Text(
+                    include_str!("../../tee-ra/data/test_pki/qe_identity.json").to_string(),
+                ),
+            ),
+        ]))
+        .into()
+    }
+
+    fn identities(mr_enclaves: &[&str]) -> Vec<u8> {
+        let identities: Vec<TrustedIdentity> = mr_enclaves
+            .iter()
+            .map(|mr_enclave| {
+                let mr_enclave: [u8; 32] = HexBinary::from_hex(mr_enclave)
+                    .expect("valid hex")
+                    .to_array()
+                    .expect("valid MRENCLAVE");
+                TrustedMrEnclaveIdentity::new(
+                    mr_enclave.into(),
+                    [""; 0],
+                    ["INTEL-SA-00334", "INTEL-SA-00615"],
+                )
+                .into()
+            })
+            .collect();
+        cbor(&identities)
+    }
+
+    fn verify_msg(identities: &[&str]) -> ExecuteMsg {
+        ExecuteMsg::VerifyDcapAttestation {
+            quote: include_bytes!("../../tee-ra/data/test_pki/quote.dat")
+                .to_vec()
+                .into(),
+            collateral: collateral(),
+            identities: Some(self::identities(identities)),
+            tcb_policy: None,
+        }
+    }
+
+    fn verify_with_policy_msg(identities: &[&str]) -> ExecuteMsg {
+        let ExecuteMsg::VerifyDcapAttestation {
+            quote,
+            collateral,
+            identities,
+            ..
+        } = verify_msg(identities);
+        ExecuteMsg::VerifyDcapAttestation {
+            quote,
+            collateral,
+            identities,
+            tcb_policy: Some(cbor(&TcbPolicy::default())),
+        }
+    }
+
+    fn recorded_report(deps: Deps, env: &Env, report: &VerificationReport) -> VerificationReport {
+        let report = query(
+            deps,
+            env.clone(),
+            QueryMsg::GetVerificationReport {
+                quote_hash: report.quote_hash.clone(),
+                inputs_hash: report.inputs_hash.clone(),
+            },
+        )
+        .expect("report was recorded");
+        from_json(report).expect("valid report")
+    }
+
+    fn recorded(res: &Response) -> bool {
+        res.attributes
+            .iter()
+            .any(|attr| attr.key == "recorded" && attr.value == "true")
+    }
+
+    #[test]
+    fn verification_is_recorded_by_quote_and_inputs_hash() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(VALID_TIME);
+        let info = message_info(&Addr::unchecked("verifier"), &[]);
+
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            verify_msg(&[MR_ENCLAVE]),
+        )
+        .expect("valid attestation");
+        assert!(!recorded(&res));
+        let report: VerificationReport =
+            from_json(res.data.expect("report is set as data")).expect("valid report");
+        assert_eq!(report.mr_enclave.to_hex(), MR_ENCLAVE);
+        assert_eq!(report.fmspc.to_hex(), "00906ed50000");
+        assert_eq!(report.matched_identity, Some(0));
+        assert_eq!(report.verified_at, env.block.time);
+        assert_eq!(recorded_report(deps.as_ref(), &env, &report), report);
+
+        // the same inputs reuse the record while the collateral is valid
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(60);
+        let res = execute(
+            deps.as_mut(),
+            later.clone(),
+            info.clone(),
+            verify_msg(&[MR_ENCLAVE]),
+        )
+        .expect("valid attestation");
+        assert!(recorded(&res));
+        let reused: VerificationReport =
+            from_json(res.data.expect("report is set as data")).expect("valid report");
+        assert_eq!(reused, report);
+
+        // verifying the quote with other inputs is recorded separately
+        let res = execute(
+            deps.as_mut(),
+            later.clone(),
+            info.clone(),
+            verify_msg(&[OTHER_MR_ENCLAVE, MR_ENCLAVE]),
+        )
+        .expect("valid attestation");
+        assert!(!recorded(&res));
+        let other: VerificationReport =
+            from_json(res.data.expect("report is set as data")).expect("valid report");
+        assert_eq!(other.matched_identity, Some(1));
+        assert_eq!(other.verified_at, later.block.time);
+        assert_eq!(other.quote_hash, report.quote_hash);
+        assert_ne!(other.inputs_hash, report.inputs_hash);
+        assert_eq!(recorded_report(deps.as_ref(), &later, &other), other);
+
+        // ... and leaves the record made with the first inputs untouched
+        assert_eq!(recorded_report(deps.as_ref(), &later, &report), report);
+    }
+
+    #[test]
+    fn recorded_report_is_reused_until_it_expires() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(VALID_TIME);
+        let info = message_info(&Addr::unchecked("verifier"), &[]);
+
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            verify_msg(&[MR_ENCLAVE]),
+        )
+        .expect("valid attestation");
+        let report: VerificationReport =
+            from_json(res.data.expect("report is set as data")).expect("valid report");
+        let ExecuteMsg::VerifyDcapAttestation {
+            quote,
+            collateral,
+            identities,
+            tcb_policy,
+        } = verify_msg(&[MR_ENCLAVE]);
+        let inputs = Inputs::new(quote, collateral, identities, tcb_policy).expect("valid inputs");
+        let record = REPORTS
+            .load(&deps.storage, inputs.key())
+            .expect("report was recorded");
+        // the PCK CRL's nextUpdate is the earliest expiry of the collateral
+        let valid_until = Timestamp::from_seconds(PCK_CRL_NEXT_UPDATE);
+        assert_eq!(record.valid_until, Some(valid_until));
+
+        let mut later = env.clone();
+        later.block.time = valid_until.minus_seconds(1);
+        let res = execute(
+            deps.as_mut(),
+            later.clone(),
+            info.clone(),
+            verify_msg(&[MR_ENCLAVE]),
+        )
+        .expect("valid attestation");
+        assert!(recorded(&res));
+        let reused: VerificationReport =
+            from_json(res.data.expect("report is set as data")).expect("valid report");
+        assert_eq!(reused, report);
+
+        later.block.time = valid_until;
+        let res = execute(
+            deps.as_mut(),
+            later.clone(),
+            info,
+            verify_msg(&[MR_ENCLAVE]),
+        )
+        .expect("valid attestation");
+        assert!(!recorded(&res));
+        let report: VerificationReport =
+            from_json(res.data.expect("report is set as data")).expect("valid report");
+        assert_eq!(report.verified_at, later.block.time);
+    }
+
+    #[test]
+    fn expired_collateral_is_rejected() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = message_info(&Addr::unchecked("verifier"), &[]);
+
+        for (time, reason) in [
+            (STALE_CRL_TIME, "the PCK CRL is past its nextUpdate"),
+            (EXPIRED_TIME, "the TCB info and QE identity have expired"),
+        ] {
+            env.block.time = Timestamp::from_seconds(time);
+            for msg in [
+                verify_msg(&[MR_ENCLAVE]),
+                verify_with_policy_msg(&[MR_ENCLAVE]),
+            ] {
+                execute(deps.as_mut(), env.clone(), info.clone(), msg).expect_err(reason);
             }
         }
     }
@@ -0,0 +1,20 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Timestamp;
+use cw_storage_plus::Map;
+use quartz_dcap_verifier_msgs::VerificationReport;
+
+/// A recorded verification.
+///
+/// The report is only reused (for the same quote and inputs) until `valid_until`, i.e. the earliest
+/// expiry of the collateral and certificates. If that can't be determined (or the report depends on
+/// the time in other ways), it is never reused.
+#[cw_serde]
+pub struct VerificationRecord {
+    pub valid_until: Option<Timestamp>,
+    pub report: VerificationReport,
+}
+
+/// The recorded verifications, keyed by the SHA-256 hash of the quote and the hash of the inputs
+/// (i.e. the collateral, identities and TCB policy) that it was verified with, so a verification
+/// with other inputs never overwrites a record.
+pub const REPORTS: Map<(&[u8], &[u8]), VerificationRecord> = Map::new("reports");
@@ -1,25 +1,149 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, HexBinary, Timestamp};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub root_cert: String,
+    /// The admin that may rotate the root certificate; defaults to the instantiator.
+    pub owner: Option<String>,
 }
 
 #[cw_serde]
-pub struct ExecuteMsg {
-    pub tcb_info: String,
-    pub certificate: String,
-    pub time: Option<String>,
+pub enum ExecuteMsg {
+    /// Submits a TCB info signed by the TCB signing `certificate`.
+    SubmitTcbInfo {
+        tcb_info: String,
+        certificate: String,
+        time: Option<String>,
+    },
+    /// Submits a QE identity signed by the TCB signing `certificate`.
+    SubmitQeIdentity {
+        qe_identity: String,
+        certificate: String,
+        time: Option<String>,
+    },
+    /// Submits a (DER-encoded) root CA CRL, signed by the root certificate.
+    SubmitRootCrl { crl: HexBinary },
+    /// Submits a (DER-encoded) PCK CRL, signed by the PCK CA `issuer` certificate.
+    SubmitPckCrl { crl: HexBinary, issuer: String },
+    /// Makes `root_cert` the current root certificate. All previously accepted roots remain
+    /// accepted for (at most) `transition_period` seconds, so that collateral issued under them
+    /// can still be submitted while the root is being rotated. Only the owner may do this.
+    UpdateRootCertificate {
+        root_cert: String,
+        transition_period: Option<u64>,
+    },
+    /// Transfers, accepts or renounces the ownership of the contract.
+    UpdateOwnership(OwnershipAction),
+}
+
+/// Ownership changes, following `cw-ownable`: the owner proposes a new owner, who must then accept
+/// the ownership (before the optional `expiry`).
+#[cw_serde]
+pub enum OwnershipAction {
+    TransferOwnership {
+        new_owner: String,
+        expiry: Option<Timestamp>,
+    },
+    AcceptOwnership,
+    RenounceOwnership,
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    /// Returns the latest TCB info for the FMSPC, failing if it has expired (i.e. its `nextUpdate`
+    /// has passed).
     #[returns(GetTcbInfoResponse)]
     GetTcbInfo { fmspc: String },
+    /// Lists the FMSPCs that have a TCB info, in ascending order.
+    #[returns(ListFmspcsResponse)]
+    ListFmspcs {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all stored TCB infos for the FMSPC, newest first.
+    #[returns(GetTcbInfoHistoryResponse)]
+    GetTcbInfoHistory { fmspc: String },
+    /// Returns the TCB info for the FMSPC with the given `tcbEvaluationDataNumber`.
+    #[returns(TcbInfoEntry)]
+    GetTcbInfoAt {
+        fmspc: String,
+        evaluation_number: u32,
+    },
+    /// Returns the latest QE identity, failing if it has expired.
+    #[returns(GetQeIdentityResponse)]
+    GetQeIdentity {},
+    /// Returns the root CA CRL, failing if it has expired.
+    #[returns(GetCrlResponse)]
+    GetRootCrl {},
+    /// Returns the PCK CRL issued by the CA with the given subject (e.g. the
+    /// `Intel SGX PCK Processor CA`), failing if it has expired.
+    #[returns(GetCrlResponse)]
+    GetPckCrl { issuer: String },
+    /// Returns the current owner and the pending ownership transfer, if any.
+    #[returns(Ownership)]
+    Ownership {},
+    /// Returns the root certificates that are currently accepted, the current one first.
+    #[returns(RootCertificatesResponse)]
+    RootCertificates {},
 }
 
 #[cw_serde]
 pub struct GetTcbInfoResponse {
     pub tcb_info: String,
 }
+
+#[cw_serde]
+pub struct ListFmspcsResponse {
+    pub fmspcs: Vec<String>,
+}
+
+#[cw_serde]
+pub struct GetTcbInfoHistoryResponse {
+    pub entries: Vec<TcbInfoEntry>,
+}
+
+#[cw_serde]
+pub struct TcbInfoEntry {
+    pub fmspc: String,
+    pub evaluation_number: u32,
+    pub issue_date: String,
+    pub next_update: String,
+    pub tcb_info: String,
+}
+
+#[cw_serde]
+pub struct GetQeIdentityResponse {
+    pub qe_identity: String,
+}
+
+#[cw_serde]
+pub struct GetCrlResponse {
+    /// The DER-encoded CRL
+    pub crl: HexBinary,
+    /// The PEM-encoded certificate of the CRL's issuer
+    pub issuer: String,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct Ownership {
+    /// The owner, or `None` if the ownership was renounced
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+    pub pending_expiry: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct RootCertificatesResponse {
+    pub roots: Vec<RootCertificate>,
+}
+
+#[cw_serde]
+pub struct RootCertificate {
+    /// The PEM-encoded root certificate
+    pub certificate: String,
+    /// The end of the root's transition window, or `None` for the current root
+    pub accepted_until: Option<Timestamp>,
+}
@@ -1,18 +1,25 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, HexBinary, MessageInfo, Order, Response,
+};
 use cw2::set_contract_version;
 use der::{DateTime, DecodePem};
-use mc_attestation_verifier::{CertificateChainVerifier, SignedTcbInfo};
+use mc_attestation_verifier::{CertificateChainVerifier, SignedQeIdentity, SignedTcbInfo};
 use p256::ecdsa::VerifyingKey;
-use quartz_tcbinfo_msgs::{ExecuteMsg, GetTcbInfoResponse, InstantiateMsg, QueryMsg};
+use quartz_tcbinfo_msgs::{
+    ExecuteMsg, GetCrlResponse, GetQeIdentityResponse, GetTcbInfoHistoryResponse,
+    GetTcbInfoResponse, InstantiateMsg, ListFmspcsResponse, QueryMsg, RootCertificatesResponse,
+    TcbInfoEntry,
+};
 use quartz_tee_ra::intel_sgx::dcap::certificate_chain::TlsCertificateChainVerifier;
 use serde_json::Value;
 use x509_cert::Certificate;
 
 use crate::{
     error::ContractError,
-    state::{TcbInfo, DATABASE, ROOT_CERTIFICATE},
+    ownership,
+    state::{TcbInfo, DATABASE, LATEST, ROOT_CERTIFICATE},
 };
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:quartz_tcbinfo";
@@ -22,98 +29,513 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    let root = Certificate::from_pem(&msg.root_cert).expect("could not parse PEM");
-    let verifier = TlsCertificateChainVerifier::new(&msg.root_cert);
-    verifier
-        .verify_certificate_chain(vec![&root], vec![], None)
-        .map_err(|_| ContractError::CertificateVerificationError)?;
+    execute::verify_root_certificate(&msg.root_cert)?;
     ROOT_CERTIFICATE
         .save(deps.storage, &msg.root_cert.to_string())
         .map_err(ContractError::Std)?;
-    assert!(DATABASE.is_empty(deps.storage));
-    Ok(Response::default())
+    if !DATABASE.is_empty(deps.storage) {
+        return Err(ContractError::DatabaseNotEmpty);
+    }
+
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    let ownership = ownership::initialize_owner(deps.storage, owner)?;
+    Ok(ownership::ownership_attributes(
+        &ownership,
+        Response::new().add_attribute("action", "instantiate"),
+    ))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    let signed_tcb_info: SignedTcbInfo =
-        SignedTcbInfo::try_from(msg.tcb_info.as_ref()).expect("failed to parse TCBInfo");
-    let raw_root = ROOT_CERTIFICATE.load(deps.storage).unwrap();
-    let root = Certificate::from_pem(raw_root.clone()).expect("could not parse PEM");
-    let verifier = TlsCertificateChainVerifier::new(&raw_root);
-    let fmspc = execute::get_fmspc(&msg.tcb_info);
-    let certificate = Certificate::from_pem(msg.certificate.clone()).expect("failed to parse PEM");
-
-    let time = msg
-        .time
-        .map(|time| time.parse::<DateTime>().expect("could not parse datetime"));
-
-    assert!(
-        execute::check_certificate_validity(&root, time),
-        "On-chain root certificate validity check failed"
-    );
-    assert!(
-        execute::check_certificate_validity(&certificate, time),
-        "Certificate validity check failed"
-    );
-
-    let key = VerifyingKey::from_sec1_bytes(
-        certificate
-            .tbs_certificate
-            .subject_public_key_info
-            .subject_public_key
-            .as_bytes()
-            .expect("Failed to parse public key"),
-    )
-    .expect("Failed to decode public key");
+    match msg {
+        ExecuteMsg::SubmitTcbInfo {
+            tcb_info,
+            certificate,
+            time,
+        } => execute::submit_tcb_info(deps, env, tcb_info, certificate, time),
+        ExecuteMsg::SubmitQeIdentity {
+            qe_identity,
+            certificate,
+            time,
+        } => execute::submit_qe_identity(deps, env, qe_identity, certificate, time),
+        ExecuteMsg::SubmitRootCrl { crl } => execute::submit_root_crl(deps, env, crl),
+        ExecuteMsg::SubmitPckCrl { crl, issuer } => execute::submit_pck_crl(deps, env, crl, issuer),
+        ExecuteMsg::UpdateRootCertificate {
+            root_cert,
+            transition_period,
+        } => execute::update_root_certificate(deps, env, info, root_cert, transition_period),
+        ExecuteMsg::UpdateOwnership(action) => {
+            ownership::update_ownership(deps, &env.block, &info.sender, action)
+        }
+    }
+}
+
+pub mod execute {
+    use std::time::Duration;
+
+    use der::Decode;
+    use quartz_tcbinfo_msgs::RootCertificate;
+    use quartz_tee_ra::intel_sgx::dcap::certificate_chain::verify_crl_signature;
+    use x509_cert::crl::CertificateList;
+
+    use super::*;
+    use crate::state::{
+        Crl, Fmspc, QeIdentity, RetiringRoot, PCK_CRLS, QE_IDENTITY, RETIRING_ROOTS, ROOT_CRL,
+    };
+
+    /// The fields of a TCB info that determine where it is stored and when it is valid.
+    pub struct TcbInfoHeader {
+        pub fmspc: Fmspc,
+        pub evaluation_number: u32,
+        pub issue_date: DateTime,
+        pub next_update: DateTime,
+    }
 
-    verifier
-        .verify_certificate_chain(vec![&certificate, &root], vec![], None)
-        .map_err(|_| ContractError::CertificateVerificationError)?;
+    pub fn submit_tcb_info(
+        deps: DepsMut,
+        env: Env,
+        tcb_info: String,
+        certificate: String,
+        time: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let header = parse_header(&tcb_info)?;
+        check_freshness(
+            "tcbinfo",
+            header.issue_date,
+            Some(header.next_update),
+            block_time(&env)?,
+        )?;
+        ensure_newer(deps.as_ref(), &header)?;
+        let signed_tcb_info: SignedTcbInfo = SignedTcbInfo::try_from(tcb_info.as_ref())
+            .map_err(|_| ContractError::TcbInfoReadError)?;
 
-    signed_tcb_info
-        .verify(Some(&key), time)
-        .map_err(|_| ContractError::TcbInfoVerificationError)?;
+        let time = parse_time(time)?;
+        let key = signing_key(deps.as_ref(), &env, &certificate, time)?;
+        signed_tcb_info
+            .verify(Some(&key), time)
+            .map_err(|_| ContractError::TcbInfoVerificationError)?;
 
-    let _ = DATABASE
-        .save(
+        DATABASE.save(
             deps.storage,
-            fmspc,
+            (header.fmspc.as_slice(), header.evaluation_number),
             &TcbInfo {
-                info: msg.tcb_info.to_string(),
+                info: tcb_info,
+                issue_date: header.issue_date.to_string(),
+                next_update: header.next_update.to_string(),
                 //  certificate: msg.certificate.to_string(),
             },
-        )
-        .map_err(ContractError::Std);
+        )?;
+        LATEST.save(deps.storage, header.fmspc, &header.evaluation_number)?;
 
-    Ok(Response::default())
-}
+        Ok(Response::new()
+            .add_attribute("action", "submit_tcb_info")
+            .add_attribute("fmspc", hex::encode(header.fmspc))
+            .add_attribute("evaluation_number", header.evaluation_number.to_string()))
+    }
 
-pub mod execute {
-    use super::*;
+    pub fn submit_qe_identity(
+        deps: DepsMut,
+        env: Env,
+        qe_identity: String,
+        certificate: String,
+        time: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let (evaluation_number, issue_date, next_update) = parse_signed_body(
+            &qe_identity,
+            "enclaveIdentity",
+            ContractError::QeIdentityReadError,
+        )?;
+        check_freshness(
+            "QE identity",
+            issue_date,
+            Some(next_update),
+            block_time(&env)?,
+        )?;
+        if let Some(stored) = QE_IDENTITY.may_load(deps.storage)? {
+            let stored_issue_date = parse_date(&stored.issue_date)?;
+            if evaluation_number < stored.evaluation_number
+                || (evaluation_number == stored.evaluation_number
+                    && issue_date <= stored_issue_date)
+            {
+                return Err(ContractError::StaleCollateral("QE identity".to_string()));
+            }
+        }
+        let signed_qe_identity = SignedQeIdentity::try_from(qe_identity.as_ref())
+            .map_err(|_| ContractError::QeIdentityReadError)?;
+
+        let time = parse_time(time)?;
+        let key = signing_key(deps.as_ref(), &env, &certificate, time)?;
+        signed_qe_identity
+            .verify(Some(&key), time)
+            .map_err(|_| ContractError::QeIdentityVerificationError)?;
+
+        QE_IDENTITY.save(
+            deps.storage,
+            &QeIdentity {
+                info: qe_identity,
+                evaluation_number,
+                issue_date: issue_date.to_string(),
+                next_update: next_update.to_string(),
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "submit_qe_identity")
+            .add_attribute("evaluation_number", evaluation_number.to_string()))
+    }
+
+    pub fn submit_root_crl(
+        deps: DepsMut,
+        env: Env,
+        crl: HexBinary,
+    ) -> Result<Response, ContractError> {
+        let stored = ROOT_CRL.may_load(deps.storage)?;
+        let crl = with_accepted_root(deps.as_ref(), &env, |root| {
+            verified_crl(&env, crl.clone(), root, stored.clone())
+        })?;
+        ROOT_CRL.save(deps.storage, &crl)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "submit_root_crl")
+            .add_attribute("this_update", crl.this_update))
+    }
+
+    pub fn submit_pck_crl(
+        deps: DepsMut,
+        env: Env,
+        crl: HexBinary,
+        issuer: String,
+    ) -> Result<Response, ContractError> {
+        // the issuer must be a (currently valid) intermediate CA of the root
+        let issuer_subject = parse_certificate(&issuer)?
+            .tbs_certificate
+            .subject
+            .to_string();
+        signing_key(deps.as_ref(), &env, &issuer, Some(block_time(&env)?))?;
+
+        let stored = PCK_CRLS.may_load(deps.storage, &issuer_subject)?;
+        let crl = verified_crl(&env, crl, &issuer, stored)?;
+        PCK_CRLS.save(deps.storage, &issuer_subject, &crl)?;
 
-    pub fn get_fmspc(tcbinfo: &str) -> [u8; 6] {
-        let tcbinfo_raw: Value = serde_json::from_str(tcbinfo).expect("could not read tcbinfo");
-        let fmspc_raw = hex::decode(
-            tcbinfo_raw
-                .get("tcbInfo")
-                .unwrap()
-                .get("fmspc")
-                .unwrap()
+        Ok(Response::new()
+            .add_attribute("action", "submit_pck_crl")
+            .add_attribute("issuer", issuer_subject)
+            .add_attribute("this_update", crl.this_update))
+    }
+
+    pub fn update_root_certificate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        root_cert: String,
+        transition_period: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        ownership::assert_owner(deps.storage, &info.sender)?;
+        let root_subject = verify_root_certificate(&root_cert)?;
+
+        // the previous roots remain accepted until the end of the transition window (if any), but
+        // never longer than they already were
+        let now = env.block.time;
+        let mut retiring = match transition_period {
+            Some(transition_period) => {
+                let accepted_until = now.plus_seconds(transition_period);
+                let mut retiring: Vec<_> = RETIRING_ROOTS
+                    .may_load(deps.storage)?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|root| root.accepted_until > now)
+                    .map(|root| RetiringRoot {
+                        accepted_until: root.accepted_until.min(accepted_until),
+                        ..root
+                    })
+                    .collect();
+                retiring.push(RetiringRoot {
+                    certificate: ROOT_CERTIFICATE.load(deps.storage)?,
+                    accepted_until,
+                });
+                retiring
+            }
+            None => vec![],
+        };
+        retiring.retain(|root| root.certificate != root_cert);
+        RETIRING_ROOTS.save(deps.storage, &retiring)?;
+        ROOT_CERTIFICATE.save(deps.storage, &root_cert)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_root_certificate")
+            .add_attribute("root_subject", root_subject)
+            .add_attribute("retiring_roots", retiring.len().to_string())
+            .add_attribute(
+                "transition_period",
+                transition_period.map_or_else(|| "none".to_string(), |period| period.to_string()),
+            ))
+    }
+
+    /// Checks that `root_cert` (PEM) is a valid self-signed certificate and returns its subject.
+    pub fn verify_root_certificate(root_cert: &str) -> Result<String, ContractError> {
+        let root = parse_certificate(root_cert)?;
+        let verifier = TlsCertificateChainVerifier::new(root_cert);
+        verifier
+            .verify_certificate_chain(vec![&root], vec![], None)
+            .map_err(|_| ContractError::CertificateVerificationError)?;
+        Ok(root.tbs_certificate.subject.to_string())
+    }
+
+    /// Returns the current root certificate, followed by the retiring roots that are still within
+    /// their transition window.
+    pub fn accepted_roots(deps: Deps, env: &Env) -> Result<Vec<RootCertificate>, ContractError> {
+        let current = RootCertificate {
+            certificate: ROOT_CERTIFICATE.load(deps.storage)?,
+            accepted_until: None,
+        };
+        let retiring = RETIRING_ROOTS
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|root| root.accepted_until >= env.block.time)
+            .map(|root| RootCertificate {
+                certificate: root.certificate,
+                accepted_until: Some(root.accepted_until),
+            });
+        Ok(std::iter::once(current).chain(retiring).collect())
+    }
+
+    /// Runs `f` with each accepted root certificate (PEM) and returns the first success, or the
+    /// error for the current root if none of the roots is accepted.
+    fn with_accepted_root<T>(
+        deps: Deps,
+        env: &Env,
+        f: impl Fn(&str) -> Result<T, ContractError>,
+    ) -> Result<T, ContractError> {
+        let mut first_err = None;
+        for root in accepted_roots(deps, env)? {
+            match f(&root.certificate) {
+                Ok(res) => return Ok(res),
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+        Err(first_err.unwrap_or(ContractError::CertificateVerificationError))
+    }
+
+    /// Parses the CRL and checks that it was signed by `issuer` (PEM), is currently valid and is
+    /// newer than the `stored` one.
+    fn verified_crl(
+        env: &Env,
+        crl: HexBinary,
+        issuer: &str,
+        stored: Option<Crl>,
+    ) -> Result<Crl, ContractError> {
+        let certificate = parse_certificate(issuer)?;
+        let list = CertificateList::from_der(crl.as_slice())
+            .map_err(|e| ContractError::CrlReadError(e.to_string()))?;
+        if list.tbs_cert_list.issuer != certificate.tbs_certificate.subject {
+            return Err(ContractError::CrlVerificationError);
+        }
+        verify_crl_signature(crl.as_slice(), &certificate)
+            .map_err(|_| ContractError::CrlVerificationError)?;
+
+        let this_update = list.tbs_cert_list.this_update.to_date_time();
+        let next_update = list
+            .tbs_cert_list
+            .next_update
+            .map(|next_update| next_update.to_date_time());
+        check_freshness("CRL", this_update, next_update, block_time(env)?)?;
+        // a CRL of another issuer (i.e. a rotated root) may be older than the stored one
+        if let Some(stored) = stored.filter(|stored| stored.issuer == issuer) {
+            if this_update <= parse_date(&stored.this_update)? {
+                return Err(ContractError::StaleCollateral("CRL".to_string()));
+            }
+        }
+
+        Ok(Crl {
+            crl,
+            issuer: issuer.to_string(),
+            this_update: this_update.to_string(),
+            next_update: next_update.map(|next_update| next_update.to_string()),
+        })
+    }
+
+    /// Verifies that `certificate` (PEM) is issued by one of the accepted root certificates and
+    /// returns its public key.
+    fn signing_key(
+        deps: Deps,
+        env: &Env,
+        certificate: &str,
+        time: Option<DateTime>,
+    ) -> Result<VerifyingKey, ContractError> {
+        with_accepted_root(deps, env, |root| signing_key_under(root, certificate, time))
+    }
+
+    fn signing_key_under(
+        raw_root: &str,
+        certificate: &str,
+        time: Option<DateTime>,
+    ) -> Result<VerifyingKey, ContractError> {
+        let root = parse_certificate(raw_root)?;
+        let verifier = TlsCertificateChainVerifier::new(raw_root);
+        let certificate = parse_certificate(certificate)?;
+
+        if !check_certificate_validity(&root, time) {
+            return Err(ContractError::RootCertificateValidityError);
+        }
+        if !check_certificate_validity(&certificate, time) {
+            return Err(ContractError::CertificateValidityError);
+        }
+
+        let key = certificate
+            .tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .as_bytes()
+            .and_then(|key| VerifyingKey::from_sec1_bytes(key).ok())
+            .ok_or(ContractError::PublicKeyReadError)?;
+
+        verifier
+            .verify_certificate_chain(vec![&certificate, &root], vec![], None)
+            .map_err(|_| ContractError::CertificateVerificationError)?;
+
+        Ok(key)
+    }
+
+    pub fn parse_certificate(pem: &str) -> Result<Certificate, ContractError> {
+        Certificate::from_pem(pem).map_err(|e| ContractError::CertificateReadError(e.to_string()))
+    }
+
+    pub fn parse_date(date: &str) -> Result<DateTime, ContractError> {
+        date.parse::<DateTime>()
+            .map_err(|_| ContractError::DateTimeReadError)
+    }
+
+    fn parse_time(time: Option<String>) -> Result<Option<DateTime>, ContractError> {
+        time.map(|time| parse_date(&time)).transpose()
+    }
+
+    pub fn parse_fmspc(fmspc: &str) -> Result<Fmspc, ContractError> {
+        hex::decode(fmspc)
+            .map_err(|e| ContractError::InvalidFmspc(e.to_string()))?
+            .try_into()
+            .map_err(|_| ContractError::InvalidFmspc(format!("{fmspc} is not 6 bytes long")))
+    }
+
+    pub fn get_fmspc(tcbinfo: &str) -> Result<Fmspc, ContractError> {
+        let tcbinfo_raw: Value =
+            serde_json::from_str(tcbinfo).map_err(|_| ContractError::TcbInfoReadError)?;
+        let fmspc = tcbinfo_raw
+            .get("tcbInfo")
+            .ok_or_else(|| ContractError::TcbInfoMissingField("tcbInfo".to_string()))?
+            .get("fmspc")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ContractError::TcbInfoMissingField("tcbInfo.fmspc".to_string()))?;
+        parse_fmspc(fmspc)
+    }
+
+    /// Returns the `tcbEvaluationDataNumber`, `issueDate` and `nextUpdate` of a signed TCB info or
+    /// QE identity, whose signed body is under the `body` key.
+    fn parse_signed_body(
+        signed: &str,
+        body: &str,
+        read_error: ContractError,
+    ) -> Result<(u32, DateTime, DateTime), ContractError> {
+        let signed: Value = serde_json::from_str(signed).map_err(|_| read_error)?;
+        let field = |name: &str| {
+            signed
+                .get(body)
+                .and_then(|body| body.get(name))
+                .ok_or_else(|| ContractError::TcbInfoMissingField(format!("{body}.{name}")))
+        };
+        let date = |name: &str| {
+            field(name)?
                 .as_str()
-                .expect("could not find fmspc string"),
-        )
-        .expect("failed to decode fmspc hex string");
-        fmspc_raw.try_into().unwrap()
+                .ok_or(ContractError::DateTimeReadError)
+                .and_then(parse_date)
+        };
+
+        Ok((
+            field("tcbEvaluationDataNumber")?
+                .as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .ok_or(ContractError::TcbInfoReadError)?,
+            date("issueDate")?,
+            date("nextUpdate")?,
+        ))
+    }
+
+    pub fn parse_header(tcbinfo: &str) -> Result<TcbInfoHeader, ContractError> {
+        let fmspc = get_fmspc(tcbinfo)?;
+        let (evaluation_number, issue_date, next_update) =
+            parse_signed_body(tcbinfo, "tcbInfo", ContractError::TcbInfoReadError)?;
+
+        Ok(TcbInfoHeader {
+            fmspc,
+            evaluation_number,
+            issue_date,
+            next_update,
+        })
+    }
+
+    pub fn block_time(env: &Env) -> Result<DateTime, ContractError> {
+        DateTime::from_unix_duration(Duration::from_secs(env.block.time.seconds()))
+            .map_err(|_| ContractError::DateTimeReadError)
+    }
+
+    /// Checks that `what` was already issued and hasn't expired at `now`.
+    pub fn check_freshness(
+        what: &str,
+        issue_date: DateTime,
+        next_update: Option<DateTime>,
+        now: DateTime,
+    ) -> Result<(), ContractError> {
+        if now < issue_date {
+            return Err(ContractError::NotYetValid {
+                what: what.to_string(),
+                since: issue_date.to_string(),
+            });
+        }
+        match next_update {
+            Some(next_update) if now > next_update => Err(ContractError::Expired {
+                what: what.to_string(),
+                expiry: next_update.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Refuses downgrades, i.e. a TCB info with an older `tcbEvaluationDataNumber` than the stored
+    /// one. Intel re-issues TCB infos with the same evaluation number, so those are accepted if
+    /// they were issued later than the stored one.
+    pub fn ensure_newer(deps: Deps, header: &TcbInfoHeader) -> Result<(), ContractError> {
+        let Some(latest) = LATEST.may_load(deps.storage, header.fmspc)? else {
+            return Ok(());
+        };
+
+        let stale = ContractError::StaleTcbInfo {
+            latest,
+            got: header.evaluation_number,
+        };
+        if header.evaluation_number < latest {
+            return Err(stale);
+        }
+        if header.evaluation_number == latest {
+            let stored = DATABASE.load(deps.storage, (header.fmspc.as_slice(), latest))?;
+            if header.issue_date <= parse_date(&stored.issue_date)? {
+                return Err(stale);
+            }
+        }
+        Ok(())
     }
 
     pub fn check_certificate_validity(cert: &Certificate, time: Option<DateTime>) -> bool {
@@ -130,40 +552,191 @@ pub mod execute {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
-        QueryMsg::GetTcbInfo { fmspc } => to_json_binary(&query::get_info(deps, fmspc)?),
+        QueryMsg::GetTcbInfo { fmspc } => Ok(to_json_binary(&query::get_info(deps, env, fmspc)?)?),
+        QueryMsg::ListFmspcs { start_after, limit } => Ok(to_json_binary(&query::list_fmspcs(
+            deps,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::GetTcbInfoHistory { fmspc } => {
+            Ok(to_json_binary(&query::get_history(deps, fmspc)?)?)
+        }
+        QueryMsg::GetTcbInfoAt {
+            fmspc,
+            evaluation_number,
+        } => Ok(to_json_binary(&query::get_info_at(
+            deps,
+            fmspc,
+            evaluation_number,
+        )?)?),
+        QueryMsg::GetQeIdentity {} => Ok(to_json_binary(&query::get_qe_identity(deps, env)?)?),
+        QueryMsg::GetRootCrl {} => Ok(to_json_binary(&query::get_root_crl(deps, env)?)?),
+        QueryMsg::GetPckCrl { issuer } => {
+            Ok(to_json_binary(&query::get_pck_crl(deps, env, issuer)?)?)
+        }
+        QueryMsg::Ownership {} => Ok(to_json_binary(&ownership::get_ownership(deps.storage)?)?),
+        QueryMsg::RootCertificates {} => Ok(to_json_binary(&RootCertificatesResponse {
+            roots: execute::accepted_roots(deps, &env)?,
+        })?),
     }
 }
 
 pub mod query {
+    use cw_storage_plus::Bound;
+
     use super::*;
+    use crate::state::{Crl, Fmspc, PCK_CRLS, QE_IDENTITY, ROOT_CRL};
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    fn to_entry(fmspc: Fmspc, evaluation_number: u32, tcb_info: TcbInfo) -> TcbInfoEntry {
+        TcbInfoEntry {
+            fmspc: hex::encode(fmspc),
+            evaluation_number,
+            issue_date: tcb_info.issue_date,
+            next_update: tcb_info.next_update,
+            tcb_info: tcb_info.info,
+        }
+    }
+
+    fn ensure_unexpired(
+        env: &Env,
+        what: &str,
+        next_update: Option<&str>,
+    ) -> Result<(), ContractError> {
+        let Some(next_update) = next_update else {
+            return Ok(());
+        };
+        if execute::block_time(env)? > execute::parse_date(next_update)? {
+            return Err(ContractError::Expired {
+                what: what.to_string(),
+                expiry: next_update.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn to_crl_response(env: &Env, crl: Crl) -> Result<GetCrlResponse, ContractError> {
+        ensure_unexpired(env, "CRL", crl.next_update.as_deref())?;
+        Ok(GetCrlResponse {
+            crl: crl.crl,
+            issuer: crl.issuer,
+        })
+    }
+
+    pub fn get_info(
+        deps: Deps,
+        env: Env,
+        fmspc: String,
+    ) -> Result<GetTcbInfoResponse, ContractError> {
+        let key = execute::parse_fmspc(&fmspc)?;
+        let latest = LATEST.load(deps.storage, key)?;
+        let tcb_info = DATABASE.load(deps.storage, (key.as_slice(), latest))?;
+        ensure_unexpired(&env, "tcbinfo", Some(&tcb_info.next_update))?;
 
-    pub fn get_info(deps: Deps, fmspc: String) -> StdResult<GetTcbInfoResponse> {
-        let key: [u8; 6] = hex::decode(fmspc)
-            .unwrap()
-            .try_into()
-            .expect("invalid fmspc");
-        let tcb_info = DATABASE.load(deps.storage, key)?;
         Ok(GetTcbInfoResponse {
             tcb_info: tcb_info.info,
         })
     }
+
+    pub fn list_fmspcs(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<ListFmspcsResponse, ContractError> {
+        let start = start_after
+            .map(|fmspc| execute::parse_fmspc(&fmspc))
+            .transpose()?
+            .map(Bound::exclusive);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let fmspcs = LATEST
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|fmspc| fmspc.map(hex::encode))
+            .collect::<Result<_, _>>()?;
+        Ok(ListFmspcsResponse { fmspcs })
+    }
+
+    pub fn get_history(
+        deps: Deps,
+        fmspc: String,
+    ) -> Result<GetTcbInfoHistoryResponse, ContractError> {
+        let key = execute::parse_fmspc(&fmspc)?;
+        let entries = DATABASE
+            .prefix(key.as_slice())
+            .range(deps.storage, None, None, Order::Descending)
+            .map(|entry| {
+                entry
+                    .map(|(evaluation_number, tcb_info)| to_entry(key, evaluation_number, tcb_info))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(GetTcbInfoHistoryResponse { entries })
+    }
+
+    pub fn get_info_at(
+        deps: Deps,
+        fmspc: String,
+        evaluation_number: u32,
+    ) -> Result<TcbInfoEntry, ContractError> {
+        let key = execute::parse_fmspc(&fmspc)?;
+        let tcb_info = DATABASE.load(deps.storage, (key.as_slice(), evaluation_number))?;
+        Ok(to_entry(key, evaluation_number, tcb_info))
+    }
+
+    pub fn get_qe_identity(deps: Deps, env: Env) -> Result<GetQeIdentityResponse, ContractError> {
+        let qe_identity = QE_IDENTITY.load(deps.storage)?;
+        ensure_unexpired(&env, "QE identity", Some(&qe_identity.next_update))?;
+        Ok(GetQeIdentityResponse {
+            qe_identity: qe_identity.info,
+        })
+    }
+
+    pub fn get_root_crl(deps: Deps, env: Env) -> Result<GetCrlResponse, ContractError> {
+        to_crl_response(&env, ROOT_CRL.load(deps.storage)?)
+    }
+
+    pub fn get_pck_crl(
+        deps: Deps,
+        env: Env,
+        issuer: String,
+    ) -> Result<GetCrlResponse, ContractError> {
+        to_crl_response(&env, PCK_CRLS.load(deps.storage, &issuer)?)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::{
-        coins,
-        testing::{message_info, mock_dependencies, mock_env},
+        coins, from_json,
+        testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier},
+        Addr, MemoryStorage, OwnedDeps, Timestamp,
     };
 
+    use quartz_tcbinfo_msgs::{Ownership, OwnershipAction};
+
     use super::*;
     const TCB_SIGNER: &str = include_str!("../data/tcb_signer.pem");
     const ROOT_CA: &str = include_str!("../data/root_ca.pem");
     const TCB_INFO: &str = include_str!("../data/tcbinfo.json");
+    const QE_IDENTITY: &str = include_str!("../data/qe_identity.json");
+    const ROOT_CRL: &[u8] = include_bytes!("../data/root_crl.der");
+    const PROCESSOR_CA: &str = include_str!("../data/processor_ca.pem");
+    const PROCESSOR_CRL: &[u8] = include_bytes!("../data/processor_crl.der");
+    const TEST_ROOT_CA: &str = include_str!("../data/test_root_ca.pem");
     const FMSPC: &str = "00606a000000";
     // const TIME: &str = "2024-07-15T15:19:13Z";
+    /// 2024-07-11T15:19:13Z, i.e. between the test tcbinfo's `issueDate` and `nextUpdate`
+    const BLOCK_TIME: u64 = 1720711153;
+
+    fn env_at(seconds: u64) -> Env {
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(seconds);
+        env
+    }
     #[test]
     fn verify_init_and_exec() {
         let time = "2024-07-11T15:19:13Z";
@@ -173,22 +746,23 @@ mod tests {
         let info = message_info(&creator, &coins(1000, "earth"));
         let init_msg = InstantiateMsg {
             root_cert: ROOT_CA.to_string(),
+            owner: None,
         };
         let mut deps = mock_dependencies();
         let res = instantiate(deps.as_mut(), mock_env(), info, init_msg);
         assert!(res.is_ok());
 
-        let exec_msg = ExecuteMsg {
+        let exec_msg = ExecuteMsg::SubmitTcbInfo {
             tcb_info: TCB_INFO.to_string(),
             certificate: TCB_SIGNER.to_string(),
             time: Some(time.to_string()),
         };
         let info = message_info(&creator, &coins(1000, "earth"));
-        let exec = execute(deps.as_mut(), mock_env(), info, exec_msg);
+        let exec = execute(deps.as_mut(), env_at(BLOCK_TIME), info, exec_msg);
         assert!(exec.is_ok());
         let query = query(
             deps.as_ref(),
-            mock_env(),
+            env_at(BLOCK_TIME),
             QueryMsg::GetTcbInfo {
                 fmspc: FMSPC.to_string(),
             },
@@ -196,4 +770,636 @@ mod tests {
         assert!(query.is_ok());
         println!("{:?}", query.unwrap());
     }
+
+    fn instantiated() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let info = message_info(&deps.api.addr_make("creator"), &[]);
+        let init_msg = InstantiateMsg {
+            root_cert: ROOT_CA.to_string(),
+            owner: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), info, init_msg).expect("valid root certificate");
+        deps
+    }
+
+    fn exec_on(
+        deps: DepsMut,
+        env: Env,
+        tcb_info: &str,
+        certificate: &str,
+        time: &str,
+    ) -> Result<Response, ContractError> {
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let exec_msg = ExecuteMsg::SubmitTcbInfo {
+            tcb_info: tcb_info.to_string(),
+            certificate: certificate.to_string(),
+            time: Some(time.to_string()),
+        };
+        execute(deps, env, info, exec_msg)
+    }
+
+    fn exec(tcb_info: &str, certificate: &str, time: &str) -> Result<Response, ContractError> {
+        let mut deps = instantiated();
+        exec_on(
+            deps.as_mut(),
+            env_at(BLOCK_TIME),
+            tcb_info,
+            certificate,
+            time,
+        )
+    }
+
+    #[test]
+    fn invalid_root_certificate_is_rejected() {
+        let mut deps = mock_dependencies();
+        let info = message_info(&deps.api.addr_make("creator"), &[]);
+        let init_msg = InstantiateMsg {
+            root_cert: "not a certificate".to_string(),
+            owner: None,
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info, init_msg).unwrap_err();
+        assert!(matches!(err, ContractError::CertificateReadError(_)));
+    }
+
+    #[test]
+    fn malformed_tcb_info_is_rejected() {
+        let time = "2024-07-11T15:19:13Z";
+
+        let err = exec("{", TCB_SIGNER, time).unwrap_err();
+        assert!(matches!(err, ContractError::TcbInfoReadError));
+
+        let err = exec(r#"{"signature":"00"}"#, TCB_SIGNER, time).unwrap_err();
+        assert!(matches!(err, ContractError::TcbInfoMissingField(_)));
+
+        let err = exec(r#"{"tcbInfo":{"version":3}}"#, TCB_SIGNER, time).unwrap_err();
+        assert!(matches!(err, ContractError::TcbInfoMissingField(_)));
+
+        let err = exec(r#"{"tcbInfo":{"fmspc":"zz"}}"#, TCB_SIGNER, time).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFmspc(_)));
+
+        let err = exec(r#"{"tcbInfo":{"fmspc":"00606a"}}"#, TCB_SIGNER, time).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFmspc(_)));
+
+        let err = exec(r#"{"tcbInfo":{"fmspc":"00606a000000"}}"#, TCB_SIGNER, time).unwrap_err();
+        assert!(matches!(err, ContractError::TcbInfoMissingField(_)));
+
+        let unsigned = r#"{"tcbInfo":{"fmspc":"00606a000000","tcbEvaluationDataNumber":17,"issueDate":"2024-07-04T15:16:13Z","nextUpdate":"2024-08-03T15:16:13Z"}}"#;
+        let err = exec(unsigned, TCB_SIGNER, time).unwrap_err();
+        assert!(matches!(err, ContractError::TcbInfoReadError));
+    }
+
+    #[test]
+    fn malformed_certificate_is_rejected() {
+        let err = exec(TCB_INFO, "not a certificate", "2024-07-11T15:19:13Z").unwrap_err();
+        assert!(matches!(err, ContractError::CertificateReadError(_)));
+    }
+
+    #[test]
+    fn malformed_or_out_of_validity_time_is_rejected() {
+        let err = exec(TCB_INFO, TCB_SIGNER, "yesterday").unwrap_err();
+        assert!(matches!(err, ContractError::DateTimeReadError));
+
+        let err = exec(TCB_INFO, TCB_SIGNER, "2000-01-01T00:00:00Z").unwrap_err();
+        assert!(matches!(err, ContractError::RootCertificateValidityError));
+    }
+
+    #[test]
+    fn malformed_or_unknown_fmspc_query_is_rejected() {
+        let deps = instantiated();
+        let query_info = |fmspc: &str| {
+            query(
+                deps.as_ref(),
+                env_at(BLOCK_TIME),
+                QueryMsg::GetTcbInfo {
+                    fmspc: fmspc.to_string(),
+                },
+            )
+        };
+
+        assert!(matches!(
+            query_info("not hex").unwrap_err(),
+            ContractError::InvalidFmspc(_)
+        ));
+        assert!(matches!(
+            query_info("00606a").unwrap_err(),
+            ContractError::InvalidFmspc(_)
+        ));
+        assert!(matches!(
+            query_info(FMSPC).unwrap_err(),
+            ContractError::Std(_)
+        ));
+    }
+
+    #[test]
+    fn tcb_info_outside_of_its_validity_window_is_rejected() {
+        let time = "2024-07-11T15:19:13Z";
+
+        let mut deps = instantiated();
+        let err = exec_on(
+            deps.as_mut(),
+            env_at(1719000000),
+            TCB_INFO,
+            TCB_SIGNER,
+            time,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotYetValid { .. }));
+
+        let err = exec_on(
+            deps.as_mut(),
+            env_at(1723000000),
+            TCB_INFO,
+            TCB_SIGNER,
+            time,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Expired { .. }));
+    }
+
+    #[test]
+    fn stale_tcb_info_is_rejected() {
+        let time = "2024-07-11T15:19:13Z";
+        let mut deps = instantiated();
+        exec_on(
+            deps.as_mut(),
+            env_at(BLOCK_TIME),
+            TCB_INFO,
+            TCB_SIGNER,
+            time,
+        )
+        .expect("fresh tcbinfo");
+
+        // same evaluation number and issue date
+        let err = exec_on(
+            deps.as_mut(),
+            env_at(BLOCK_TIME),
+            TCB_INFO,
+            TCB_SIGNER,
+            time,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::StaleTcbInfo {
+                latest: 17,
+                got: 17
+            }
+        ));
+
+        let header = |evaluation_number, issue_date: &str| execute::TcbInfoHeader {
+            fmspc: execute::parse_fmspc(FMSPC).expect("valid fmspc"),
+            evaluation_number,
+            issue_date: issue_date.parse().expect("valid date"),
+            next_update: "2024-08-03T15:16:13Z".parse().expect("valid date"),
+        };
+        assert!(execute::ensure_newer(deps.as_ref(), &header(16, "2024-07-10T00:00:00Z")).is_err());
+        assert!(execute::ensure_newer(deps.as_ref(), &header(17, "2024-07-10T00:00:00Z")).is_ok());
+        assert!(execute::ensure_newer(deps.as_ref(), &header(18, "2024-07-01T00:00:00Z")).is_ok());
+    }
+
+    #[test]
+    fn tcb_infos_can_be_enumerated() {
+        let time = "2024-07-11T15:19:13Z";
+        let mut deps = instantiated();
+        exec_on(
+            deps.as_mut(),
+            env_at(BLOCK_TIME),
+            TCB_INFO,
+            TCB_SIGNER,
+            time,
+        )
+        .expect("fresh tcbinfo");
+        let query_at = |seconds, msg| query(deps.as_ref(), env_at(seconds), msg);
+
+        let res: ListFmspcsResponse = from_json(
+            query_at(
+                BLOCK_TIME,
+                QueryMsg::ListFmspcs {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .expect("valid query"),
+        )
+        .expect("valid response");
+        assert_eq!(res.fmspcs, vec![FMSPC.to_string()]);
+
+        let res: ListFmspcsResponse = from_json(
+            query_at(
+                BLOCK_TIME,
+                QueryMsg::ListFmspcs {
+                    start_after: Some(FMSPC.to_string()),
+                    limit: Some(10),
+                },
+            )
+            .expect("valid query"),
+        )
+        .expect("valid response");
+        assert!(res.fmspcs.is_empty());
+
+        let res: GetTcbInfoHistoryResponse = from_json(
+            query_at(
+                BLOCK_TIME,
+                QueryMsg::GetTcbInfoHistory {
+                    fmspc: FMSPC.to_string(),
+                },
+            )
+            .expect("valid query"),
+        )
+        .expect("valid response");
+        assert_eq!(res.entries.len(), 1);
+        assert_eq!(res.entries[0].evaluation_number, 17);
+        assert_eq!(res.entries[0].next_update, "2024-08-03T15:16:13Z");
+
+        let res: TcbInfoEntry = from_json(
+            query_at(
+                BLOCK_TIME,
+                QueryMsg::GetTcbInfoAt {
+                    fmspc: FMSPC.to_string(),
+                    evaluation_number: 17,
+                },
+            )
+            .expect("valid query"),
+        )
+        .expect("valid response");
+        assert_eq!(res.tcb_info, TCB_INFO);
+        assert!(query_at(
+            BLOCK_TIME,
+            QueryMsg::GetTcbInfoAt {
+                fmspc: FMSPC.to_string(),
+                evaluation_number: 16,
+            }
+        )
+        .is_err());
+
+        let err = query_at(
+            1723000000,
+            QueryMsg::GetTcbInfo {
+                fmspc: FMSPC.to_string(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Expired { .. }));
+    }
+
+    /// 2023-07-20T00:00:00Z, i.e. while both the test QE identity and root CRL are valid
+    const QE_IDENTITY_TIME: u64 = 1689811200;
+    /// 2023-05-01T00:00:00Z, i.e. while the test PCK processor CRL is valid
+    const PCK_CRL_TIME: u64 = 1682899200;
+
+    fn submit(deps: DepsMut, seconds: u64, msg: ExecuteMsg) -> Result<Response, ContractError> {
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        execute(deps, env_at(seconds), info, msg)
+    }
+
+    fn submit_qe_identity(qe_identity: &str) -> ExecuteMsg {
+        ExecuteMsg::SubmitQeIdentity {
+            qe_identity: qe_identity.to_string(),
+            certificate: TCB_SIGNER.to_string(),
+            time: None,
+        }
+    }
+
+    #[test]
+    fn qe_identity_is_verified_and_stored() {
+        let mut deps = instantiated();
+        submit(
+            deps.as_mut(),
+            QE_IDENTITY_TIME,
+            submit_qe_identity(QE_IDENTITY),
+        )
+        .expect("valid QE identity");
+
+        let res: GetQeIdentityResponse = from_json(
+            query(
+                deps.as_ref(),
+                env_at(QE_IDENTITY_TIME),
+                QueryMsg::GetQeIdentity {},
+            )
+            .expect("stored QE identity"),
+        )
+        .expect("valid response");
+        assert_eq!(res.qe_identity, QE_IDENTITY);
+
+        let err = submit(
+            deps.as_mut(),
+            QE_IDENTITY_TIME,
+            submit_qe_identity(QE_IDENTITY),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::StaleCollateral(_)));
+
+        let err = query(
+            deps.as_ref(),
+            env_at(1700000000),
+            QueryMsg::GetQeIdentity {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Expired { .. }));
+    }
+
+    #[test]
+    fn tampered_qe_identity_is_rejected() {
+        let tampered = QE_IDENTITY.replace("\"isvprodid\":1", "\"isvprodid\":2");
+        assert_ne!(tampered, QE_IDENTITY);
+
+        let mut deps = instantiated();
+        let err = submit(
+            deps.as_mut(),
+            QE_IDENTITY_TIME,
+            submit_qe_identity(&tampered),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::QeIdentityVerificationError));
+
+        let err = submit(deps.as_mut(), BLOCK_TIME, submit_qe_identity(QE_IDENTITY)).unwrap_err();
+        assert!(matches!(err, ContractError::Expired { .. }));
+    }
+
+    #[test]
+    fn root_crl_is_verified_and_stored() {
+        let mut deps = instantiated();
+        let msg = || ExecuteMsg::SubmitRootCrl {
+            crl: ROOT_CRL.into(),
+        };
+        submit(deps.as_mut(), QE_IDENTITY_TIME, msg()).expect("valid root CRL");
+
+        let res: GetCrlResponse = from_json(
+            query(
+                deps.as_ref(),
+                env_at(QE_IDENTITY_TIME),
+                QueryMsg::GetRootCrl {},
+            )
+            .expect("stored root CRL"),
+        )
+        .expect("valid response");
+        assert_eq!(res.crl.as_slice(), ROOT_CRL);
+        assert_eq!(res.issuer, ROOT_CA);
+
+        let err = submit(deps.as_mut(), QE_IDENTITY_TIME, msg()).unwrap_err();
+        assert!(matches!(err, ContractError::StaleCollateral(_)));
+
+        let err = query(deps.as_ref(), env_at(BLOCK_TIME), QueryMsg::GetRootCrl {}).unwrap_err();
+        assert!(matches!(err, ContractError::Expired { .. }));
+    }
+
+    #[test]
+    fn crl_signed_by_another_issuer_is_rejected() {
+        let mut deps = instantiated();
+        let err = submit(
+            deps.as_mut(),
+            PCK_CRL_TIME,
+            ExecuteMsg::SubmitRootCrl {
+                crl: PROCESSOR_CRL.into(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CrlVerificationError));
+
+        let err = submit(
+            deps.as_mut(),
+            QE_IDENTITY_TIME,
+            ExecuteMsg::SubmitPckCrl {
+                crl: ROOT_CRL.into(),
+                issuer: PROCESSOR_CA.to_string(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CrlVerificationError));
+
+        let err = submit(
+            deps.as_mut(),
+            PCK_CRL_TIME,
+            ExecuteMsg::SubmitRootCrl {
+                crl: b"not a crl".as_slice().into(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CrlReadError(_)));
+    }
+
+    #[test]
+    fn pck_crl_is_stored_by_issuer() {
+        let mut deps = instantiated();
+        submit(
+            deps.as_mut(),
+            PCK_CRL_TIME,
+            ExecuteMsg::SubmitPckCrl {
+                crl: PROCESSOR_CRL.into(),
+                issuer: PROCESSOR_CA.to_string(),
+            },
+        )
+        .expect("valid PCK CRL");
+
+        let issuer = execute::parse_certificate(PROCESSOR_CA)
+            .expect("valid certificate")
+            .tbs_certificate
+            .subject
+            .to_string();
+        let res: GetCrlResponse = from_json(
+            query(
+                deps.as_ref(),
+                env_at(PCK_CRL_TIME),
+                QueryMsg::GetPckCrl { issuer },
+            )
+            .expect("stored PCK CRL"),
+        )
+        .expect("valid response");
+        assert_eq!(res.crl.as_slice(), PROCESSOR_CRL);
+        assert_eq!(res.issuer, PROCESSOR_CA);
+
+        assert!(query(
+            deps.as_ref(),
+            env_at(PCK_CRL_TIME),
+            QueryMsg::GetPckCrl {
+                issuer: "CN=Intel SGX PCK Platform CA".to_string(),
+            },
+        )
+        .is_err());
+    }
+
+    fn update_root(
+        deps: DepsMut,
+        sender: &Addr,
+        seconds: u64,
+        transition_period: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::UpdateRootCertificate {
+            root_cert: TEST_ROOT_CA.to_string(),
+            transition_period,
+        };
+        execute(deps, env_at(seconds), message_info(sender, &[]), msg)
+    }
+
+    fn update_ownership(
+        deps: DepsMut,
+        sender: &Addr,
+        seconds: u64,
+        action: OwnershipAction,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::UpdateOwnership(action);
+        execute(deps, env_at(seconds), message_info(sender, &[]), msg)
+    }
+
+    #[test]
+    fn only_owner_can_update_root_certificate() {
+        let mut deps = instantiated();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let err = update_root(deps.as_mut(), &user, BLOCK_TIME, None).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let msg = ExecuteMsg::UpdateRootCertificate {
+            root_cert: TCB_SIGNER.to_string(),
+            transition_period: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env_at(BLOCK_TIME),
+            message_info(&creator, &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CertificateVerificationError));
+
+        let res = update_root(deps.as_mut(), &creator, BLOCK_TIME, None).expect("owner update");
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "root_subject" && attr.value == "CN=Quartz Test Root CA"));
+
+        // the Intel root is no longer accepted
+        let err = exec_on(
+            deps.as_mut(),
+            env_at(BLOCK_TIME),
+            TCB_INFO,
+            TCB_SIGNER,
+            "2024-07-11T15:19:13Z",
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CertificateVerificationError));
+    }
+
+    #[test]
+    fn previous_root_is_accepted_during_transition_window() {
+        let time = "2024-07-11T15:19:13Z";
+        let roots = |deps: Deps, seconds| -> RootCertificatesResponse {
+            from_json(
+                query(deps, env_at(seconds), QueryMsg::RootCertificates {}).expect("valid query"),
+            )
+            .expect("valid response")
+        };
+
+        let mut deps = instantiated();
+        let creator = deps.api.addr_make("creator");
+        update_root(deps.as_mut(), &creator, BLOCK_TIME - 100, Some(1000)).expect("owner update");
+
+        let res = roots(deps.as_ref(), BLOCK_TIME);
+        assert_eq!(res.roots.len(), 2);
+        assert_eq!(res.roots[0].certificate, TEST_ROOT_CA);
+        assert_eq!(res.roots[1].certificate, ROOT_CA);
+        assert_eq!(
+            res.roots[1].accepted_until,
+            Some(Timestamp::from_seconds(BLOCK_TIME + 900))
+        );
+        assert_eq!(roots(deps.as_ref(), BLOCK_TIME + 901).roots.len(), 1);
+
+        exec_on(
+            deps.as_mut(),
+            env_at(BLOCK_TIME),
+            TCB_INFO,
+            TCB_SIGNER,
+            time,
+        )
+        .expect("signed under the previous root");
+
+        let mut deps = instantiated();
+        update_root(deps.as_mut(), &creator, BLOCK_TIME - 100, Some(1000)).expect("owner update");
+        let err = exec_on(
+            deps.as_mut(),
+            env_at(BLOCK_TIME + 901),
+            TCB_INFO,
+            TCB_SIGNER,
+            time,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CertificateVerificationError));
+    }
+
+    #[test]
+    fn ownership_can_be_transferred() {
+        let mut deps = instantiated();
+        let creator = deps.api.addr_make("creator");
+        let new_owner = deps.api.addr_make("new_owner");
+        let transfer = |expiry| OwnershipAction::TransferOwnership {
+            new_owner: new_owner.to_string(),
+            expiry,
+        };
+
+        let err =
+            update_ownership(deps.as_mut(), &new_owner, BLOCK_TIME, transfer(None)).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = update_ownership(
+            deps.as_mut(),
+            &new_owner,
+            BLOCK_TIME,
+            OwnershipAction::AcceptOwnership,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingOwnershipTransfer));
+
+        update_ownership(
+            deps.as_mut(),
+            &creator,
+            BLOCK_TIME,
+            transfer(Some(Timestamp::from_seconds(BLOCK_TIME + 10))),
+        )
+        .expect("owner transfer");
+        let err = update_ownership(
+            deps.as_mut(),
+            &creator,
+            BLOCK_TIME,
+            OwnershipAction::AcceptOwnership,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = update_ownership(
+            deps.as_mut(),
+            &new_owner,
+            BLOCK_TIME + 10,
+            OwnershipAction::AcceptOwnership,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::OwnershipTransferExpired));
+
+        update_ownership(deps.as_mut(), &creator, BLOCK_TIME, transfer(None))
+            .expect("owner transfer");
+        update_ownership(
+            deps.as_mut(),
+            &new_owner,
+            BLOCK_TIME,
+            OwnershipAction::AcceptOwnership,
+        )
+        .expect("pending owner accepts");
+
+        let ownership: Ownership = from_json(
+            query(deps.as_ref(), env_at(BLOCK_TIME), QueryMsg::Ownership {}).expect("valid query"),
+        )
+        .expect("valid response");
+        assert_eq!(ownership.owner, Some(new_owner.clone()));
+        assert_eq!(ownership.pending_owner, None);
+
+        let err = update_root(deps.as_mut(), &creator, BLOCK_TIME, None).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        update_ownership(
+            deps.as_mut(),
+            &new_owner,
+            BLOCK_TIME,
+            OwnershipAction::RenounceOwnership,
+        )
+        .expect("owner renounces");
+        let err = update_root(deps.as_mut(), &new_owner, BLOCK_TIME, None).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
 }
@@ -20,4 +20,36 @@ pub enum ContractError {
     DateTimeReadError,
     #[error("invalid tcbinfo")]
     TcbInfoReadError,
+    #[error("invalid PEM certificate: {0}")]
+    CertificateReadError(String),
+    #[error("root certificate is not valid at the given time")]
+    RootCertificateValidityError,
+    #[error("certificate is not valid at the given time")]
+    CertificateValidityError,
+    #[error("tcbinfo is missing the {0} field")]
+    TcbInfoMissingField(String),
+    #[error("invalid FMSPC: {0}")]
+    InvalidFmspc(String),
+    #[error("tcbinfo database must be empty on instantiation")]
+    DatabaseNotEmpty,
+    #[error("tcbinfo (evaluation number {got}) is not newer than the stored one ({latest})")]
+    StaleTcbInfo { latest: u32, got: u32 },
+    #[error("{what} is not valid before {since}")]
+    NotYetValid { what: String, since: String },
+    #[error("{what} expired at {expiry}")]
+    Expired { what: String, expiry: String },
+    #[error("{0} is not newer than the stored one")]
+    StaleCollateral(String),
+    #[error("invalid QE identity")]
+    QeIdentityReadError,
+    #[error("failed to verify QE identity")]
+    QeIdentityVerificationError,
+    #[error("invalid CRL: {0}")]
+    CrlReadError(String),
+    #[error("failed to verify CRL")]
+    CrlVerificationError,
+    #[error("there is no pending ownership transfer")]
+    NoPendingOwnershipTransfer,
+    #[error("the ownership transfer has expired")]
+    OwnershipTransferExpired,
 }
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{testing::MockApi, Addr, Coin, Empty, Uint128};
+    use cosmwasm_std::{testing::MockApi, Addr, Coin, Empty, Timestamp, Uint128};
     use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
     use quartz_tcbinfo_msgs::InstantiateMsg;
 
@@ -50,6 +50,7 @@ mod tests {
 
         let msg = InstantiateMsg {
             root_cert: ROOT_CA.to_string(),
+            owner: None,
         };
         let cw_template_contract_addr = app
             .instantiate_contract(
@@ -75,8 +76,10 @@ mod tests {
         #[test]
         fn add_tcbinfo() {
             let (mut app, cw_template_contract) = proper_instantiate();
+            // the test tcbinfo is valid between 2024-07-04 and 2024-08-03
+            app.update_block(|block| block.time = Timestamp::from_seconds(1720711153));
 
-            let msg = ExecuteMsg {
+            let msg = ExecuteMsg::SubmitTcbInfo {
                 tcb_info: TCB_INFO.to_string(),
                 certificate: TCB_SIGNER.to_string(),
                 time: Some(TIME.to_string()),
@@ -0,0 +1,100 @@
+//! A minimal take on `cw-ownable`: the contract has a single owner, who can propose a new owner,
+//! who must then accept the ownership, or renounce the ownership altogether.
+
+use cosmwasm_std::{Addr, BlockInfo, DepsMut, Response, Storage};
+use quartz_tcbinfo_msgs::{Ownership, OwnershipAction};
+
+use crate::{error::ContractError, state::OWNERSHIP};
+
+pub fn initialize_owner(
+    storage: &mut dyn Storage,
+    owner: Addr,
+) -> Result<Ownership, ContractError> {
+    let ownership = Ownership {
+        owner: Some(owner),
+        ..Ownership::default()
+    };
+    OWNERSHIP.save(storage, &ownership)?;
+    Ok(ownership)
+}
+
+pub fn get_ownership(storage: &dyn Storage) -> Result<Ownership, ContractError> {
+    Ok(OWNERSHIP.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn assert_owner(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    match get_ownership(storage)?.owner {
+        Some(owner) if owner == *sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+fn is_expired(ownership: &Ownership, block: &BlockInfo) -> bool {
+    ownership
+        .pending_expiry
+        .is_some_and(|expiry| block.time >= expiry)
+}
+
+pub fn update_ownership(
+    deps: DepsMut,
+    block: &BlockInfo,
+    sender: &Addr,
+    action: OwnershipAction,
+) -> Result<Response, ContractError> {
+    let ownership = match action {
+        OwnershipAction::TransferOwnership { new_owner, expiry } => {
+            assert_owner(deps.storage, sender)?;
+            if expiry.is_some_and(|expiry| block.time >= expiry) {
+                return Err(ContractError::OwnershipTransferExpired);
+            }
+            Ownership {
+                pending_owner: Some(deps.api.addr_validate(&new_owner)?),
+                pending_expiry: expiry,
+                ..get_ownership(deps.storage)?
+            }
+        }
+        OwnershipAction::AcceptOwnership => {
+            let ownership = get_ownership(deps.storage)?;
+            match &ownership.pending_owner {
+                None => return Err(ContractError::NoPendingOwnershipTransfer),
+                Some(pending_owner) if pending_owner != sender => {
+                    return Err(ContractError::Unauthorized {})
+                }
+                Some(_) if is_expired(&ownership, block) => {
+                    return Err(ContractError::OwnershipTransferExpired)
+                }
+                Some(_) => Ownership {
+                    owner: ownership.pending_owner,
+                    pending_owner: None,
+                    pending_expiry: None,
+                },
+            }
+        }
+        OwnershipAction::RenounceOwnership => {
+            assert_owner(deps.storage, sender)?;
+            Ownership::default()
+        }
+    };
+    OWNERSHIP.save(deps.storage, &ownership)?;
+
+    Ok(ownership_attributes(
+        &ownership,
+        Response::new().add_attribute("action", "update_ownership"),
+    ))
+}
+
+pub fn ownership_attributes(ownership: &Ownership, res: Response) -> Response {
+    let or_none = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+    res.add_attribute(
+        "owner",
+        or_none(ownership.owner.as_ref().map(Addr::to_string)),
+    )
+    .add_attribute(
+        "pending_owner",
+        or_none(ownership.pending_owner.as_ref().map(Addr::to_string)),
+    )
+    .add_attribute(
+        "pending_expiry",
+        or_none(ownership.pending_expiry.map(|expiry| expiry.to_string())),
+    )
+}
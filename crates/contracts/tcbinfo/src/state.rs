@@ -1,13 +1,54 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{HexBinary, Timestamp};
 use cw_storage_plus::{Item, Map};
+use quartz_tcbinfo_msgs::Ownership;
 
 pub type Fmspc = [u8; 6];
 
 #[cw_serde]
 pub struct TcbInfo {
     pub info: String,
+    pub issue_date: String,
+    pub next_update: String,
     //  pub certificate: String,
 }
 
-pub const DATABASE: Map<Fmspc, TcbInfo> = Map::new("state");
+/// All TCB infos, keyed by FMSPC and `tcbEvaluationDataNumber`.
+pub const DATABASE: Map<(&[u8], u32), TcbInfo> = Map::new("tcb_infos");
+/// The latest `tcbEvaluationDataNumber` for each FMSPC.
+pub const LATEST: Map<Fmspc, u32> = Map::new("latest_tcb_infos");
+/// The current (PEM-encoded) root certificate.
 pub const ROOT_CERTIFICATE: Item<String> = Item::new("root_certificate");
+
+/// A root certificate that was replaced, but is still accepted until the end of its transition
+/// window.
+#[cw_serde]
+pub struct RetiringRoot {
+    pub certificate: String,
+    pub accepted_until: Timestamp,
+}
+
+pub const RETIRING_ROOTS: Item<Vec<RetiringRoot>> = Item::new("retiring_roots");
+pub const OWNERSHIP: Item<Ownership> = Item::new("ownership");
+
+#[cw_serde]
+pub struct QeIdentity {
+    pub info: String,
+    pub evaluation_number: u32,
+    pub issue_date: String,
+    pub next_update: String,
+}
+
+#[cw_serde]
+pub struct Crl {
+    pub crl: HexBinary,
+    /// The PEM-encoded certificate of the CRL's issuer
+    pub issuer: String,
+    pub this_update: String,
+    pub next_update: Option<String>,
+}
+
+pub const QE_IDENTITY: Item<QeIdentity> = Item::new("qe_identity");
+pub const ROOT_CRL: Item<Crl> = Item::new("root_crl");
+/// PCK CRLs, keyed by the subject of their issuer (i.e. the PCK Processor or Platform CA).
+pub const PCK_CRLS: Map<&str, Crl> = Map::new("pck_crls");
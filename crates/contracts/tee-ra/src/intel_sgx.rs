@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use self::dcap::tcb_policy::TcbPolicyError;
+
 pub mod dcap;
 
 #[derive(Error, Debug)]
@@ -10,4 +12,12 @@ pub enum Error {
     MrEnclaveMismatch,
     #[error("DCAP specific error: {0:?}")]
     Dcap(Box<dcap::VerificationOutput<dcap::DcapVerifierOutput>>),
+    #[error("Invalid DCAP evidence: {0}")]
+    InvalidEvidence(String),
+    #[error("Invalid verification time: {0}")]
+    InvalidTime(u64),
+    #[error("DCAP verification failed: {0}")]
+    DcapVerification(String),
+    #[error(transparent)]
+    TcbPolicy(#[from] TcbPolicyError),
 }
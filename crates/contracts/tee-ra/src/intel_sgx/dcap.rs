@@ -1,23 +1,271 @@
 pub mod certificate_chain;
 pub mod mc_attest_verifier;
+pub mod report;
+pub mod tcb_policy;
 
-use mc_attestation_verifier::Evidence;
+use std::time::Duration;
+
+use der::{DateTime, DecodePem};
+use mc_attestation_verifier::{
+    Accessor, Advisories, CertificateChainVerifier, Evidence, QeIdentity, QeReportBodyVerifier,
+    Quote3Verifier, SignedQeIdentity, SignedQeIdentityVerifier, SignedTcbInfoVerifier,
+    TrustedIdentitiesVerifier, VerificationMessage, VerificationTreeDisplay, Verifier,
+};
 pub use mc_attestation_verifier::{
     TrustedIdentity, TrustedMrEnclaveIdentity, TrustedMrSignerIdentity, VerificationOutput,
 };
+use mc_sgx_core_types::{IsvProductId, IsvSvn, MrEnclave, MrSigner};
+use mc_sgx_dcap_types::CertificationData;
 pub use mc_sgx_dcap_types::{Collateral, Quote3, Quote3Error};
+use p256::ecdsa::VerifyingKey;
+use x509_cert::{crl::CertificateList, Certificate};
 
-use self::mc_attest_verifier::dcap::DcapVerifier;
-pub use self::mc_attest_verifier::dcap::DcapVerifierOutput;
+use self::{
+    certificate_chain::TlsCertificateChainVerifier,
+    mc_attest_verifier::dcap::DcapVerifier,
+    tcb_policy::{latest_tcb_date, TcbPolicy},
+};
+pub use self::{mc_attest_verifier::dcap::DcapVerifierOutput, report::VerificationReport};
+use super::Error;
 
+/// Verifies the quote against the collateral and the trusted identities.
+///
+/// `time` is the current UNIX timestamp (in seconds), e.g. the block time. If specified, the
+/// validity periods of the certificates, the CRLs and the TCB info and QE identity are checked
+/// against it, otherwise time validation is skipped (e.g. inside an enclave that has no trusted
+/// time).
 pub fn verify(
     quote: Quote3<Vec<u8>>,
     collateral: Collateral,
     identities: &[TrustedIdentity],
-) -> VerificationOutput<DcapVerifierOutput> {
-    let evidence = Evidence::new(quote, collateral).expect("Failed to get evidence");
-    let verifier = DcapVerifier::new(identities, None);
-    verifier.verify(&evidence)
+    time: Option<u64>,
+) -> Result<VerificationOutput<DcapVerifierOutput>, Error> {
+    let time = verification_time(time)?;
+    let evidence =
+        Evidence::new(quote, collateral).map_err(|e| Error::InvalidEvidence(e.to_string()))?;
+    let verifier = DcapVerifier::new(identities, time);
+    Ok(verifier.verify(&evidence))
+}
+
+/// Verifies the quote like [`verify`], except that the TCB status and advisories of the platform
+/// are checked against the given [`TcbPolicy`] (instead of the advisories of the identities).
+///
+/// `now` is the current UNIX timestamp (in seconds), which is used for time validation (like the
+/// `time` of [`verify`]) and for applying the policy's grace period. Returns the
+/// [`VerificationReport`] (with the accepted TCB status) on success.
+pub fn verify_with_policy(
+    quote: Quote3<Vec<u8>>,
+    collateral: Collateral,
+    identities: &[TrustedIdentity],
+    policy: &TcbPolicy,
+    now: Option<u64>,
+) -> Result<VerificationReport, Error> {
+    let time = verification_time(now)?;
+    let quote_chain = certificate_chain_from_quote(&quote)?;
+    let evidence = Evidence::new(quote, collateral.clone())
+        .map_err(|e| Error::InvalidEvidence(e.to_string()))?;
+
+    let tcb_key = verify_certificate_chain(
+        "TCB info",
+        collateral.tcb_issuer_chain(),
+        [collateral.root_ca_crl()],
+        time,
+    )?;
+    let qe_key = verify_certificate_chain(
+        "QE identity",
+        collateral.qe_identity_issuer_chain(),
+        [collateral.root_ca_crl()],
+        time,
+    )?;
+    let quote_key = verify_certificate_chain(
+        "quote",
+        &quote_chain,
+        [collateral.root_ca_crl(), collateral.pck_crl()],
+        time,
+    )?;
+
+    let tcb_info_verifier = SignedTcbInfoVerifier::new(tcb_key, time);
+    ensure_verified(&tcb_info_verifier, tcb_info_verifier.verify(&evidence))?;
+
+    let qe_identity_verifier = SignedQeIdentityVerifier::new(qe_key, time);
+    ensure_verified(
+        &qe_identity_verifier,
+        qe_identity_verifier.verify(&evidence),
+    )?;
+
+    let signed_qe_identity: SignedQeIdentity = evidence.get();
+    let qe_identity = QeIdentity::try_from(&signed_qe_identity)
+        .map_err(|e| Error::InvalidEvidence(e.to_string()))?;
+    let qe_report_body_verifier = QeReportBodyVerifier::new(qe_identity);
+    ensure_verified(
+        &qe_report_body_verifier,
+        qe_report_body_verifier.verify(&evidence),
+    )?;
+
+    let quote_verifier = Quote3Verifier::new(quote_key);
+    ensure_verified(&quote_verifier, quote_verifier.verify(&evidence))?;
+
+    let identities_verifier = TrustedIdentitiesVerifier::new(identities);
+    ensure_verified(
+        &identities_verifier,
+        identities_verifier.verify(&IdentityEvidence(&evidence)),
+    )?;
+
+    let report =
+        VerificationReport::new(&evidence, &quote_chain, collateral.tcb_info(), identities)?;
+    policy.check(
+        report.tcb_status,
+        &report.advisory_ids,
+        latest_tcb_date(collateral.tcb_info()),
+        now,
+    )?;
+
+    Ok(report)
+}
+
+/// Builds the [`VerificationReport`] for a quote that was verified with [`verify`].
+pub fn report(
+    quote: Quote3<Vec<u8>>,
+    collateral: Collateral,
+    identities: &[TrustedIdentity],
+) -> Result<VerificationReport, Error> {
+    let quote_chain = certificate_chain_from_quote(&quote)?;
+    let evidence = Evidence::new(quote, collateral.clone())
+        .map_err(|e| Error::InvalidEvidence(e.to_string()))?;
+    VerificationReport::new(&evidence, &quote_chain, collateral.tcb_info(), identities)
+}
+
+/// The evidence as seen by the trusted identities, i.e. without any advisories, since those are
+/// checked by the [`TcbPolicy`].
+pub(crate) struct IdentityEvidence<'a>(pub(crate) &'a Evidence<Vec<u8>>);
+
+impl Accessor<Advisories> for IdentityEvidence<'_> {
+    fn get(&self) -> Advisories {
+        Advisories::default()
+    }
+}
+
+macro_rules! identity_evidence_field_accessor {
+    ($($field_type:ty),*) => {$(
+        impl Accessor<$field_type> for IdentityEvidence<'_> {
+            fn get(&self) -> $field_type {
+                self.0.get()
+            }
+        }
+    )*}
+}
+
+identity_evidence_field_accessor!(MrEnclave, MrSigner, IsvProductId, IsvSvn);
+
+fn ensure_verified<V, O>(verifier: &V, output: VerificationOutput<O>) -> Result<(), Error>
+where
+    V: VerificationMessage<O>,
+{
+    if output.is_success().into() {
+        Ok(())
+    } else {
+        Err(Error::DcapVerification(
+            VerificationTreeDisplay::new(verifier, output).to_string(),
+        ))
+    }
+}
+
+fn verification_time(time: Option<u64>) -> Result<Option<DateTime>, Error> {
+    time.map(|secs| {
+        DateTime::from_unix_duration(Duration::from_secs(secs))
+            .map_err(|_| Error::InvalidTime(secs))
+    })
+    .transpose()
+}
+
+// Returns the key of the leaf certificate (i.e. the signing key) if the chain is valid.
+fn verify_certificate_chain<'c>(
+    name: &str,
+    chain: &[Certificate],
+    crls: impl IntoIterator<Item = &'c CertificateList>,
+    time: Option<DateTime>,
+) -> Result<Option<VerifyingKey>, Error> {
+    TlsCertificateChainVerifier
+        .verify_certificate_chain(chain, crls, time)
+        .map_err(|e| Error::DcapVerification(format!("The {name} issuer chain is invalid: {e}")))?;
+
+    Ok(chain.first().and_then(|cert| {
+        let key_bytes = cert
+            .tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .as_bytes()?;
+        VerifyingKey::from_sec1_bytes(key_bytes).ok()
+    }))
+}
+
+/// Returns the time (as a UNIX timestamp, in seconds) until which a verification of the quote
+/// against the collateral holds, i.e. the earliest `nextUpdate` of the TCB info, the QE identity
+/// and the CRLs, and the earliest `notAfter` of the issuer chains and the quote's PCK chain.
+///
+/// Returns `None` if any of them is missing or malformed.
+pub fn valid_until(quote: &Quote3<Vec<u8>>, collateral: &Collateral) -> Option<u64> {
+    let next_update = |signed_json: &str, field: &str| -> Option<u64> {
+        let json: serde_json::Value = serde_json::from_str(signed_json).ok()?;
+        let next_update: DateTime = json.get(field)?.get("nextUpdate")?.as_str()?.parse().ok()?;
+        Some(next_update.unix_duration().as_secs())
+    };
+    let crl_next_update = |crl: &CertificateList| {
+        crl.tbs_cert_list
+            .next_update
+            .map(|time| time.to_unix_duration().as_secs())
+    };
+    let not_after = |cert: &Certificate| {
+        cert.tbs_certificate
+            .validity
+            .not_after
+            .to_unix_duration()
+            .as_secs()
+    };
+
+    let quote_chain = certificate_chain_from_quote(quote).ok()?;
+    let certs = quote_chain
+        .iter()
+        .chain(collateral.pck_crl_issuer_chain())
+        .chain(collateral.tcb_issuer_chain())
+        .chain(collateral.qe_identity_issuer_chain())
+        .map(not_after);
+
+    [
+        next_update(collateral.tcb_info(), "tcbInfo")?,
+        next_update(collateral.qe_identity(), "enclaveIdentity")?,
+        crl_next_update(collateral.root_ca_crl())?,
+        crl_next_update(collateral.pck_crl())?,
+    ]
+    .into_iter()
+    .chain(certs)
+    .min()
+}
+
+/// Returns the issuer of the quote's PCK certificate, i.e. the CA whose CRL applies to it.
+pub fn pck_crl_issuer(quote: &Quote3<Vec<u8>>) -> Result<String, Error> {
+    let quote_chain = certificate_chain_from_quote(quote)?;
+    let pck_cert = quote_chain
+        .first()
+        .ok_or_else(|| Error::InvalidEvidence("empty PCK certificate chain".to_string()))?;
+    Ok(pck_cert.tbs_certificate.issuer.to_string())
+}
+
+// The PCK certificate chain is part of the quote's certification data.
+fn certificate_chain_from_quote(quote: &Quote3<Vec<u8>>) -> Result<Vec<Certificate>, Error> {
+    let signature_data = quote.signature_data();
+    let certification_data = signature_data.certification_data();
+    let CertificationData::PckCertificateChain(pem_chain) = certification_data else {
+        return Err(Error::InvalidEvidence(
+            "unsupported quote certification data".to_string(),
+        ));
+    };
+
+    pem_chain
+        .into_iter()
+        .map(Certificate::from_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::InvalidEvidence(e.to_string()))
 }
 
 #[cfg(test)]
@@ -29,12 +277,54 @@ mod tests {
     use mc_sgx_dcap_sys_types::sgx_ql_qve_collateral_t;
     use mc_sgx_dcap_types::{Collateral, Quote3};
 
-    use crate::intel_sgx::dcap::certificate_chain::TlsCertificateChainVerifier;
+    use super::*;
+    use crate::intel_sgx::{
+        dcap::{
+            certificate_chain::TlsCertificateChainVerifier,
+            tcb_policy::{TcbPolicyError, TcbStatus},
+        },
+        Error,
+    };
 
     const TCB_INFO_JSON: &str = include_str!("../../data/fmspc_00906ED50000_2023_07_12.json");
     const QE_IDENTITY_JSON: &str = include_str!("../../data/qe_identity.json");
 
+    /// The certificates and CRLs that a collateral is made of.
+    struct Pki {
+        root_ca: &'static str,
+        processor_ca: &'static str,
+        tcb_signer: &'static str,
+        root_crl: &'static [u8],
+        pck_crl: &'static [u8],
+    }
+
+    const INTEL_PKI: Pki = Pki {
+        root_ca: include_str!("../../data/root_ca.pem"),
+        processor_ca: include_str!("../../data/processor_ca.pem"),
+        tcb_signer: include_str!("../../data/tcb_signer.pem"),
+        root_crl: include_bytes!("../../data/root_crl.der"),
+        pck_crl: include_bytes!("../../data/processor_crl.der"),
+    };
+
+    // Intel's collateral is never valid all at once (the PCK CRL expired before the TCB info was
+    // issued), so the `test_pki` fixtures re-sign the same quote, TCB info and QE identity with a
+    // test PKI whose CRLs are current from 2023-07-12T21:00:00Z to 2023-08-01T00:00:00Z.
+    const TEST_PKI: Pki = Pki {
+        root_ca: include_str!("../../data/test_pki/root_ca.pem"),
+        processor_ca: include_str!("../../data/test_pki/processor_ca.pem"),
+        tcb_signer: include_str!("../../data/test_pki/tcb_signer.pem"),
+        root_crl: include_bytes!("../../data/test_pki/root_crl.der"),
+        pck_crl: include_bytes!("../../data/test_pki/processor_crl.der"),
+    };
+    const TEST_QUOTE: &[u8] = include_bytes!("../../data/test_pki/quote.dat");
+    const TEST_TCB_INFO_JSON: &str = include_str!("../../data/test_pki/tcb_info.json");
+    const TEST_QE_IDENTITY_JSON: &str = include_str!("../../data/test_pki/qe_identity.json");
+
     fn collateral(tcb_info: &str, qe_identity: &str) -> Collateral {
+        collateral_with(&INTEL_PKI, tcb_info, qe_identity)
+    }
+
+    fn collateral_with(pki: &Pki, tcb_info: &str, qe_identity: &str) -> Collateral {
         let mut sgx_collateral = sgx_ql_qve_collateral_t::default();
 
         // SAFETY: Version is a union which is inherently unsafe
@@ -43,25 +333,25 @@ mod tests {
         version.major_version = 3;
         version.minor_version = 1;
 
-        let pck_issuer_cert = include_str!("../../data/processor_ca.pem");
-        let root_cert = include_str!("../../data/root_ca.pem");
-        let mut pck_crl_chain = [pck_issuer_cert, root_cert].join("\n").as_bytes().to_vec();
+        let mut pck_crl_chain = [pki.processor_ca, pki.root_ca]
+            .join("\n")
+            .as_bytes()
+            .to_vec();
         pck_crl_chain.push(0);
         sgx_collateral.pck_crl_issuer_chain = pck_crl_chain.as_ptr() as _;
         sgx_collateral.pck_crl_issuer_chain_size = pck_crl_chain.len() as u32;
 
-        let mut root_crl = include_bytes!("../../data/root_crl.der").to_vec();
+        let mut root_crl = pki.root_crl.to_vec();
         root_crl.push(0);
         sgx_collateral.root_ca_crl = root_crl.as_ptr() as _;
         sgx_collateral.root_ca_crl_size = root_crl.len() as u32;
 
-        let mut pck_crl = include_bytes!("../../data/processor_crl.der").to_vec();
+        let mut pck_crl = pki.pck_crl.to_vec();
         pck_crl.push(0);
         sgx_collateral.pck_crl = pck_crl.as_ptr() as _;
         sgx_collateral.pck_crl_size = pck_crl.len() as u32;
 
-        let tcb_cert = include_str!("../../data/tcb_signer.pem");
-        let mut tcb_chain = [tcb_cert, root_cert].join("\n").as_bytes().to_vec();
+        let mut tcb_chain = [pki.tcb_signer, pki.root_ca].join("\n").as_bytes().to_vec();
         tcb_chain.push(0);
         sgx_collateral.tcb_info_issuer_chain = tcb_chain.as_ptr() as _;
         sgx_collateral.tcb_info_issuer_chain_size = tcb_chain.len() as u32;
@@ -105,6 +395,167 @@ mod tests {
         // println!("\n{displayable}");
     }
 
+    #[test]
+    fn valid_until_is_the_earliest_expiry() {
+        let quote_bytes = include_bytes!("../../data/hw_quote.dat");
+        let quote = Quote3::try_from(quote_bytes.to_vec()).expect("Failed to parse quote");
+        let collateral = collateral(TCB_INFO_JSON, QE_IDENTITY_JSON);
+
+        // the PCK CRL's nextUpdate (2023-05-21T22:00:36Z) precedes the TCB info's
+        assert_eq!(valid_until(&quote, &collateral), Some(1684706436));
+
+        let tcb_info = TCB_INFO_JSON.replace("\"nextUpdate\":\"2023-08-11T19:56:44Z\",", "");
+        let collateral = self::collateral(&tcb_info, QE_IDENTITY_JSON);
+        assert_eq!(valid_until(&quote, &collateral), None);
+    }
+
+    #[test]
+    fn pck_crl_issuer_is_the_pck_certificate_issuer() {
+        let quote_bytes = include_bytes!("../../data/hw_quote.dat");
+        let quote = Quote3::try_from(quote_bytes.to_vec()).expect("Failed to parse quote");
+        let processor_ca = Certificate::from_pem(include_str!("../../data/processor_ca.pem"))
+            .expect("failed to parse cert");
+
+        assert_eq!(
+            pck_crl_issuer(&quote).expect("quote has a PCK chain"),
+            processor_ca.tbs_certificate.subject.to_string()
+        );
+    }
+
+    #[test]
+    fn verify_with_policy_reports_rejected_tcb_status() {
+        let quote_bytes = include_bytes!("../../data/hw_quote.dat");
+        let quote = Quote3::try_from(quote_bytes.to_vec()).expect("Failed to parse quote");
+        let collateral = collateral(TCB_INFO_JSON, QE_IDENTITY_JSON);
+        let mr_enclave =
+            MrEnclave::from_hex("840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4")
+                .expect("malformed MRENCLAVE hex");
+        let identities = [TrustedMrEnclaveIdentity::new(mr_enclave, [""; 0], [""; 0]).into()];
+
+        let report = verify_with_policy(
+            quote.clone(),
+            collateral.clone(),
+            &identities,
+            &TcbPolicy::default(),
+            None,
+        )
+        .expect("accepted by the default policy");
+        assert_eq!(report.tcb_status, TcbStatus::SWHardeningNeeded);
+        assert_eq!(
+            report.advisory_ids,
+            ["INTEL-SA-00334", "INTEL-SA-00615"]
+                .map(String::from)
+                .into()
+        );
+        assert_eq!(hex::encode(report.fmspc), "00906ed50000");
+        assert_eq!(&report.mr_enclave[..], AsRef::<[u8]>::as_ref(&mr_enclave));
+        assert_eq!(report.matched_identity, Some(0));
+        assert_eq!(
+            report.pck_certificate.subject,
+            "C=US,ST=CA,L=Santa Clara,O=Intel Corporation,CN=Intel SGX PCK Certificate"
+        );
+        let tcb_level = report.tcb_level.expect("matching TCB level");
+        assert_eq!(tcb_level.sgx_tcb_components.len(), 16);
+
+        let up_to_date_only = TcbPolicy::new(
+            [TcbStatus::UpToDate],
+            ["INTEL-SA-00334", "INTEL-SA-00615"],
+            None,
+        );
+        let err = verify_with_policy(
+            quote.clone(),
+            collateral.clone(),
+            &identities,
+            &up_to_date_only,
+            None,
+        )
+        .expect_err("status not accepted");
+        assert!(matches!(
+            err,
+            Error::TcbPolicy(TcbPolicyError::StatusNotAccepted {
+                status: TcbStatus::SWHardeningNeeded
+            })
+        ));
+
+        let other_identities =
+            [TrustedMrEnclaveIdentity::new(MrEnclave::from([0; 32]), [""; 0], [""; 0]).into()];
+        let err = verify_with_policy(
+            quote,
+            collateral,
+            &other_identities,
+            &TcbPolicy::default(),
+            None,
+        )
+        .expect_err("untrusted enclave");
+        assert!(matches!(err, Error::DcapVerification(_)));
+    }
+
+    #[test]
+    fn verify_checks_collateral_expiry() {
+        // the test collateral is valid from 2023-07-12T21:00:00Z (when its CRLs were issued) to
+        // 2023-08-01T00:00:00Z (when its PCK CRL goes stale), the TCB info until
+        // 2023-08-11T19:56:44Z and the QE identity until 2023-08-11T20:48:25Z
+        const VALID_TIME: u64 = 1689206400; // 2023-07-13
+        const STALE_CRL_TIME: u64 = 1691193600; // 2023-08-05
+        const EXPIRED_TIME: u64 = 1691884800; // 2023-08-13
+
+        let quote = Quote3::try_from(TEST_QUOTE.to_vec()).expect("Failed to parse quote");
+        let malformed_collateral = collateral_with(&TEST_PKI, "{}", TEST_QE_IDENTITY_JSON);
+        let collateral = collateral_with(&TEST_PKI, TEST_TCB_INFO_JSON, TEST_QE_IDENTITY_JSON);
+        let mr_enclave =
+            MrEnclave::from_hex("840d61b0585dc8b4dc90f53af293c760fda06bee75978a6a86263ffb296423f4")
+                .expect("malformed MRENCLAVE hex");
+        let identities = [TrustedMrEnclaveIdentity::new(
+            mr_enclave,
+            [""; 0],
+            ["INTEL-SA-00334", "INTEL-SA-00615"],
+        )
+        .into()];
+
+        let output = verify(
+            quote.clone(),
+            collateral.clone(),
+            &identities,
+            Some(VALID_TIME),
+        )
+        .expect("valid evidence");
+        assert_eq!(output.is_success().unwrap_u8(), 1);
+
+        for time in [VALID_TIME - 2 * 86400, STALE_CRL_TIME, EXPIRED_TIME] {
+            let output = verify(quote.clone(), collateral.clone(), &identities, Some(time))
+                .expect("valid evidence");
+            assert_eq!(output.is_success().unwrap_u8(), 0);
+        }
+
+        for (time, reason) in [
+            (STALE_CRL_TIME, "stale PCK CRL"),
+            (EXPIRED_TIME, "expired TCB info"),
+        ] {
+            let err = verify_with_policy(
+                quote.clone(),
+                collateral.clone(),
+                &identities,
+                &TcbPolicy::default(),
+                Some(time),
+            )
+            .expect_err(reason);
+            assert!(matches!(err, Error::DcapVerification(_)));
+        }
+        verify_with_policy(
+            quote.clone(),
+            collateral.clone(),
+            &identities,
+            &TcbPolicy::default(),
+            Some(VALID_TIME),
+        )
+        .expect("valid collateral");
+        assert_eq!(valid_until(&quote, &collateral), Some(1690848000));
+
+        let err = verify(quote, malformed_collateral, &identities, Some(VALID_TIME))
+            .expect_err("malformed TCB info");
+        assert!(matches!(err, Error::InvalidEvidence(_)));
+    }
+
     #[test]
     fn test_quote_parse() {
         let quote_bytes = hex!(
@@ -1,7 +1,7 @@
 use der::{DateTime, Encode};
 use mc_attestation_verifier::{CertificateChainVerifier, CertificateChainVerifierError};
 use x509_cert::{crl::CertificateList, Certificate};
-use x509_parser::parse_x509_certificate;
+use x509_parser::{parse_x509_certificate, parse_x509_crl};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
 pub struct TlsCertificateChainVerifier;
@@ -17,11 +17,22 @@ impl CertificateChainVerifier for TlsCertificateChainVerifier {
     fn verify_certificate_chain<'a, 'b>(
         &self,
         certificate_chain: impl IntoIterator<Item = &'a Certificate>,
-        _crls: impl IntoIterator<Item = &'b CertificateList>,
-        _time: impl Into<Option<DateTime>>,
+        crls: impl IntoIterator<Item = &'b CertificateList>,
+        time: impl Into<Option<DateTime>>,
     ) -> Result<(), CertificateChainVerifierError> {
+        let certificate_chain: Vec<_> = certificate_chain.into_iter().collect();
+        if certificate_chain.is_empty() {
+            return Err(CertificateChainVerifierError::GeneralCertificateError);
+        }
+        let time = time.into();
+        if let Some(time) = time {
+            for cert in &certificate_chain {
+                verify_validity(cert, time)?;
+            }
+        }
+
         let enc_certs = certificate_chain
-            .into_iter()
+            .iter()
             .map(|cert| cert.to_der())
             .collect::<Result<Vec<_>, _>>()
             .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
@@ -31,32 +42,116 @@ impl CertificateChainVerifier for TlsCertificateChainVerifier {
             .map(|der| parse_x509_certificate(der))
             .collect::<Result<Vec<_>, _>>()
             .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
-        // Skip applying the Certificate Revocation List entirely
-        /*
-           let enc_crls = crls
-               .into_iter()
-               .map(|crl| der_encode(crl))
-               .collect::<Result<Vec<_>, _>>()
-               .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
-           let _crls = enc_crls
-               .iter()
-               .map(|enc_crl| parse_x509_crl(enc_crl.as_ref()))
-               .collect::<Result<Vec<_>, _>>();
-           .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
-        */
 
         let v: Vec<_> = cert_chain.to_vec();
         let mut issuers: Vec<usize> = (1..v.len()).collect();
         issuers.push(v.len() - 1);
         let subjects: Vec<usize> = (0..v.len()).collect();
-        for (i, s) in core::iter::zip(issuers, subjects) {
+        for (i, s) in core::iter::zip(issuers.iter().copied(), subjects) {
             let r = v[s].1.verify_signature(Some(v[i].1.public_key()));
             r.map_err(|_| CertificateChainVerifierError::SignatureVerification)?
         }
+
+        // Passing no CRLs at all only checks the chain's signatures (e.g. the tcbinfo contract
+        // checking a signing cert against the root before any CRL is known). Otherwise, the CRLs
+        // must cover every issuer in the chain.
+        let crls: Vec<_> = crls.into_iter().collect();
+        if crls.is_empty() {
+            return Ok(());
+        }
+        for (cert, i) in core::iter::zip(&certificate_chain, issuers) {
+            verify_not_revoked(cert, certificate_chain[i], &crls, time)?;
+        }
         Ok(())
     }
 }
 
+fn verify_validity(
+    cert: &Certificate,
+    time: DateTime,
+) -> Result<(), CertificateChainVerifierError> {
+    let validity = &cert.tbs_certificate.validity;
+    let time = time.unix_duration();
+    if time < validity.not_before.to_unix_duration() {
+        Err(CertificateChainVerifierError::CertificateNotYetValid)
+    } else if time > validity.not_after.to_unix_duration() {
+        Err(CertificateChainVerifierError::CertificateExpired)
+    } else {
+        Ok(())
+    }
+}
+
+// Every certificate must be covered by a CRL signed by its issuer (whose own signature on the
+// certificate has already been verified), and must not be listed in it. If a time is given, the CRL
+// must also be current at that time.
+fn verify_not_revoked(
+    cert: &Certificate,
+    issuer: &Certificate,
+    crls: &[&CertificateList],
+    time: Option<DateTime>,
+) -> Result<(), CertificateChainVerifierError> {
+    let crl = crls
+        .iter()
+        .find(|crl| crl.tbs_cert_list.issuer == cert.tbs_certificate.issuer)
+        .ok_or(CertificateChainVerifierError::GeneralCertificateError)?;
+    if let Some(time) = time {
+        verify_crl_validity(crl, time)?;
+    }
+    let enc_crl = crl
+        .to_der()
+        .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
+    verify_crl_signature(&enc_crl, issuer)?;
+
+    let revoked = crl
+        .tbs_cert_list
+        .revoked_certificates
+        .iter()
+        .flatten()
+        .any(|revoked| revoked.serial_number == cert.tbs_certificate.serial_number);
+    if revoked {
+        Err(CertificateChainVerifierError::CertificateRevoked)
+    } else {
+        Ok(())
+    }
+}
+
+// A CRL without a `nextUpdate` is never considered current, since there's no telling when it went
+// stale.
+fn verify_crl_validity(
+    crl: &CertificateList,
+    time: DateTime,
+) -> Result<(), CertificateChainVerifierError> {
+    let time = time.unix_duration();
+    let this_update = crl.tbs_cert_list.this_update.to_unix_duration();
+    let next_update = crl
+        .tbs_cert_list
+        .next_update
+        .ok_or(CertificateChainVerifierError::GeneralCertificateError)?
+        .to_unix_duration();
+    if time < this_update || time > next_update {
+        Err(CertificateChainVerifierError::GeneralCertificateError)
+    } else {
+        Ok(())
+    }
+}
+
+/// Verifies that the (DER-encoded) CRL was signed by `issuer`.
+pub fn verify_crl_signature(
+    crl: &[u8],
+    issuer: &Certificate,
+) -> Result<(), CertificateChainVerifierError> {
+    let issuer = issuer
+        .to_der()
+        .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
+    let (_, issuer) = parse_x509_certificate(&issuer)
+        .map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
+    let (_, crl) =
+        parse_x509_crl(crl).map_err(|_| CertificateChainVerifierError::GeneralCertificateError)?;
+
+    crl.verify_signature(issuer.public_key())
+        .map_err(|_| CertificateChainVerifierError::SignatureVerification)
+}
+
 #[cfg(test)]
 mod test {
     use der::{Decode, DecodePem};
@@ -68,6 +163,10 @@ mod test {
     const ROOT_CA: &str = include_str!("../../../data/root_ca.pem");
     const PROCESSOR_CRL: &[u8] = include_bytes!("../../../data/processor_crl.der");
     const ROOT_CRL: &[u8] = include_bytes!("../../../data/root_crl.der");
+    // a test CA whose CRL revokes the leaf it issued
+    const REVOKED_LEAF_CERT: &str = include_str!("../../../data/revoked_leaf_cert.pem");
+    const REVOKING_CA: &str = include_str!("../../../data/revoking_ca.pem");
+    const REVOKING_CA_CRL: &[u8] = include_bytes!("../../../data/revoking_ca_crl.der");
 
     #[test]
     fn verify_valid_cert_chain() {
@@ -102,6 +201,118 @@ mod test {
         );
     }
 
+    #[test]
+    fn cert_chain_validity_is_checked_at_time() {
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let crls = [ROOT_CRL, PROCESSOR_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        let verifier = TlsCertificateChainVerifier::new(ROOT_CA);
+        let at = |time: &str| time.parse::<DateTime>().expect("failed to parse time");
+
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), at("2023-05-01T00:00:00Z"))
+            .is_ok());
+        // the leaf certificate is valid from 2022-06-13 to 2029-06-13
+        assert_eq!(
+            verifier.verify_certificate_chain(
+                chain.iter(),
+                crls.iter(),
+                at("2021-01-01T00:00:00Z")
+            ),
+            Err(CertificateChainVerifierError::CertificateNotYetValid)
+        );
+        assert_eq!(
+            verifier.verify_certificate_chain(
+                chain.iter(),
+                crls.iter(),
+                at("2030-01-01T00:00:00Z")
+            ),
+            Err(CertificateChainVerifierError::CertificateExpired)
+        );
+    }
+
+    #[test]
+    fn stale_crl_fails() {
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let crls = [ROOT_CRL, PROCESSOR_CRL]
+            .iter()
+            .map(|crl| CertificateList::from_der(crl).expect("failed to parse CRL"))
+            .collect::<Vec<_>>();
+        let verifier = TlsCertificateChainVerifier::new(ROOT_CA);
+        let at = |time: &str| time.parse::<DateTime>().expect("failed to parse time");
+
+        // the processor CRL is valid from 2023-04-21T22:00:36Z to 2023-05-21T22:00:36Z
+        for time in ["2023-04-10T00:00:00Z", "2023-07-12T20:48:25Z"] {
+            assert_eq!(
+                verifier.verify_certificate_chain(chain.iter(), crls.iter(), at(time)),
+                Err(CertificateChainVerifierError::GeneralCertificateError)
+            );
+        }
+        // ... which isn't checked without a time
+        assert!(verifier
+            .verify_certificate_chain(chain.iter(), crls.iter(), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn cert_chain_without_issuer_crl_fails() {
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let root_crl = CertificateList::from_der(ROOT_CRL).expect("failed to parse CRL");
+        let verifier = TlsCertificateChainVerifier::new(ROOT_CA);
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), [&root_crl], None),
+            Err(CertificateChainVerifierError::GeneralCertificateError)
+        );
+    }
+
+    #[test]
+    fn revoked_cert_fails() {
+        let chain = [REVOKED_LEAF_CERT, REVOKING_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let crl = CertificateList::from_der(REVOKING_CA_CRL).expect("failed to parse CRL");
+        let verifier = TlsCertificateChainVerifier::new(REVOKING_CA);
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), [&crl], None),
+            Err(CertificateChainVerifierError::CertificateRevoked)
+        );
+        // the CA itself isn't revoked
+        assert!(verifier
+            .verify_certificate_chain(chain[1..].iter(), [&crl], None)
+            .is_ok());
+    }
+
+    #[test]
+    fn crl_not_signed_by_issuer_fails() {
+        let chain = [LEAF_CERT, PROCESSOR_CA, ROOT_CA]
+            .iter()
+            .map(|cert| Certificate::from_pem(cert).expect("failed to parse cert"))
+            .collect::<Vec<_>>();
+        let mut processor_crl =
+            CertificateList::from_der(PROCESSOR_CRL).expect("failed to parse CRL");
+        // keep the issuer name, but swap in a signature by another key
+        let other_crl = CertificateList::from_der(REVOKING_CA_CRL).expect("failed to parse CRL");
+        processor_crl.signature = other_crl.signature;
+        let root_crl = CertificateList::from_der(ROOT_CRL).expect("failed to parse CRL");
+        let verifier = TlsCertificateChainVerifier::new(ROOT_CA);
+        assert_eq!(
+            verifier.verify_certificate_chain(chain.iter(), [&root_crl, &processor_crl], None),
+            Err(CertificateChainVerifierError::SignatureVerification)
+        );
+    }
+
     #[test]
     #[ignore]
     fn unordered_cert_chain_succeeds() {
@@ -119,5 +330,18 @@ mod test {
             .is_ok());
     }
 
+    #[test]
+    fn crl_signatures() {
+        let root = Certificate::from_pem(ROOT_CA).expect("failed to parse cert");
+        let processor_ca = Certificate::from_pem(PROCESSOR_CA).expect("failed to parse cert");
+
+        assert!(verify_crl_signature(ROOT_CRL, &root).is_ok());
+        assert!(verify_crl_signature(PROCESSOR_CRL, &processor_ca).is_ok());
+        assert_eq!(
+            verify_crl_signature(PROCESSOR_CRL, &root),
+            Err(CertificateChainVerifierError::SignatureVerification)
+        );
+    }
+
     // TODO(hu55a1n1) - add [PKITS tests](https://csrc.nist.gov/projects/pki-testing)
 }
@@ -0,0 +1,117 @@
+//! The details of a successful DCAP verification, i.e. what the attesting platform and enclave
+//! were found to be.
+
+use std::collections::BTreeSet;
+
+use mc_attestation_verifier::{
+    Accessor, Advisories, Evidence, TrustedIdentitiesVerifier, TrustedIdentity, Verifier,
+};
+use mc_sgx_dcap_types::{Quote3, TcbInfo as PckTcb};
+use serde::{Deserialize, Serialize};
+use x509_cert::Certificate;
+
+use super::{
+    tcb_policy::{matching_tcb_level, status_and_advisories, TcbStatus},
+    IdentityEvidence,
+};
+use crate::intel_sgx::Error;
+
+/// The TCB level of the TCB info that the platform's TCB (as per its PCK certificate) matched.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TcbLevel {
+    pub sgx_tcb_components: Vec<u32>,
+    pub pce_svn: u32,
+    pub tcb_date: String,
+}
+
+/// The PCK (leaf) certificate of the quote, which identifies the attesting platform.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PckCertificate {
+    pub subject: String,
+    pub issuer: String,
+    /// The hex-encoded serial number
+    pub serial_number: String,
+    /// UNIX timestamps (in seconds) of the certificate's validity period
+    pub not_before: u64,
+    pub not_after: u64,
+}
+
+impl From<&Certificate> for PckCertificate {
+    fn from(cert: &Certificate) -> Self {
+        let tbs = &cert.tbs_certificate;
+        Self {
+            subject: tbs.subject.to_string(),
+            issuer: tbs.issuer.to_string(),
+            serial_number: tbs.serial_number.to_string(),
+            not_before: tbs.validity.not_before.to_unix_duration().as_secs(),
+            not_after: tbs.validity.not_after.to_unix_duration().as_secs(),
+        }
+    }
+}
+
+/// The outcome of a successful DCAP verification.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub tcb_status: TcbStatus,
+    pub advisory_ids: BTreeSet<String>,
+    /// The matched TCB level, if the TCB info could be parsed
+    pub tcb_level: Option<TcbLevel>,
+    pub fmspc: [u8; 6],
+    pub mr_enclave: [u8; 32],
+    pub mr_signer: [u8; 32],
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+    /// The index of the first trusted identity that the enclave matched
+    pub matched_identity: Option<usize>,
+    pub pck_certificate: PckCertificate,
+}
+
+impl VerificationReport {
+    /// Builds the report for the (already verified) evidence. `pck_chain` is the certificate chain
+    /// of the quote, starting with the PCK certificate.
+    pub(crate) fn new(
+        evidence: &Evidence<Vec<u8>>,
+        pck_chain: &[Certificate],
+        signed_tcb_info: &str,
+        identities: &[TrustedIdentity],
+    ) -> Result<Self, Error> {
+        let pck_certificate = pck_chain
+            .first()
+            .ok_or_else(|| Error::InvalidEvidence("empty PCK certificate chain".to_string()))?;
+        let pck_tcb =
+            PckTcb::try_from(pck_certificate).map_err(|e| Error::InvalidEvidence(e.to_string()))?;
+
+        let advisories: Advisories = evidence.get();
+        let (tcb_status, advisory_ids) = status_and_advisories(&advisories)
+            .map_err(|e| Error::InvalidEvidence(e.to_string()))?;
+
+        let quote: Quote3<Vec<u8>> = evidence.get();
+        let report_body = quote.app_report_body();
+
+        let matched_identity = identities.iter().position(|identity| {
+            TrustedIdentitiesVerifier::new([identity.clone()])
+                .verify(&IdentityEvidence(evidence))
+                .is_success()
+                .into()
+        });
+
+        Ok(Self {
+            tcb_status,
+            advisory_ids,
+            tcb_level: matching_tcb_level(signed_tcb_info, &pck_tcb),
+            fmspc: *pck_tcb.fmspc(),
+            mr_enclave: measurement(report_body.mr_enclave().as_ref())?,
+            mr_signer: measurement(report_body.mr_signer().as_ref())?,
+            isv_prod_id: report_body.isv_product_id().into(),
+            isv_svn: report_body.isv_svn().into(),
+            matched_identity,
+            pck_certificate: pck_certificate.into(),
+        })
+    }
+}
+
+fn measurement(bytes: &[u8]) -> Result<[u8; 32], Error> {
+    bytes
+        .try_into()
+        .map_err(|_| Error::InvalidEvidence("invalid measurement size".to_string()))
+}
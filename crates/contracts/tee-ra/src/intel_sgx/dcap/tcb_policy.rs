@@ -0,0 +1,317 @@
+//! A configurable policy for the TCB status (and the corresponding advisories) of the attesting
+//! platform.
+
+use std::collections::BTreeSet;
+
+use der::DateTime;
+use mc_attestation_verifier::Advisories;
+pub use mc_attestation_verifier::AdvisoryStatus as TcbStatus;
+use mc_sgx_dcap_types::TcbInfo as PckTcb;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::report::TcbLevel;
+
+/// The statuses that are accepted by default.
+pub const DEFAULT_ACCEPTED_STATUSES: [TcbStatus; 2] =
+    [TcbStatus::UpToDate, TcbStatus::SWHardeningNeeded];
+
+/// The advisories that are allowed by default.
+pub const DEFAULT_ALLOWED_ADVISORIES: [&str; 2] = ["INTEL-SA-00334", "INTEL-SA-00615"];
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum TcbPolicyError {
+    #[error("TCB status {status:?} is not accepted by the TCB policy")]
+    StatusNotAccepted { status: TcbStatus },
+    #[error("Advisory {advisory} (TCB status {status:?}) is not allowed by the TCB policy")]
+    AdvisoryNotAllowed { status: TcbStatus, advisory: String },
+    #[error(
+        "TCB status {status:?} was only accepted for {grace_period}s after {out_of_date_since}, \
+        but it's now {now}"
+    )]
+    GracePeriodExpired {
+        status: TcbStatus,
+        out_of_date_since: u64,
+        grace_period: u64,
+        now: u64,
+    },
+}
+
+/// The TCB statuses and advisories that are accepted for the attesting platform.
+///
+/// A platform whose TCB level is `OutOfDate` (or `OutOfDateConfigurationNeeded`) and isn't
+/// accepted explicitly, is still accepted for `out_of_date_grace_period` seconds after the latest
+/// TCB level was released (i.e. the `tcbDate` of the first level in the TCB info).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TcbPolicy {
+    accepted_statuses: BTreeSet<TcbStatus>,
+    allowed_advisories: BTreeSet<String>,
+    out_of_date_grace_period: Option<u64>,
+}
+
+impl Default for TcbPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACCEPTED_STATUSES, DEFAULT_ALLOWED_ADVISORIES, None)
+    }
+}
+
+impl TcbPolicy {
+    pub fn new(
+        accepted_statuses: impl IntoIterator<Item = TcbStatus>,
+        allowed_advisories: impl IntoIterator<Item = impl ToString>,
+        out_of_date_grace_period: Option<u64>,
+    ) -> Self {
+        Self {
+            accepted_statuses: accepted_statuses.into_iter().collect(),
+            allowed_advisories: allowed_advisories
+                .into_iter()
+                .map(|advisory| advisory.to_string())
+                .collect(),
+            out_of_date_grace_period,
+        }
+    }
+
+    pub fn accepted_statuses(&self) -> &BTreeSet<TcbStatus> {
+        &self.accepted_statuses
+    }
+
+    pub fn allowed_advisories(&self) -> &BTreeSet<String> {
+        &self.allowed_advisories
+    }
+
+    pub fn out_of_date_grace_period(&self) -> Option<u64> {
+        self.out_of_date_grace_period
+    }
+
+    /// Checks the TCB status and advisories of the platform against the policy.
+    ///
+    /// `out_of_date_since` and `now` are UNIX timestamps (in seconds) and are only required for
+    /// applying the grace period, i.e. without them an `OutOfDate` status must be accepted
+    /// explicitly.
+    pub fn check(
+        &self,
+        status: TcbStatus,
+        advisories: &BTreeSet<String>,
+        out_of_date_since: Option<u64>,
+        now: Option<u64>,
+    ) -> Result<(), TcbPolicyError> {
+        if !self.accepted_statuses.contains(&status) {
+            self.check_grace_period(status, out_of_date_since, now)?;
+        }
+
+        match advisories.difference(&self.allowed_advisories).next() {
+            Some(advisory) => Err(TcbPolicyError::AdvisoryNotAllowed {
+                status,
+                advisory: advisory.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn check_grace_period(
+        &self,
+        status: TcbStatus,
+        out_of_date_since: Option<u64>,
+        now: Option<u64>,
+    ) -> Result<(), TcbPolicyError> {
+        let is_out_of_date = matches!(
+            status,
+            TcbStatus::OutOfDate | TcbStatus::OutOfDateConfigurationNeeded
+        );
+        let (Some(grace_period), Some(out_of_date_since), Some(now), true) = (
+            self.out_of_date_grace_period,
+            out_of_date_since,
+            now,
+            is_out_of_date,
+        ) else {
+            return Err(TcbPolicyError::StatusNotAccepted { status });
+        };
+
+        if now > out_of_date_since.saturating_add(grace_period) {
+            return Err(TcbPolicyError::GracePeriodExpired {
+                status,
+                out_of_date_since,
+                grace_period,
+                now,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// `Advisories` doesn't expose its fields, so we go through its serde representation.
+#[derive(Deserialize)]
+struct AdvisoriesRepr {
+    ids: BTreeSet<String>,
+    status: TcbStatus,
+}
+
+pub(crate) fn status_and_advisories(
+    advisories: &Advisories,
+) -> Result<(TcbStatus, BTreeSet<String>), serde_json::Error> {
+    let repr: AdvisoriesRepr = serde_json::from_value(serde_json::to_value(advisories)?)?;
+    Ok((repr.status, repr.ids))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedTcbInfoJson {
+    tcb_info: TcbInfoJson,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TcbInfoJson {
+    tcb_levels: Vec<TcbLevelJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TcbLevelJson {
+    tcb: TcbJson,
+    tcb_date: String,
+}
+
+#[derive(Deserialize)]
+struct TcbJson {
+    sgxtcbcomponents: Vec<TcbComponentJson>,
+    pcesvn: u32,
+}
+
+#[derive(Deserialize)]
+struct TcbComponentJson {
+    svn: u32,
+}
+
+/// Returns the release date (as a UNIX timestamp) of the latest TCB level in the (signed) TCB
+/// info, i.e. the time since which all older TCB levels are out of date.
+pub(crate) fn latest_tcb_date(signed_tcb_info: &str) -> Option<u64> {
+    let tcb_info: SignedTcbInfoJson = serde_json::from_str(signed_tcb_info).ok()?;
+    let latest_level = tcb_info.tcb_info.tcb_levels.first()?;
+    let tcb_date: DateTime = latest_level.tcb_date.parse().ok()?;
+    Some(tcb_date.unix_duration().as_secs())
+}
+
+/// Returns the first TCB level in the (signed) TCB info whose SVNs are all lower than or equal to
+/// the platform's, i.e. the level that determines the platform's TCB status.
+pub(crate) fn matching_tcb_level(signed_tcb_info: &str, pck_tcb: &PckTcb) -> Option<TcbLevel> {
+    let tcb_info: SignedTcbInfoJson = serde_json::from_str(signed_tcb_info).ok()?;
+    tcb_info
+        .tcb_info
+        .tcb_levels
+        .into_iter()
+        .find(|level| {
+            level.tcb.pcesvn <= *pck_tcb.pce_svn()
+                && level
+                    .tcb
+                    .sgxtcbcomponents
+                    .iter()
+                    .zip(pck_tcb.svns())
+                    .all(|(component, svn)| component.svn <= *svn)
+        })
+        .map(|level| TcbLevel {
+            sgx_tcb_components: level
+                .tcb
+                .sgxtcbcomponents
+                .iter()
+                .map(|component| component.svn)
+                .collect(),
+            pce_svn: level.tcb.pcesvn,
+            tcb_date: level.tcb_date,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCB_INFO_JSON: &str = include_str!("../../../data/fmspc_00906ED50000_2023_07_12.json");
+
+    fn advisories(ids: &[&str]) -> BTreeSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = TcbPolicy::default();
+
+        policy
+            .check(TcbStatus::UpToDate, &advisories(&[]), None, None)
+            .expect("up to date");
+        policy
+            .check(
+                TcbStatus::SWHardeningNeeded,
+                &advisories(&["INTEL-SA-00334", "INTEL-SA-00615"]),
+                None,
+                None,
+            )
+            .expect("mitigated advisories");
+
+        assert_eq!(
+            policy.check(
+                TcbStatus::SWHardeningNeeded,
+                &advisories(&["INTEL-SA-00334", "INTEL-SA-00657"]),
+                None,
+                None,
+            ),
+            Err(TcbPolicyError::AdvisoryNotAllowed {
+                status: TcbStatus::SWHardeningNeeded,
+                advisory: "INTEL-SA-00657".to_string()
+            })
+        );
+        assert_eq!(
+            policy.check(TcbStatus::OutOfDate, &advisories(&[]), Some(0), Some(0)),
+            Err(TcbPolicyError::StatusNotAccepted {
+                status: TcbStatus::OutOfDate
+            })
+        );
+    }
+
+    #[test]
+    fn test_out_of_date_grace_period() {
+        let policy = TcbPolicy::new([TcbStatus::UpToDate], [""; 0], Some(100));
+
+        policy
+            .check(
+                TcbStatus::OutOfDate,
+                &advisories(&[]),
+                Some(1000),
+                Some(1100),
+            )
+            .expect("within grace period");
+        assert_eq!(
+            policy.check(
+                TcbStatus::OutOfDate,
+                &advisories(&[]),
+                Some(1000),
+                Some(1101)
+            ),
+            Err(TcbPolicyError::GracePeriodExpired {
+                status: TcbStatus::OutOfDate,
+                out_of_date_since: 1000,
+                grace_period: 100,
+                now: 1101
+            })
+        );
+        assert_eq!(
+            policy.check(TcbStatus::OutOfDate, &advisories(&[]), Some(1000), None),
+            Err(TcbPolicyError::StatusNotAccepted {
+                status: TcbStatus::OutOfDate
+            })
+        );
+        assert_eq!(
+            policy.check(TcbStatus::Revoked, &advisories(&[]), Some(1000), Some(1000)),
+            Err(TcbPolicyError::StatusNotAccepted {
+                status: TcbStatus::Revoked
+            })
+        );
+    }
+
+    #[test]
+    fn test_latest_tcb_date() {
+        // 2023-02-15T00:00:00Z
+        assert_eq!(latest_tcb_date(TCB_INFO_JSON), Some(1676419200));
+        assert_eq!(latest_tcb_date("{}"), None);
+    }
+}
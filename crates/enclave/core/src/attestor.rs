@@ -10,7 +10,7 @@ use quartz_contract_core::{
     msg::{
         execute::attested::{
             Attestation, DcapAttestation, HasUserData, MockAttestation, RawDcapAttestation,
-            RawMockAttestation,
+            RawMockAttestation, UserDataDomain,
         },
         HasDomainType,
     },
@@ -21,9 +21,11 @@ use reqwest::{blocking::Client, Url};
 use serde::{Deserialize, Serialize};
 use serde_json::Error as SerdeError;
 use serde_with::{serde_as, DisplayFromStr};
+use tonic::Status;
 
 use crate::{
     backup_restore::{Export, Import},
+    store::Store,
     types::Fmspc,
 };
 
@@ -38,6 +40,49 @@ const ROOT_CA: &str = include_str!("../data/root_ca.pem");
 const ROOT_CRL: &[u8] = include_bytes!("../data/root_crl.der");
 const TCB_SIGNER: &str = include_str!("../data/tcb_signer.pem");
 
+/// Returns the deployment (i.e. the configured chain and the stored contract, if any) that the
+/// enclave's attested messages are meant for (see [`HasUserData::user_data_for`]).
+pub async fn user_data_domain<S>(store: &S) -> Result<UserDataDomain, Status>
+where
+    S: Store,
+    S::Contract: ToString,
+{
+    let config = store
+        .get_config()
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .ok_or_else(|| Status::not_found("config not found"))?;
+    let contract = store
+        .get_contract()
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+    Ok(UserDataDomain::new(
+        config.light_client_opts().chain_id().clone(),
+        contract.map(|contract| contract.to_string()),
+    ))
+}
+
+/// Same as [`user_data_domain`], but also binds the user data to this replica's operator (if it
+/// was registered as one), as required for app outputs that only take effect once a quorum of
+/// operators submitted them.
+pub async fn output_user_data_domain<S>(store: &S) -> Result<UserDataDomain, Status>
+where
+    S: Store,
+    S::Contract: ToString,
+{
+    let domain = user_data_domain(store).await?;
+    let operator = store
+        .get_operator()
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+    Ok(match operator {
+        Some(operator) => domain.with_operator(operator),
+        None => domain,
+    })
+}
+
 /// The trait defines the interface for generating attestations from within an enclave.
 pub trait Attestor: Send + Sync + 'static {
     type Error: ToString;
@@ -246,4 +291,8 @@ impl HasUserData for NullUserData {
     fn user_data(&self) -> UserData {
         [0u8; 64]
     }
+
+    fn msg_type(&self) -> &'static str {
+        "null"
+    }
 }
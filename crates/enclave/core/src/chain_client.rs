@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 use cosmrs::abci::GasInfo;
 use serde::{de::DeserializeOwned, Serialize};
+use tendermint::{block::Height, Hash};
 
 use crate::chain_client::default::DefaultTxConfig;
 
@@ -42,6 +43,26 @@ pub trait ChainClient: Send + Sync + 'static {
         query: impl Into<Self::Query> + Send,
     ) -> Result<R, Self::Error>;
 
+    /// Sends a query to the specified contract for the state at the specified height and returns a
+    /// deserialized result (e.g. to query the state that a proven request must be consistent with).
+    ///
+    /// # Parameters
+    ///
+    /// - `contract`: A reference to the contract identifier.
+    /// - `query`: A query message convertible into the client's query type.
+    /// - `height`: The height of the state to query.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized query result on success,
+    /// or an error of type `Self::Error` if the query fails.
+    async fn query_contract_at_height<R: DeserializeOwned + Default + Send>(
+        &self,
+        contract: &Self::Contract,
+        query: impl Into<Self::Query> + Send,
+        height: u64,
+    ) -> Result<R, Self::Error>;
+
     /// Retrieves an existence proof for a given storage key in the contract.
     ///
     /// # Parameters
@@ -59,6 +80,45 @@ pub trait ChainClient: Send + Sync + 'static {
         storage_key: &str,
     ) -> Result<Self::Proof, Self::Error>;
 
+    /// Retrieves an existence proof for a given storage key in the contract, for the state at the
+    /// specified height (e.g. the height of the event that triggered the request).
+    ///
+    /// # Parameters
+    ///
+    /// - `contract`: A reference to the contract identifier.
+    /// - `storage_key`: The storage key for which to obtain the existence proof.
+    /// - `height`: The height at which the state must be proven.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the existence proof of type `Self::Proof` on success,
+    /// or an error of type `Self::Error` if the operation fails.
+    async fn existence_proof_at_height(
+        &self,
+        contract: &Self::Contract,
+        storage_key: &str,
+        height: u64,
+    ) -> Result<Self::Proof, Self::Error>;
+
+    /// Sets the trusted height and hash that subsequent existence proofs must be anchored at.
+    ///
+    /// The enclave only accepts light client proofs that start at its current trusted state, so the
+    /// host calls this with the enclave's trusted height and hash before handling every event.
+    /// Clients that don't generate light client proofs can rely on the default (no-op) impl.
+    ///
+    /// # Parameters
+    ///
+    /// - `height`: The enclave's trusted height.
+    /// - `hash`: The enclave's trusted hash.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success (`Ok(())`) or an error of type `Self::Error`
+    /// if the operation fails.
+    async fn set_trusted_anchor(&self, _height: Height, _hash: Hash) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Sends a transaction to the specified contract.
     ///
     /// # Parameters
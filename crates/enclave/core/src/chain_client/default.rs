@@ -3,15 +3,17 @@ use cosmrs::{abci::GasInfo, crypto::secp256k1::SigningKey, AccountId};
 use cw_client::{CwClient, GrpcClient};
 use futures_util::StreamExt;
 use log::{debug, error, info, trace};
+pub use quartz_tm_prover::config::WitnessPolicy;
 use quartz_tm_prover::{
-    config::{Config as TmProverConfig, ProofOutput},
-    prover::prove,
+    config::{Config as TmProverConfig, List, ProofOutput},
+    prover::Prover,
 };
 use reqwest::Url;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{json, Value};
 use tendermint::{block::Height, chain::Id as TmChainId, Hash};
 use tendermint_rpc::{query::EventType, SubscriptionClient, WebSocketClient};
+use tokio::sync::Mutex;
 
 use crate::chain_client::ChainClient;
 
@@ -19,22 +21,32 @@ use crate::chain_client::ChainClient;
 /// This implementation uses -
 ///     - gRPC for sending transactions and running queries
 ///     - websocket for waiting for blocks
-///     - tendermint HTTP RPC for generating light client proofs
+///     - tendermint HTTP RPC for generating light client proofs (cross-checked against the witnesses)
 pub struct DefaultChainClient {
     pub chain_id: TmChainId,
     pub grpc_client: GrpcClient,
     pub node_url: Url,
+    pub witnesses: Vec<Url>,
+    pub min_witnesses: usize,
+    pub witness_policy: WitnessPolicy,
     pub ws_url: Url,
     pub trusted_height: Height,
     pub trusted_hash: Hash,
+    /// The trusted height and hash that proofs are anchored at (see
+    /// [`ChainClient::set_trusted_anchor`]), initially `trusted_height` and `trusted_hash`.
+    trusted_anchor: Mutex<(Height, Hash)>,
+    /// Lazily created on the first proof and reused afterwards.
+    prover: Mutex<Option<Prover>>,
 }
 
 impl DefaultChainClient {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         chain_id: TmChainId,
         signer: SigningKey,
         grpc_url: Url,
         node_url: Url,
+        witnesses: Vec<Url>,
         ws_url: Url,
         trusted_height: Height,
         trusted_hash: Hash,
@@ -44,11 +56,75 @@ impl DefaultChainClient {
             chain_id,
             grpc_client: GrpcClient::new(signer, grpc_url),
             node_url,
+            witnesses,
+            min_witnesses: 1,
+            witness_policy: WitnessPolicy::default(),
             ws_url,
             trusted_height,
             trusted_hash,
+            trusted_anchor: Mutex::new((trusted_height, trusted_hash)),
+            prover: Mutex::new(None),
         }
     }
+
+    /// Sets the minimum number of distinct witnesses that must be reachable when generating proofs
+    /// and what to do if there are fewer.
+    pub fn with_witness_policy(mut self, min_witnesses: usize, policy: WitnessPolicy) -> Self {
+        self.min_witnesses = min_witnesses;
+        self.witness_policy = policy;
+        self
+    }
+
+    async fn generate_proof(
+        &self,
+        contract: &AccountId,
+        storage_key: &str,
+        proof_height: Option<Height>,
+    ) -> Result<ProofOutput, anyhow::Error> {
+        debug!("Generating existence proof for contract {contract} with storage key {storage_key}");
+
+        let (trusted_height, trusted_hash) = *self.trusted_anchor.lock().await;
+        let mut prover = self.prover.lock().await;
+        if prover.is_none() {
+            info!("Creating prover trusting height {trusted_height}");
+            let prover_config = TmProverConfig {
+                primary: self.node_url.as_str().parse()?,
+                witnesses: List(
+                    self.witnesses
+                        .iter()
+                        .map(|w| w.as_str().parse())
+                        .collect::<Result<_, _>>()?,
+                ),
+                min_witnesses: self.min_witnesses,
+                witness_policy: self.witness_policy,
+                trusted_height,
+                trusted_hash,
+                verbose: "1".parse()?,
+                chain_id: self.chain_id.to_string(),
+                ..Default::default()
+            };
+            *prover = Some(Prover::new(&prover_config).await.map_err(|report| {
+                error!("Failed to create tendermint prover: {}", report);
+                anyhow!("Failed to create tendermint prover. Report: {}", report)
+            })?);
+        }
+
+        let prover = prover.as_mut().expect("prover initialized above");
+        prover
+            .prove(
+                trusted_height,
+                trusted_hash,
+                contract.clone(),
+                storage_key.to_string(),
+                None,
+                proof_height,
+            )
+            .await
+            .map_err(|report| {
+                error!("Tendermint prover failed: {}", report);
+                anyhow!("Tendermint prover failed. Report: {}", report)
+            })
+    }
 }
 
 pub enum Query {
@@ -94,38 +170,51 @@ impl ChainClient for DefaultChainClient {
         }
     }
 
+    async fn query_contract_at_height<R: DeserializeOwned + Default + Send>(
+        &self,
+        contract: &Self::Contract,
+        query: impl Into<Self::Query> + Send,
+        height: u64,
+    ) -> Result<R, Self::Error> {
+        debug!("Querying contract: {contract} at height {height}");
+        match query.into() {
+            Query::Json(q) => {
+                trace!("Executing JSON query");
+                self.grpc_client
+                    .query_smart_at_height(contract, q, height)
+                    .await
+            }
+            Query::String(q) => {
+                trace!("Executing raw query");
+                self.grpc_client
+                    .query_raw_at_height(contract, q, height)
+                    .await
+            }
+        }
+    }
+
     async fn existence_proof(
         &self,
         contract: &Self::Contract,
         storage_key: &str,
     ) -> Result<Self::Proof, Self::Error> {
-        debug!("Generating existence proof for contract {contract} with storage key {storage_key}");
+        self.generate_proof(contract, storage_key, None).await
+    }
 
-        let prover_config = TmProverConfig {
-            primary: self.node_url.as_str().parse()?,
-            witnesses: self.node_url.as_str().parse()?,
-            trusted_height: self.trusted_height,
-            trusted_hash: self.trusted_hash,
-            verbose: "1".parse()?,
-            contract_address: contract.clone(),
-            storage_key: storage_key.to_string(),
-            chain_id: self.chain_id.to_string(),
-            ..Default::default()
-        };
-
-        let proof_output = tokio::task::spawn_blocking(move || {
-            trace!("Spawning blocking task for proof generation");
-            // Create a new runtime inside the blocking thread.
-            let rt = tokio::runtime::Runtime::new()?;
-            rt.block_on(async {
-                prove(prover_config).await.map_err(|report| {
-                    error!("Tendermint prover failed: {}", report);
-                    anyhow!("Tendermint prover failed. Report: {}", report)
-                })
-            })
-        })
-        .await??; // Handle both JoinError and your custom error
-        Ok(proof_output)
+    async fn existence_proof_at_height(
+        &self,
+        contract: &Self::Contract,
+        storage_key: &str,
+        height: u64,
+    ) -> Result<Self::Proof, Self::Error> {
+        self.generate_proof(contract, storage_key, Some(height.try_into()?))
+            .await
+    }
+
+    async fn set_trusted_anchor(&self, height: Height, hash: Hash) -> Result<(), Self::Error> {
+        trace!("Anchoring proofs at trusted height {height}");
+        *self.trusted_anchor.lock().await = (height, hash);
+        Ok(())
     }
 
     async fn send_tx<M: Serialize>(
@@ -0,0 +1,85 @@
+//! Opening of [`EncryptedEnvelope`]s, i.e. user requests that are encrypted to the enclave's pub
+//! key (see `seal_envelope` in `quartz-contract-core`, with the `client` feature).
+//!
+//! The sealed payload is the plaintext along with the envelope's AAD, so that the enclave can make
+//! sure that the (cleartext) AAD that the contract checked is the one that the user sealed.
+
+use quartz_contract_core::msg::execute::envelope::{
+    EncryptedEnvelope, EnvelopeScheme, SealedPayload,
+};
+use serde::de::DeserializeOwned;
+use tonic::Status;
+
+use crate::key_manager::Decrypt;
+
+/// Decrypts the envelope using the enclave's key-manager and returns the plaintext, after making
+/// sure that the envelope is meant for `contract` and that its AAD wasn't tampered with.
+pub async fn open_envelope<K, T>(
+    key_manager: &K,
+    envelope: &EncryptedEnvelope<T>,
+    contract: &str,
+) -> Result<T, Status>
+where
+    K: Decrypt,
+    T: DeserializeOwned,
+{
+    if envelope.aad().contract != contract {
+        return Err(Status::invalid_argument(
+            "envelope is meant for another contract",
+        ));
+    }
+
+    let plaintext = match envelope.scheme() {
+        EnvelopeScheme::EciesSecp256k1 => {
+            let ciphertext = [envelope.ephemeral_key(), envelope.ciphertext()].concat();
+            key_manager
+                .decrypt(&ciphertext)
+                .await
+                .map_err(|e| Status::invalid_argument(e.to_string()))?
+        }
+    };
+
+    let sealed: SealedPayload<T> = serde_json::from_slice(&plaintext)
+        .map_err(|e| Status::invalid_argument(format!("invalid envelope payload: {e}")))?;
+    if &sealed.aad != envelope.aad() {
+        return Err(Status::invalid_argument("envelope AAD mismatch"));
+    }
+
+    Ok(sealed.msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use quartz_contract_core::msg::execute::envelope::{seal_envelope, EnvelopeAad};
+
+    use super::*;
+    use crate::key_manager::{default::DefaultKeyManager, KeyManager};
+
+    #[tokio::test]
+    async fn test_sealed_envelope_opens_only_with_its_aad() {
+        let key_manager = DefaultKeyManager::default();
+        let pk: Vec<u8> = key_manager.pub_key().await.into();
+        let aad = EnvelopeAad::new("contract", 7);
+
+        let envelope =
+            seal_envelope(&pk, aad.clone(), "hello".to_string()).expect("valid enclave pk");
+        let msg = open_envelope(&key_manager, &envelope, "contract")
+            .await
+            .expect("sealed to the enclave");
+        assert_eq!(msg, "hello");
+
+        assert!(open_envelope(&key_manager, &envelope, "other-contract")
+            .await
+            .is_err());
+
+        let tampered: EncryptedEnvelope<String> = EncryptedEnvelope::new(
+            envelope.scheme().clone(),
+            envelope.ephemeral_key().to_vec(),
+            envelope.ciphertext().to_vec(),
+            EnvelopeAad::new("contract", 8),
+        );
+        assert!(open_envelope(&key_manager, &tampered, "contract")
+            .await
+            .is_err());
+    }
+}
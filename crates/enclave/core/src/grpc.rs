@@ -1,15 +1,24 @@
 //! gRPC service implementation for core enclave requests (handshake)
 use cosmrs::AccountId;
 use quartz_proto::quartz::{
-    core_server::Core, InstantiateRequest, InstantiateResponse, SessionCreateRequest,
+    core_server::Core, HandoverExportRequest, HandoverExportResponse, HandoverImportRequest,
+    HandoverImportResponse, HandoverOfferRequest, HandoverOfferResponse, InstantiateRequest,
+    InstantiateResponse, MisbehaviourRequest, MisbehaviourResponse, ReanchorRequest,
+    ReanchorResponse, RegisterOperatorRequest, RegisterOperatorResponse, SessionCreateRequest,
     SessionCreateResponse, SessionSetPubKeyRequest, SessionSetPubKeyResponse,
 };
+use serde::Deserialize;
 use tendermint::{block::Height, Hash};
-use tonic::{Request, Response, Status};
+use tonic::{Code, Request, Response, Status};
 
 use crate::{
-    attestor::Attestor, handler::Handler, key_manager::KeyManager, store::Store, DefaultEnclave,
-    Notification,
+    attestor::Attestor,
+    backup_restore::{Export, Import},
+    handler::Handler,
+    handover::VerifiableAttestation,
+    key_manager::KeyManager,
+    store::Store,
+    DefaultEnclave, Notification,
 };
 
 #[async_trait::async_trait]
@@ -28,13 +37,31 @@ where
     }
 }
 
+impl<C, A, K, S> DefaultEnclave<C, A, K, S> {
+    /// Notifies the host if the request was refused because it revealed a light client attack (see
+    /// [`record_verified_headers`](crate::misbehaviour::record_verified_headers)), so that the
+    /// evidence gets backed up right away.
+    async fn notify_misbehaviour<T>(&self, result: Result<T, Status>) -> Result<T, Status> {
+        if matches!(&result, Err(status) if status.code() == Code::Aborted) {
+            self.notifier_tx
+                .send(Notification::MisbehaviourDetected)
+                .await
+                .expect("Receiver half of the channel must NOT be closed");
+        }
+
+        result
+    }
+}
+
 #[async_trait::async_trait]
 impl<C, A, K, S> Core for DefaultEnclave<C, A, K, S>
 where
     C: Send + Sync + 'static,
     A: Attestor + Clone,
-    K: KeyManager + Clone,
-    S: Store<Contract = AccountId, Height = Height, Hash = Hash> + Clone,
+    A::Attestation: VerifiableAttestation,
+    A::RawAttestation: for<'de> Deserialize<'de>,
+    K: KeyManager + Clone + Export + Import,
+    S: Store<Contract = AccountId, Height = Height, Hash = Hash> + Clone + Export + Import,
 {
     async fn instantiate(
         &self,
@@ -54,8 +81,66 @@ where
         &self,
         request: Request<SessionSetPubKeyRequest>,
     ) -> Result<Response<SessionSetPubKeyResponse>, Status> {
+        let response = self.notify_misbehaviour(request.handle(self).await).await?;
+
+        self.notifier_tx
+            .send(Notification::HandshakeComplete)
+            .await
+            .expect("Receiver half of the channel must NOT be closed");
+
+        Ok(response)
+    }
+
+    async fn reanchor(
+        &self,
+        request: Request<ReanchorRequest>,
+    ) -> Result<Response<ReanchorResponse>, Status> {
+        let response = self.notify_misbehaviour(request.handle(self).await).await?;
+
+        // persist the adopted anchor, otherwise a restart would go back to the stale one
+        self.notifier_tx
+            .send(Notification::StateUpdated)
+            .await
+            .expect("Receiver half of the channel must NOT be closed");
+
+        Ok(response)
+    }
+
+    async fn register_operator(
+        &self,
+        request: Request<RegisterOperatorRequest>,
+    ) -> Result<Response<RegisterOperatorResponse>, Status> {
+        request.handle(self).await
+    }
+
+    async fn misbehaviour(
+        &self,
+        request: Request<MisbehaviourRequest>,
+    ) -> Result<Response<MisbehaviourResponse>, Status> {
+        request.handle(self).await
+    }
+
+    async fn handover_offer(
+        &self,
+        request: Request<HandoverOfferRequest>,
+    ) -> Result<Response<HandoverOfferResponse>, Status> {
+        request.handle(self).await
+    }
+
+    async fn handover_export(
+        &self,
+        request: Request<HandoverExportRequest>,
+    ) -> Result<Response<HandoverExportResponse>, Status> {
+        self.notify_misbehaviour(request.handle(self).await).await
+    }
+
+    async fn handover_import(
+        &self,
+        request: Request<HandoverImportRequest>,
+    ) -> Result<Response<HandoverImportResponse>, Status> {
         let response = request.handle(self).await?;
 
+        // the imported session is already established on-chain
         self.notifier_tx
             .send(Notification::HandshakeComplete)
             .await
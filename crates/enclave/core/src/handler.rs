@@ -2,18 +2,33 @@ use cosmrs::AccountId;
 use k256::ecdsa::VerifyingKey;
 use log::{debug, error};
 use quartz_proto::quartz::{
-    InstantiateRequest, InstantiateResponse, SessionCreateRequest, SessionCreateResponse,
+    HandoverExportRequest, HandoverExportResponse, HandoverImportRequest, HandoverImportResponse,
+    HandoverOfferRequest, HandoverOfferResponse, InstantiateRequest, InstantiateResponse,
+    MisbehaviourRequest, MisbehaviourResponse, ReanchorRequest, ReanchorResponse,
+    RegisterOperatorRequest, RegisterOperatorResponse, SessionCreateRequest, SessionCreateResponse,
     SessionSetPubKeyRequest, SessionSetPubKeyResponse,
 };
+use serde::Deserialize;
 use tendermint::{block::Height, Hash};
 use tonic::Status;
 
-use crate::{attestor::Attestor, key_manager::KeyManager, store::Store, Enclave};
+use crate::{
+    attestor::Attestor,
+    backup_restore::{Export, Import},
+    handover::VerifiableAttestation,
+    key_manager::KeyManager,
+    store::Store,
+    Enclave,
+};
 
 pub type A<E> = <<E as Enclave>::Attestor as Attestor>::Attestation;
 pub type RA<E> = <<E as Enclave>::Attestor as Attestor>::RawAttestation;
 
+pub mod handover;
 pub mod instantiate;
+pub mod misbehaviour;
+pub mod reanchor;
+pub mod register_operator;
 pub mod session_create;
 pub mod session_set_pubkey;
 
@@ -48,6 +63,12 @@ pub enum CoreEnclaveRequest {
     Instantiate(InstantiateRequest),
     SessionCreate(SessionCreateRequest),
     SessionSetPubKey(SessionSetPubKeyRequest),
+    Reanchor(ReanchorRequest),
+    RegisterOperator(RegisterOperatorRequest),
+    Misbehaviour(MisbehaviourRequest),
+    HandoverOffer(HandoverOfferRequest),
+    HandoverExport(HandoverExportRequest),
+    HandoverImport(HandoverImportRequest),
 }
 
 /// Core enclave responses
@@ -56,14 +77,22 @@ pub enum CoreEnclaveResponse {
     Instantiate(InstantiateResponse),
     SessionCreate(SessionCreateResponse),
     SessionSetPubKey(SessionSetPubKeyResponse),
+    Reanchor(ReanchorResponse),
+    RegisterOperator(RegisterOperatorResponse),
+    Misbehaviour(MisbehaviourResponse),
+    HandoverOffer(HandoverOfferResponse),
+    HandoverExport(HandoverExportResponse),
+    HandoverImport(HandoverImportResponse),
 }
 
 #[async_trait::async_trait]
 impl<E: Enclave> Handler<E> for CoreEnclaveRequest
 where
     E: Enclave,
-    E::KeyManager: KeyManager<PubKey = VerifyingKey>,
-    E::Store: Store<Contract = AccountId, Height = Height, Hash = Hash>,
+    E::KeyManager: KeyManager<PubKey = VerifyingKey> + Export + Import,
+    E::Store: Store<Contract = AccountId, Height = Height, Hash = Hash> + Clone + Export + Import,
+    A<E>: VerifiableAttestation,
+    RA<E>: for<'de> Deserialize<'de>,
 {
     type Error = Status;
     type Response = CoreEnclaveResponse;
@@ -86,10 +115,43 @@ where
                     .await
                     .map(CoreEnclaveResponse::SessionSetPubKey)
             }
+            CoreEnclaveRequest::Reanchor(req) => {
+                debug!("Handling reanchor request");
+                req.handle(ctx).await.map(CoreEnclaveResponse::Reanchor)
+            }
+            CoreEnclaveRequest::RegisterOperator(req) => {
+                debug!("Handling register operator request");
+                req.handle(ctx)
+                    .await
+                    .map(CoreEnclaveResponse::RegisterOperator)
+            }
+            CoreEnclaveRequest::Misbehaviour(req) => {
+                debug!("Handling misbehaviour request");
+                req.handle(ctx).await.map(CoreEnclaveResponse::Misbehaviour)
+            }
+            CoreEnclaveRequest::HandoverOffer(req) => {
+                debug!("Handling hand-over offer request");
+                req.handle(ctx)
+                    .await
+                    .map(CoreEnclaveResponse::HandoverOffer)
+            }
+            CoreEnclaveRequest::HandoverExport(req) => {
+                debug!("Handling hand-over export request");
+                req.handle(ctx)
+                    .await
+                    .map(CoreEnclaveResponse::HandoverExport)
+            }
+            CoreEnclaveRequest::HandoverImport(req) => {
+                debug!("Handling hand-over import request");
+                req.handle(ctx)
+                    .await
+                    .map(CoreEnclaveResponse::HandoverImport)
+            }
         }
     }
 }
 
+#[allow(clippy::result_large_err)]
 pub fn ensure_seq_num_consistency(
     seq_num_in_store: u64,
     seq_num_on_chain: u64,
@@ -0,0 +1,502 @@
+use cosmrs::AccountId;
+use k256::ecdsa::SigningKey;
+use log::{debug, error, info};
+use quartz_contract_core::{
+    msg::{
+        execute::attested::{HasUserData, UserDataDomain},
+        HasDomainType,
+    },
+    state::{Config, RawConfig, CONFIG_KEY},
+};
+use quartz_proto::quartz::{
+    HandoverExportRequest as RawHandoverExportRequest,
+    HandoverExportResponse as RawHandoverExportResponse,
+    HandoverImportRequest as RawHandoverImportRequest,
+    HandoverImportResponse as RawHandoverImportResponse,
+    HandoverOfferRequest as RawHandoverOfferRequest,
+    HandoverOfferResponse as RawHandoverOfferResponse,
+};
+use tendermint::{block::Height, Hash};
+use tonic::Status;
+
+use crate::{
+    attestor::{user_data_domain, Attestor},
+    backup_restore::{Export, Import},
+    handler::{Handler, A, RA},
+    handover::{
+        AttestedHandoverOffer, AttestedHandoverPayload, HandoverExport, HandoverExportRequest,
+        HandoverOffer, HandoverPayload, PendingHandover, VerifiableAttestation, PENDING_HANDOVER,
+    },
+    misbehaviour::{ensure_not_halted, record_verified_headers},
+    proven_request::PROVEN_REQUEST_LOCK,
+    store::Store,
+    Enclave,
+};
+
+/// Handled by the new enclave - generates an ephemeral key and attests to its public key (for the
+/// contract whose secrets are handed over).
+#[async_trait::async_trait]
+impl<E: Enclave> Handler<E> for RawHandoverOfferRequest {
+    type Error = Status;
+    type Response = RawHandoverOfferResponse;
+
+    async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        // the new enclave doesn't know the contract before the import
+        let contract: AccountId = serde_json::from_str(&self.message)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let config = ctx
+            .store()
+            .await
+            .get_config()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("config not found"))?;
+        let domain = UserDataDomain::new(
+            config.light_client_opts().chain_id().clone(),
+            Some(contract.to_string()),
+        );
+
+        let attestor = ctx.attestor().await;
+        let mut pending_handover = PENDING_HANDOVER.lock().await;
+
+        let key = SigningKey::random(&mut rand::thread_rng());
+        let offer = HandoverOffer::new(key.verifying_key().to_sec1_bytes().into());
+        let attestation = attestor
+            .attestation(offer.user_data_for(&domain))
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        // replaces any previous (unused) offer
+        *pending_handover = Some(PendingHandover { key, domain });
+
+        let attested_offer = AttestedHandoverOffer {
+            offer,
+            attestation: RA::<E>::from(attestation),
+        };
+        Ok(RawHandoverOfferResponse {
+            message: serde_json::to_string(&attested_offer).expect("infallible serializer"),
+        })
+    }
+}
+
+/// Handled by the old enclave - exports its secrets to a new enclave with an identity that was
+/// approved on-chain.
+#[async_trait::async_trait]
+impl<E> Handler<E> for RawHandoverExportRequest
+where
+    E: Enclave,
+    E::KeyManager: Export,
+    E::Store: Store<Contract = AccountId, Height = Height, Hash = Hash> + Export,
+    A<E>: VerifiableAttestation,
+    RA<E>: for<'de> serde::Deserialize<'de>,
+{
+    type Error = Status;
+    type Response = RawHandoverExportResponse;
+
+    async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        // the raw attestation isn't necessarily `Send`, so don't hold on to it
+        let (offer, attestation, config_proof) = {
+            let request: HandoverExportRequest<RA<E>> = serde_json::from_str(&self.message)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            let HandoverExportRequest {
+                offer: AttestedHandoverOffer { offer, attestation },
+                config_proof,
+            } = request;
+            let attestation: A<E> = into_domain_type(attestation)?;
+            (offer, attestation, config_proof)
+        };
+
+        let _guard = PROVEN_REQUEST_LOCK.lock().await;
+        let store = ctx.store().await;
+        ensure_not_halted(store).await?;
+
+        let contract = store
+            .get_contract()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("contract not found"))?;
+        let config = store
+            .get_config()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("config not found"))?;
+        let (trusted_height, trusted_hash) = store
+            .get_trusted_height_hash()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let (target_height, target_hash) = config_proof.target_height_hash();
+
+        debug!("Verifying proof of publication for contract config");
+        let (proof_value, _msg, verified_headers) = config_proof
+            .verify_with_headers(
+                config.light_client_opts(),
+                trusted_height,
+                trusted_hash,
+                contract,
+                CONFIG_KEY.to_string(),
+                None,
+            )
+            .map_err(Status::failed_precondition)?;
+        record_verified_headers(store, verified_headers).await?;
+
+        // the new enclave must have an identity that was approved on-chain (for this deployment)
+        let proven_config: RawConfig = serde_json::from_slice(&proof_value)
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+        let approved_config = Config::try_from(proven_config.clone())
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+        let domain = user_data_domain(store).await?;
+        if attestation.user_data() != offer.user_data_for(&domain) {
+            error!("Hand-over offer doesn't match its attestation");
+            return Err(Status::permission_denied("user data mismatch"));
+        }
+        attestation.verify(&approved_config).map_err(|e| {
+            error!("Failed to verify the new enclave's attestation: {e}");
+            Status::permission_denied(e)
+        })?;
+
+        // update trusted height and hash
+        store
+            .set_trusted_height_hash(target_height, target_hash)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        let payload = HandoverPayload {
+            config: proven_config,
+            key_manager: ctx
+                .key_manager()
+                .await
+                .export()
+                .await
+                .map_err(|e| Status::internal(format!("key-manager export failed: {e:?}")))?,
+            store: store
+                .export()
+                .await
+                .map_err(|e| Status::internal(format!("store export failed: {e:?}")))?,
+        };
+        let ciphertext = payload
+            .encrypt(offer.pub_key())
+            .map_err(Status::invalid_argument)?;
+        let attestation = ctx
+            .attestor()
+            .await
+            .attestation(HandoverExport::new(&offer, &ciphertext).user_data_for(&domain))
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        let attested_payload = AttestedHandoverPayload {
+            ciphertext,
+            attestation: RA::<E>::from(attestation),
+        };
+
+        info!("Exported enclave secrets to successor enclave");
+        Ok(RawHandoverExportResponse {
+            message: serde_json::to_string(&attested_payload).expect("infallible serializer"),
+        })
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn into_domain_type<R: HasDomainType>(raw: R) -> Result<R::DomainType, Status> {
+    R::DomainType::try_from(raw).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+/// Handled by the new enclave - imports the secrets exported by the old enclave.
+///
+/// Nothing is imported unless the export is attested by an enclave that the new enclave's own
+/// config trusts (e.g. via [`Config::with_identities`]) and is bound to the in-progress offer.
+///
+/// The key-manager and store returned by the enclave must be shared handles (e.g.
+/// [`SharedKeyManager`](crate::key_manager::shared::SharedKeyManager) and
+/// [`DefaultStore`](crate::store::default::DefaultStore)), otherwise the import is lost.
+#[async_trait::async_trait]
+impl<E> Handler<E> for RawHandoverImportRequest
+where
+    E: Enclave,
+    E::KeyManager: Import,
+    E::Store: Clone + Import,
+    A<E>: VerifiableAttestation,
+    RA<E>: for<'de> serde::Deserialize<'de>,
+{
+    type Error = Status;
+    type Response = RawHandoverImportResponse;
+
+    async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        // the raw attestation isn't necessarily `Send`, so don't hold on to it
+        let (ciphertext, attestation) = {
+            let attested_payload: AttestedHandoverPayload<RA<E>> =
+                serde_json::from_str(&self.message)
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            let attestation: A<E> = into_domain_type(attested_payload.attestation)?;
+            (attested_payload.ciphertext, attestation)
+        };
+
+        let trusted_config = ctx
+            .store()
+            .await
+            .get_config()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("config not found"))?;
+
+        let mut pending_handover = PENDING_HANDOVER.lock().await;
+        let PendingHandover { key, domain } = pending_handover
+            .as_ref()
+            .ok_or_else(|| Status::failed_precondition("no hand-over in progress"))?;
+
+        // the export must be attested by a trusted enclave for the in-progress offer
+        let offer = HandoverOffer::new(key.verifying_key().to_sec1_bytes().into());
+        let export = HandoverExport::new(&offer, &ciphertext);
+        if attestation.user_data() != export.user_data_for(domain) {
+            error!("Hand-over export doesn't match its attestation");
+            return Err(Status::permission_denied("user data mismatch"));
+        }
+        attestation.verify(&trusted_config).map_err(|e| {
+            error!("Failed to verify the old enclave's attestation: {e}");
+            Status::permission_denied(e)
+        })?;
+
+        let payload =
+            HandoverPayload::decrypt(key, &ciphertext).map_err(Status::invalid_argument)?;
+        let config = Config::try_from(payload.config)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        // the offer can only be used once
+        pending_handover.take();
+
+        let mut key_manager = ctx.key_manager().await;
+        key_manager
+            .import(payload.key_manager)
+            .await
+            .map_err(|e| Status::internal(format!("key-manager import failed: {e:?}")))?;
+        let mut store = ctx.store().await.clone();
+        store
+            .import(payload.store)
+            .await
+            .map_err(|e| Status::internal(format!("store import failed: {e:?}")))?;
+        store
+            .set_config(config)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        info!("Imported enclave secrets from predecessor enclave");
+        Ok(RawHandoverImportResponse {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quartz_contract_core::{
+        msg::execute::attested::{MockAttestation, RawMockAttestation},
+        state::UserData,
+    };
+    use tonic::Code;
+
+    use super::*;
+    use crate::{
+        key_manager::default::DefaultKeyManager,
+        proof_of_publication::ProofOfPublication,
+        testing::{
+            contract, enclave, light_blocks, light_client_opts, merkle_proof, TestEnclave, CHAIN_ID,
+        },
+    };
+
+    async fn attested_offer(
+        enclave: &TestEnclave,
+        contract: &AccountId,
+    ) -> AttestedHandoverOffer<RawMockAttestation> {
+        let response = RawHandoverOfferRequest {
+            message: serde_json::to_string(contract).expect("infallible serializer"),
+        }
+        .handle(enclave)
+        .await
+        .expect("offer");
+        serde_json::from_str(&response.message).expect("valid offer")
+    }
+
+    /// A request to export to the given offer, proving that the contract's config is `config` with
+    /// a chain of light blocks whose last block commits to `app_hash` (or to the published config
+    /// if `None`).
+    fn export_request(
+        offer: AttestedHandoverOffer<RawMockAttestation>,
+        config: Config,
+        app_hash: Option<Vec<u8>>,
+    ) -> RawHandoverExportRequest {
+        let value = serde_json::to_vec(&RawConfig::from(config)).expect("infallible serializer");
+        let (merkle_proof, config_app_hash) = merkle_proof(contract(), CONFIG_KEY, &value);
+        let light_blocks = light_blocks(&[vec![0; 32], app_hash.unwrap_or(config_app_hash)]);
+        let request = HandoverExportRequest {
+            offer,
+            config_proof: ProofOfPublication::new(light_blocks, merkle_proof, None),
+        };
+        RawHandoverExportRequest {
+            message: serde_json::to_string(&request).expect("infallible serializer"),
+        }
+    }
+
+    async fn trusted_height_hash(enclave: &TestEnclave) -> (Height, Hash) {
+        enclave
+            .store()
+            .await
+            .get_trusted_height_hash()
+            .await
+            .expect("infallible store")
+    }
+
+    fn import_request(ciphertext: Vec<u8>, user_data: UserData) -> RawHandoverImportRequest {
+        let attested_payload = AttestedHandoverPayload {
+            ciphertext,
+            attestation: RawMockAttestation::from(MockAttestation(user_data)),
+        };
+        RawHandoverImportRequest {
+            message: serde_json::to_string(&attested_payload).expect("infallible serializer"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_requires_an_approved_successor() {
+        let light_blocks = light_blocks(&[vec![0; 32]]);
+        let config =
+            |mr_enclave| Config::new(mr_enclave, light_client_opts(&light_blocks[0]), None, None);
+
+        let anchor = (
+            light_blocks[0].height(),
+            light_blocks[0].signed_header.header.hash(),
+        );
+
+        // the mock enclave's MRENCLAVE isn't approved on-chain
+        let old_enclave = enclave(&light_blocks[0]).await;
+        let offer = attested_offer(&old_enclave, &contract()).await;
+        let err = export_request(offer, config([1; 32]), None)
+            .handle(&old_enclave)
+            .await
+            .expect_err("untrusted successor");
+        assert_eq!(err.code(), Code::PermissionDenied);
+        // the proof doesn't advance the trusted height unless the successor is approved
+        assert_eq!(trusted_height_hash(&old_enclave).await, anchor);
+
+        let old_enclave = enclave(&light_blocks[0]).await;
+        let offer = attested_offer(&old_enclave, &contract()).await;
+        export_request(offer, config([0; 32]), None)
+            .handle(&old_enclave)
+            .await
+            .expect("approved successor");
+        assert_ne!(trusted_height_hash(&old_enclave).await, anchor);
+    }
+
+    #[tokio::test]
+    async fn test_export_requires_an_offer_for_the_same_contract() {
+        let light_blocks = light_blocks(&[vec![0; 32]]);
+        let old_enclave = enclave(&light_blocks[0]).await;
+        let config = Config::new([0; 32], light_client_opts(&light_blocks[0]), None, None);
+
+        // an offer (of an approved enclave) for another deployment can't be replayed
+        let other_contract = AccountId::new("wasm", &[2; 20]).expect("valid account id");
+        let offer = attested_offer(&old_enclave, &other_contract).await;
+        let err = export_request(offer, config, None)
+            .handle(&old_enclave)
+            .await
+            .expect_err("offer for another contract");
+        assert_eq!(err.code(), Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_export_requires_a_valid_config_proof() {
+        let light_blocks = light_blocks(&[vec![0; 32]]);
+        let enclave = enclave(&light_blocks[0]).await;
+        let config = Config::new([0; 32], light_client_opts(&light_blocks[0]), None, None);
+
+        // the config that approves the successor was never published
+        let offer = attested_offer(&enclave, &contract()).await;
+        let err = export_request(offer, config, Some(vec![1; 32]))
+            .handle(&enclave)
+            .await
+            .expect_err("invalid proof");
+        assert_eq!(err.code(), Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn test_import_requires_attested_export() {
+        let light_blocks = light_blocks(&[vec![0; 32]]);
+        let enclave = enclave(&light_blocks[0]).await;
+        let store = enclave.store().await;
+        let local_config = store
+            .get_config()
+            .await
+            .expect("infallible store")
+            .expect("config");
+
+        let offer = attested_offer(&enclave, &contract()).await.offer;
+
+        let exported_config = Config::new(
+            [0; 32],
+            light_client_opts(&light_blocks[0]),
+            Some("tcbinfo".to_string()),
+            None,
+        );
+        let payload = HandoverPayload {
+            config: exported_config.into(),
+            key_manager: DefaultKeyManager::default()
+                .export()
+                .await
+                .expect("infallible export"),
+            store: store.export().await.expect("infallible export"),
+        };
+        let ciphertext = payload.encrypt(offer.pub_key()).expect("valid pub key");
+        let domain = UserDataDomain::new(CHAIN_ID, Some(contract().to_string()));
+        let user_data = HandoverExport::new(&offer, &ciphertext).user_data_for(&domain);
+
+        // anyone can encrypt to the offer's public key, but the payload isn't attested
+        let err = import_request(ciphertext.clone(), [0; 64])
+            .handle(&enclave)
+            .await
+            .expect_err("unattested export");
+        assert_eq!(err.code(), Code::PermissionDenied);
+
+        // an attestation of another export can't be replayed
+        let other_ciphertext = payload.encrypt(offer.pub_key()).expect("valid pub key");
+        let err = import_request(other_ciphertext, user_data)
+            .handle(&enclave)
+            .await
+            .expect_err("attestation of another export");
+        assert_eq!(err.code(), Code::PermissionDenied);
+
+        // the export must be attested by an enclave that the new enclave trusts
+        store
+            .set_config(local_config.clone().with_mr_enclave([1; 32]))
+            .await
+            .expect("infallible store");
+        let err = import_request(ciphertext.clone(), user_data)
+            .handle(&enclave)
+            .await
+            .expect_err("untrusted enclave");
+        assert_eq!(err.code(), Code::PermissionDenied);
+        store
+            .set_config(local_config)
+            .await
+            .expect("infallible store");
+
+        // nothing was imported
+        let config = store
+            .get_config()
+            .await
+            .expect("infallible store")
+            .expect("config");
+        assert_eq!(config.tcbinfo_contract(), None);
+
+        import_request(ciphertext.clone(), user_data)
+            .handle(&enclave)
+            .await
+            .expect("attested export");
+        let config = store
+            .get_config()
+            .await
+            .expect("infallible store")
+            .expect("config");
+        assert_eq!(config.tcbinfo_contract(), Some("tcbinfo"));
+
+        // the offer is consumed by the import
+        let err = import_request(ciphertext, user_data)
+            .handle(&enclave)
+            .await
+            .expect_err("offer already used");
+        assert_eq!(err.code(), Code::FailedPrecondition);
+    }
+}
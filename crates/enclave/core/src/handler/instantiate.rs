@@ -1,4 +1,7 @@
-use quartz_contract_core::msg::{execute::attested::Attested, instantiate::CoreInstantiate};
+use quartz_contract_core::msg::{
+    execute::attested::{Attested, HasUserData, UserDataDomain},
+    instantiate::CoreInstantiate,
+};
 use quartz_proto::quartz::{
     InstantiateRequest as RawInstantiateRequest, InstantiateResponse as RawInstantiateResponse,
 };
@@ -7,6 +10,7 @@ use tonic::Status;
 use crate::{
     attestor::Attestor,
     handler::{Handler, A, RA},
+    misbehaviour::ensure_not_halted,
     store::Store,
     types::InstantiateResponse,
     Enclave,
@@ -18,6 +22,8 @@ impl<E: Enclave> Handler<E> for RawInstantiateRequest {
     type Response = RawInstantiateResponse;
 
     async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        ensure_not_halted(ctx.store().await).await?;
+
         // create `CoreInstantiate` msg and attest to it
         let config = ctx
             .store()
@@ -26,11 +32,13 @@ impl<E: Enclave> Handler<E> for RawInstantiateRequest {
             .await
             .map_err(|e| Status::internal(e.to_string()))?
             .ok_or_else(|| Status::not_found("config not found"))?;
+        // the contract doesn't exist yet, so the attestation is only bound to the chain
+        let domain = UserDataDomain::new(config.light_client_opts().chain_id().clone(), None);
         let msg = CoreInstantiate::new(config);
         let attestation = ctx
             .attestor()
             .await
-            .attestation(msg.clone())
+            .attestation(msg.user_data_for(&domain))
             .map_err(|e| Status::internal(e.to_string()))?;
         let attested_msg = Attested::new(msg, attestation);
 
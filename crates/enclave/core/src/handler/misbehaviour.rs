@@ -0,0 +1,26 @@
+use quartz_proto::quartz::{
+    MisbehaviourRequest as RawMisbehaviourRequest, MisbehaviourResponse as RawMisbehaviourResponse,
+};
+use tonic::Status;
+
+use crate::{handler::Handler, store::Store, Enclave};
+
+/// Returns the evidence of a detected light client attack as JSON (or `null` if there is none).
+#[async_trait::async_trait]
+impl<E: Enclave> Handler<E> for RawMisbehaviourRequest {
+    type Error = Status;
+    type Response = RawMisbehaviourResponse;
+
+    async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        let evidence = ctx
+            .store()
+            .await
+            .get_misbehaviour_evidence()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(RawMisbehaviourResponse {
+            message: serde_json::to_string(&evidence).expect("infallible serializer"),
+        })
+    }
+}
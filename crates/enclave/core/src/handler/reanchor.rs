@@ -0,0 +1,519 @@
+use cosmrs::AccountId;
+use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use log::{debug, error, info};
+use quartz_contract_core::{
+    msg::execute::{attested::UserDataDomain, set_trusted_anchor::anchor_sign_bytes},
+    state::{Config, TrustedAnchor, TRUSTED_ANCHOR_KEY},
+};
+use quartz_proto::quartz::{
+    ReanchorRequest as RawReanchorRequest, ReanchorResponse as RawReanchorResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{hex::Hex, serde_as};
+use tendermint::{block::Height, Hash};
+use tonic::Status;
+
+use crate::{
+    handler::Handler, misbehaviour::record_verified_headers,
+    proof_of_publication::ProofOfPublication, proven_request::PROVEN_REQUEST_LOCK, store::Store,
+    Enclave,
+};
+
+/// The proof of publication of a governance approved trusted anchor (see the contract's
+/// `SetTrustedAnchor` msg), i.e. the message of a [`RawReanchorRequest`].
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReanchorRequest {
+    #[serde(flatten)]
+    proof: ProofOfPublication<TrustedAnchor>,
+    /// The config's anchor signer's signature of the anchor (see `anchor_sign_bytes`) - only
+    /// required for re-anchoring an enclave whose trusted state is past the trusting period.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anchor_signature: Option<Vec<u8>>,
+}
+
+impl ReanchorRequest {
+    pub fn new(
+        proof: ProofOfPublication<TrustedAnchor>,
+        anchor_signature: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            proof,
+            anchor_signature,
+        }
+    }
+}
+
+/// Checks that the config's anchor signer signed the anchor for the given contract.
+fn verify_anchor_signature(
+    config: &Config,
+    contract: &AccountId,
+    anchor: &TrustedAnchor,
+    signature: &[u8],
+) -> Result<(), Status> {
+    let anchor_signer = config
+        .anchor_signer()
+        .ok_or_else(|| Status::failed_precondition("no anchor signer configured"))?;
+    let verifying_key = VerifyingKey::from_sec1_bytes(anchor_signer.as_slice())
+        .map_err(|e| Status::internal(e.to_string()))?;
+    let signature =
+        Signature::from_slice(signature).map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+    let domain = UserDataDomain::new(
+        config.light_client_opts().chain_id().clone(),
+        Some(contract.to_string()),
+    );
+    let sign_bytes =
+        anchor_sign_bytes(&domain, anchor).map_err(|e| Status::internal(e.to_string()))?;
+    verifying_key
+        .verify(&sign_bytes, &signature)
+        .map_err(|_| Status::permission_denied("invalid trusted anchor signature"))
+}
+
+/// Re-anchors the enclave's light client to a governance approved trusted height and hash (see the
+/// contract's `SetTrustedAnchor` msg).
+///
+/// While the enclave's trusted state is within the trusting period, the proof of publication is
+/// verified starting from it, just like any other proven request, and must go through the header at
+/// the anchor's height, so the host can't make the enclave adopt an anchor that the contract didn't
+/// publish or that isn't part of the verified chain.
+///
+/// Once the trusted state is past the trusting period, the enclave can't verify anything from it
+/// anymore. Neither can it tell for itself that it is, since the host controls its clock. The
+/// anchor must then be signed by the config's anchor signer (see `Config::with_anchor_signer`) and
+/// the proof of publication is verified starting from the anchor's header itself, i.e. the trust
+/// rests on the signer, just like the initial trust rests on the config's trusted height and hash.
+///
+/// This is also the only way to recover an enclave that was halted because of a light client attack
+/// (see [`crate::misbehaviour`]) - the admin publishes an anchor above the height of the attack and
+/// once it is adopted, the evidence is cleared.
+#[async_trait::async_trait]
+impl<E> Handler<E> for RawReanchorRequest
+where
+    E: Enclave,
+    E::Store: Store<Contract = AccountId, Height = Height, Hash = Hash>,
+{
+    type Error = Status;
+    type Response = RawReanchorResponse;
+
+    async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        let ReanchorRequest {
+            proof,
+            anchor_signature,
+        } = serde_json::from_str(&self.message)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let _guard = PROVEN_REQUEST_LOCK.lock().await;
+        let store = ctx.store().await;
+        let evidence = store
+            .get_misbehaviour_evidence()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        let contract = store
+            .get_contract()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("contract not found"))?;
+        let config = store
+            .get_config()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("config not found"))?;
+        let (trusted_height, trusted_hash) = store
+            .get_trusted_height_hash()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        // the enclave must never go back in time
+        let anchor_height = Height::try_from(proof.msg().height())
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        if anchor_height <= trusted_height {
+            return Err(Status::failed_precondition(format!(
+                "stale trusted anchor: {anchor_height} <= {trusted_height}"
+            )));
+        }
+        if let Some(evidence) = &evidence {
+            if anchor_height <= evidence.height() {
+                return Err(Status::unavailable(format!(
+                    "enclave halted: trusted anchor {anchor_height} <= attack height {}",
+                    evidence.height()
+                )));
+            }
+        }
+        let anchor_hash = Hash::Sha256(proof.msg().hash());
+
+        let light_client_opts = config.light_client_opts();
+        let (proof_value, anchor, verified_headers) = match anchor_signature {
+            None => {
+                debug!(
+                    "Verifying proof of publication for trusted anchor at height {anchor_height}"
+                );
+                proof
+                    .verify_via(
+                        light_client_opts,
+                        trusted_height,
+                        trusted_hash,
+                        Some(anchor_height),
+                        contract,
+                        TRUSTED_ANCHOR_KEY.to_string(),
+                        None,
+                    )
+                    .map_err(Status::failed_precondition)?
+            }
+            Some(signature) => {
+                verify_anchor_signature(&config, &contract, proof.msg(), &signature)?;
+
+                debug!(
+                    "Verifying proof of publication from signed anchor at height {anchor_height}"
+                );
+                proof
+                    .verify_with_headers(
+                        light_client_opts,
+                        anchor_height,
+                        anchor_hash,
+                        contract,
+                        TRUSTED_ANCHOR_KEY.to_string(),
+                        None,
+                    )
+                    .map_err(Status::failed_precondition)?
+            }
+        };
+        let anchor_header = verified_headers
+            .iter()
+            .find(|lb| lb.height() == anchor_height)
+            .map(|lb| lb.signed_header.header.hash());
+        record_verified_headers(store, verified_headers).await?;
+
+        let proven_anchor: TrustedAnchor = serde_json::from_slice(&proof_value)
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+        if proven_anchor != anchor {
+            error!("Proven trusted anchor doesn't match the claimed one");
+            return Err(Status::failed_precondition("trusted anchor mismatch"));
+        }
+        if anchor_header != Some(anchor_hash) {
+            error!("Trusted anchor doesn't match the verified header at height {anchor_height}");
+            return Err(Status::failed_precondition("trusted anchor mismatch"));
+        }
+
+        // adopt the proven anchor as the new trusted height and hash
+        store
+            .set_trusted_height_hash(anchor_height, anchor_hash)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        if evidence.is_some() {
+            info!("Trusted anchor adopted, lifting the halt");
+            store
+                .clear_misbehaviour_evidence()
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+        }
+
+        Ok(RawReanchorResponse {
+            message: serde_json::to_string(&anchor).expect("infallible serializer"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k256::ecdsa::{signature::Signer, SigningKey};
+    use quartz_contract_core::state::LightClientOpts;
+    use quartz_cw_proof::proof::cw::RawCwProof;
+    use tendermint_light_client::{
+        components::clock::{Clock, SystemClock},
+        types::LightBlock,
+    };
+
+    use super::*;
+    use crate::{
+        misbehaviour::{ensure_not_halted, MisbehaviourEvidence},
+        testing::{
+            contract, enclave, light_blocks, light_blocks_at, merkle_proof, TestEnclave, CHAIN_ID,
+        },
+    };
+
+    fn anchor(light_block: &LightBlock) -> TrustedAnchor {
+        TrustedAnchor::new(
+            light_block.height().value(),
+            light_block
+                .signed_header
+                .header
+                .hash()
+                .as_bytes()
+                .try_into()
+                .expect("sha256 hash"),
+        )
+    }
+
+    /// Generates a chain of 3 light blocks (starting at the given height and unix time), the last of
+    /// which commits to the publication of its parent as the trusted anchor, along with the Merkle
+    /// proof of the publication.
+    fn published_anchor_at(
+        height: u64,
+        unix_time: i64,
+        app_hash: Vec<u8>,
+    ) -> (Vec<LightBlock>, RawCwProof, TrustedAnchor) {
+        let anchor =
+            anchor(&light_blocks_at(height, unix_time, &[app_hash.clone(), app_hash.clone()])[1]);
+        let value = serde_json::to_vec(&anchor).expect("infallible serializer");
+        let (merkle_proof, anchor_app_hash) = merkle_proof(contract(), TRUSTED_ANCHOR_KEY, &value);
+        let light_blocks = light_blocks_at(
+            height,
+            unix_time,
+            &[app_hash.clone(), app_hash, anchor_app_hash],
+        );
+        (light_blocks, merkle_proof, anchor)
+    }
+
+    fn request(
+        proof: ProofOfPublication<TrustedAnchor>,
+        anchor_signature: Option<Vec<u8>>,
+    ) -> RawReanchorRequest {
+        RawReanchorRequest {
+            message: serde_json::to_string(&ReanchorRequest::new(proof, anchor_signature))
+                .expect("infallible serializer"),
+        }
+    }
+
+    /// Same as [`published_anchor_at`] starting at height 1, with a request proving the anchor (from
+    /// `trace_start` onwards).
+    fn published_anchor(
+        app_hash: Vec<u8>,
+        trace_start: usize,
+    ) -> (Vec<LightBlock>, RawReanchorRequest) {
+        let (light_blocks, merkle_proof, anchor) = published_anchor_at(1, 1, app_hash);
+        let proof =
+            ProofOfPublication::new(light_blocks[trace_start..].to_vec(), merkle_proof, anchor);
+        (light_blocks, request(proof, None))
+    }
+
+    async fn trusted(enclave: &TestEnclave) -> (Height, Hash) {
+        enclave
+            .store()
+            .await
+            .get_trusted_height_hash()
+            .await
+            .expect("infallible store")
+    }
+
+    fn height_hash(light_block: &LightBlock) -> (Height, Hash) {
+        (
+            light_block.height(),
+            light_block.signed_header.header.hash(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_published_anchor_is_adopted() {
+        let (light_blocks, request) = published_anchor(vec![0; 32], 0);
+        let enclave = enclave(&light_blocks[0]).await;
+
+        request.handle(&enclave).await.expect("valid proof");
+
+        assert_eq!(trusted(&enclave).await, height_hash(&light_blocks[1]));
+    }
+
+    #[tokio::test]
+    async fn test_anchor_must_be_a_verified_header() {
+        let (light_blocks, merkle_proof, anchor) = published_anchor_at(1, 1, vec![0; 32]);
+        let enclave = enclave(&light_blocks[0]).await;
+
+        // the anchor's publication is proven, but the anchor's header itself is skipped
+        let trace = vec![light_blocks[0].clone(), light_blocks[2].clone()];
+        let proof = ProofOfPublication::new(trace, merkle_proof, anchor);
+        request(proof, None)
+            .handle(&enclave)
+            .await
+            .expect_err("anchor header not verified");
+
+        assert_eq!(trusted(&enclave).await, height_hash(&light_blocks[0]));
+    }
+
+    fn anchor_signer() -> SigningKey {
+        SigningKey::from_slice(&[7; 32]).expect("valid key")
+    }
+
+    fn sign(signer: &SigningKey, anchor: &TrustedAnchor) -> Vec<u8> {
+        let domain = UserDataDomain::new(CHAIN_ID, Some(contract().to_string()));
+        let sign_bytes = anchor_sign_bytes(&domain, anchor).expect("serializable");
+        let signature: Signature = signer.sign(&sign_bytes);
+        signature.to_bytes().to_vec()
+    }
+
+    /// Returns an enclave whose trusted state (from 1970) is past the trusting period, along with
+    /// its trusted light block.
+    async fn expired_enclave(anchor_signer: Option<&SigningKey>) -> (TestEnclave, LightBlock) {
+        let stale = light_blocks(&[vec![0; 32]]).remove(0);
+        let enclave = enclave(&stale).await;
+
+        let trusting_period = 30 * 24 * 60 * 60;
+        let light_client_opts = LightClientOpts::new(
+            CHAIN_ID.to_string(),
+            stale.height().value(),
+            height_hash(&stale)
+                .1
+                .as_bytes()
+                .try_into()
+                .expect("sha256 hash"),
+            (1, 3),
+            trusting_period,
+            5,
+            10,
+        )
+        .expect("valid light client opts");
+        let mut config = Config::new([0; 32], light_client_opts, None, None);
+        if let Some(anchor_signer) = anchor_signer {
+            config =
+                config.with_anchor_signer(anchor_signer.verifying_key().to_sec1_bytes().to_vec());
+        }
+        enclave
+            .store()
+            .await
+            .set_config(config)
+            .await
+            .expect("infallible store");
+
+        (enclave, stale)
+    }
+
+    /// Same as [`published_anchor_at`], an hour ago (i.e. unlike the expired trusted state, within
+    /// the trusting period), with a proof of the anchor starting at the anchor itself.
+    fn recently_published_anchor(
+        app_hash: Vec<u8>,
+    ) -> (Vec<LightBlock>, ProofOfPublication<TrustedAnchor>) {
+        let an_hour_ago = SystemClock.now().unix_timestamp() - 60 * 60;
+        let (light_blocks, merkle_proof, anchor) = published_anchor_at(10, an_hour_ago, app_hash);
+        let proof = ProofOfPublication::new(light_blocks[1..].to_vec(), merkle_proof, anchor);
+        (light_blocks, proof)
+    }
+
+    #[tokio::test]
+    async fn test_expired_enclave_is_reanchored() {
+        let signer = anchor_signer();
+        let (enclave, stale) = expired_enclave(Some(&signer)).await;
+        let (light_blocks, proof) = recently_published_anchor(vec![0; 32]);
+
+        // the proof can't be verified from the expired trusted state
+        request(proof.clone(), None)
+            .handle(&enclave)
+            .await
+            .expect_err("trusted state expired");
+        assert_eq!(trusted(&enclave).await, height_hash(&stale));
+
+        let signature = sign(&signer, proof.msg());
+        request(proof, Some(signature))
+            .handle(&enclave)
+            .await
+            .expect("valid proof from the signed anchor");
+        assert_eq!(trusted(&enclave).await, height_hash(&light_blocks[1]));
+    }
+
+    #[tokio::test]
+    async fn test_forged_anchor_is_rejected() {
+        let (light_blocks, _) = published_anchor(vec![0; 32], 0);
+        let enclave = enclave(&light_blocks[0]).await;
+
+        // a different chain that publishes its own anchor, proven from the forged anchor itself
+        let (forged_blocks, merkle_proof, forged_anchor) = published_anchor_at(1, 1, vec![1; 32]);
+        let forged =
+            ProofOfPublication::new(forged_blocks[1..].to_vec(), merkle_proof, forged_anchor);
+        request(forged.clone(), None)
+            .handle(&enclave)
+            .await
+            .expect_err("forged anchor");
+
+        // nor can it be proven from the anchor itself, without an anchor signer
+        let signature = sign(&anchor_signer(), forged.msg());
+        request(forged, Some(signature))
+            .handle(&enclave)
+            .await
+            .expect_err("no anchor signer configured");
+
+        assert_eq!(trusted(&enclave).await, height_hash(&light_blocks[0]));
+    }
+
+    #[tokio::test]
+    async fn test_forged_anchor_is_rejected_after_expiry() {
+        let signer = anchor_signer();
+        let (enclave, stale) = expired_enclave(Some(&signer)).await;
+
+        // a different chain that publishes its own anchor, proven from the forged anchor itself
+        let (_, forged) = recently_published_anchor(vec![1; 32]);
+        request(forged.clone(), None)
+            .handle(&enclave)
+            .await
+            .expect_err("trusted state expired");
+
+        // the host can't sign the anchor itself
+        let host_signer = SigningKey::from_slice(&[8; 32]).expect("valid key");
+        let signature = sign(&host_signer, forged.msg());
+        request(forged.clone(), Some(signature))
+            .handle(&enclave)
+            .await
+            .expect_err("not signed by the anchor signer");
+
+        // nor reuse the signer's signature of another anchor
+        let (_, genuine) = recently_published_anchor(vec![0; 32]);
+        let signature = sign(&signer, genuine.msg());
+        request(forged.clone(), Some(signature))
+            .handle(&enclave)
+            .await
+            .expect_err("signature of another anchor");
+
+        assert_eq!(trusted(&enclave).await, height_hash(&stale));
+
+        // without an anchor signer, an expired enclave can't be re-anchored at all
+        let (enclave, stale) = expired_enclave(None).await;
+        let signature = sign(&signer, forged.msg());
+        request(forged, Some(signature))
+            .handle(&enclave)
+            .await
+            .expect_err("no anchor signer configured");
+        assert_eq!(trusted(&enclave).await, height_hash(&stale));
+    }
+
+    #[tokio::test]
+    async fn test_reanchoring_lifts_the_halt() {
+        let (light_blocks, request) = published_anchor(vec![0; 32], 0);
+        let enclave = enclave(&light_blocks[0]).await;
+        let store = enclave.store().await;
+
+        // a conflicting header at the trusted height
+        let conflicting = crate::testing::light_blocks(&[vec![1; 32]]).remove(0);
+        let evidence = MisbehaviourEvidence::new(
+            light_blocks[0].signed_header.clone(),
+            conflicting.signed_header,
+        );
+        store
+            .set_misbehaviour_evidence(evidence.clone())
+            .await
+            .expect("infallible store");
+
+        request
+            .handle(&enclave)
+            .await
+            .expect("anchor above the attack");
+        ensure_not_halted(store).await.expect("halt lifted");
+
+        // an anchor at (or below) the height of the attack doesn't lift the halt
+        let enclave = crate::testing::enclave(&light_blocks[0]).await;
+        let store = enclave.store().await;
+        let evidence = MisbehaviourEvidence::new(
+            light_blocks[1].signed_header.clone(),
+            light_blocks[1].signed_header.clone(),
+        );
+        store
+            .set_misbehaviour_evidence(evidence)
+            .await
+            .expect("infallible store");
+        let (_, request) = published_anchor(vec![0; 32], 0);
+        request
+            .handle(&enclave)
+            .await
+            .expect_err("anchor not above the attack");
+        ensure_not_halted(store).await.expect_err("still halted");
+    }
+}
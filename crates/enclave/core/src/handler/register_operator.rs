@@ -0,0 +1,128 @@
+use cosmrs::AccountId;
+use quartz_contract_core::msg::execute::{
+    attested::{Attested, HasUserData},
+    register_operator::RegisterOperator,
+};
+use quartz_proto::quartz::{
+    RegisterOperatorRequest as RawRegisterOperatorRequest,
+    RegisterOperatorResponse as RawRegisterOperatorResponse,
+};
+use tokio::sync::Mutex;
+use tonic::Status;
+
+use crate::{
+    attestor::{user_data_domain, Attestor},
+    handler::{Handler, A, RA},
+    key_manager::KeyManager,
+    misbehaviour::ensure_not_halted,
+    store::Store,
+    types::RegisterOperatorResponse,
+    Enclave,
+};
+
+/// Serializes operator registrations, so that two of them can't both bind the replica.
+static REGISTER_OPERATOR_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Attests to this replica holding the session key on behalf of the given operator, so that the
+/// operator can register it with the contract (see the contract's `RegisterOperator` msg). The
+/// session key is expected to have been imported from the enclave that completed the handshake
+/// (see the hand-over handlers). The replica's app outputs are then attested for the operator (see
+/// [`output_user_data_domain`](crate::attestor::output_user_data_domain)).
+///
+/// The operator is bound to the replica once and for all - the host can re-register the replica for
+/// the same operator (e.g. after a session reset), but never for another one. Otherwise, a single
+/// replica could attest the same output for several operators and make up a quorum on its own.
+#[async_trait::async_trait]
+impl<E> Handler<E> for RawRegisterOperatorRequest
+where
+    E: Enclave,
+    E::Store: Store<Contract = AccountId>,
+{
+    type Error = Status;
+    type Response = RawRegisterOperatorResponse;
+
+    async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        ensure_not_halted(ctx.store().await).await?;
+
+        let operator: AccountId = serde_json::from_str(&self.message)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let _guard = REGISTER_OPERATOR_LOCK.lock().await;
+        let store = ctx.store().await;
+        match store
+            .get_operator()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+        {
+            Some(registered) if registered != operator.to_string() => {
+                return Err(Status::failed_precondition(format!(
+                    "replica already registered for operator {registered}"
+                )));
+            }
+            Some(_) => {}
+            None => {
+                // this replica's outputs are attested for the operator from now on
+                store
+                    .set_operator(operator.to_string())
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?;
+            }
+        }
+
+        // create `RegisterOperator` msg and attest to it
+        let pub_key = ctx.key_manager().await.pub_key().await.into();
+        let msg = RegisterOperator::new(operator.to_string(), pub_key);
+        let domain = user_data_domain(store).await?;
+        let attestation = ctx
+            .attestor()
+            .await
+            .attestation(msg.user_data_for(&domain))
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let attested_msg = Attested::new(msg, attestation);
+
+        // return response with attested `RegisterOperator` msg
+        let response: RegisterOperatorResponse<A<E>, RA<E>> =
+            RegisterOperatorResponse::new(attested_msg);
+        Ok(response.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{enclave, light_blocks};
+
+    fn request(operator: &AccountId) -> RawRegisterOperatorRequest {
+        RawRegisterOperatorRequest {
+            message: serde_json::to_string(operator).expect("infallible serializer"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replica_is_bound_to_its_first_operator() {
+        let enclave = enclave(&light_blocks(&[vec![0; 32]])[0]).await;
+        let alice = AccountId::new("wasm", &[2; 20]).expect("valid account id");
+        let bob = AccountId::new("wasm", &[3; 20]).expect("valid account id");
+
+        request(&alice)
+            .handle(&enclave)
+            .await
+            .expect("first registration");
+        request(&alice)
+            .handle(&enclave)
+            .await
+            .expect("same operator");
+        request(&bob)
+            .handle(&enclave)
+            .await
+            .expect_err("another operator");
+
+        let operator = enclave
+            .store()
+            .await
+            .get_operator()
+            .await
+            .expect("infallible store");
+        assert_eq!(operator, Some(alice.to_string()));
+    }
+}
@@ -1,6 +1,9 @@
 use cosmrs::AccountId;
 use quartz_contract_core::{
-    msg::execute::{attested::Attested, session_create::SessionCreate},
+    msg::execute::{
+        attested::{Attested, HasUserData},
+        session_create::SessionCreate,
+    },
     state::Nonce,
 };
 use quartz_proto::quartz::{
@@ -11,8 +14,9 @@ use rand::Rng;
 use tonic::Status;
 
 use crate::{
-    attestor::Attestor,
+    attestor::{user_data_domain, Attestor},
     handler::{Handler, A, RA},
+    misbehaviour::ensure_not_halted,
     store::Store,
     types::SessionCreateResponse,
     Enclave,
@@ -27,6 +31,8 @@ where
     type Response = RawSessionCreateResponse;
 
     async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        ensure_not_halted(ctx.store().await).await?;
+
         // store contract
         let deployed_contract: AccountId = serde_json::from_str(&self.message)
             .map_err(|e| Status::invalid_argument(e.to_string()))?;
@@ -56,10 +62,11 @@ where
 
         // create `SessionCreate` msg and attest to it
         let msg = SessionCreate::new(nonce, deployed_contract.to_string());
+        let domain = user_data_domain(ctx.store().await).await?;
         let attestation = ctx
             .attestor()
             .await
-            .attestation(msg.clone())
+            .attestation(msg.user_data_for(&domain))
             .map_err(|e| Status::internal(e.to_string()))?;
         let attested_msg = Attested::new(msg, attestation);
 
@@ -1,6 +1,9 @@
 use cosmrs::AccountId;
 use quartz_contract_core::{
-    msg::execute::{attested::Attested, session_set_pub_key::SessionSetPubKey},
+    msg::execute::{
+        attested::{Attested, HasUserData},
+        session_set_pub_key::SessionSetPubKey,
+    },
     state::{Session, SESSION_KEY},
 };
 use quartz_proto::quartz::{
@@ -11,9 +14,10 @@ use tendermint::{block::Height, Hash};
 use tonic::Status;
 
 use crate::{
-    attestor::Attestor,
+    attestor::{user_data_domain, Attestor},
     handler::{Handler, A, RA},
     key_manager::KeyManager,
+    misbehaviour::{ensure_not_halted, record_verified_headers},
     proof_of_publication::ProofOfPublication,
     store::Store,
     types::SessionSetPubKeyResponse,
@@ -31,6 +35,8 @@ where
     type Response = RawSessionSetPubKeyResponse;
 
     async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        ensure_not_halted(ctx.store().await).await?;
+
         // verify proof of publication
         let proof: ProofOfPublication<Option<()>> = serde_json::from_str(&self.message)
             .map_err(|e| Status::invalid_argument(e.to_string()))?;
@@ -56,8 +62,8 @@ where
             .map_err(|e| Status::internal(e.to_string()))?;
         let (target_height, target_hash) = proof.target_height_hash();
 
-        let (value, _msg) = proof
-            .verify(
+        let (value, _msg, verified_headers) = proof
+            .verify_with_headers(
                 config.light_client_opts(),
                 trusted_height,
                 trusted_hash,
@@ -66,6 +72,7 @@ where
                 None,
             )
             .map_err(Status::failed_precondition)?;
+        record_verified_headers(ctx.store().await, verified_headers).await?;
 
         // update trusted height and hash
         ctx.store()
@@ -92,10 +99,11 @@ where
 
         // create `SessionSetPubKey` msg and attest to it
         let msg = SessionSetPubKey::new(nonce, pk);
+        let domain = user_data_domain(ctx.store().await).await?;
         let attestation = ctx
             .attestor()
             .await
-            .attestation(msg.clone())
+            .attestation(msg.user_data_for(&domain))
             .map_err(|e| Status::internal(e.to_string()))?;
         let attested_msg = Attested::new(msg, attestation);
 
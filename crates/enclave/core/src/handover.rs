@@ -0,0 +1,253 @@
+//! Enclave-to-enclave secret hand-over for enclave upgrades.
+//!
+//! An enclave upgrade changes the MRENCLAVE, so the new enclave can neither unseal the old
+//! enclave's backup nor decrypt the (on-chain) state that was encrypted to the old enclave's key.
+//! Once the contract admin has approved the new MRENCLAVE (see the contract's
+//! `UpdateEnclaveIdentity` msg), the secrets are handed over as follows -
+//!
+//! 1. The new enclave generates an ephemeral key and returns an attested [`HandoverOffer`]
+//!    containing its public key, for the contract whose secrets it is meant to receive.
+//! 2. The old enclave verifies a proof of publication of the contract's config, checks that the
+//!    offer is attested by an enclave that the (on-chain) config trusts, and encrypts its
+//!    key-manager and store to the ephemeral public key.
+//!    The old enclave also attests to the ciphertext (bound to the offer's public key), so that
+//!    the new enclave only imports secrets that were exported by a trusted enclave.
+//! 3. The new enclave verifies the old enclave's attestation against its own config, decrypts the
+//!    [`HandoverPayload`] and imports it.
+//!
+//! Like all other attested messages, the offer and the export are bound to the deployment (i.e. the
+//! chain id and the contract, see [`HasUserData::user_data_for`]), so neither can be replayed to
+//! an enclave of another deployment.
+
+use ecies::{decrypt, encrypt};
+use k256::ecdsa::SigningKey;
+use quartz_contract_core::{
+    msg::execute::attested::{
+        user_data_json, Attestation, DcapAttestation, HasUserData, MockAttestation, UserDataDomain,
+    },
+    state::{Config, EnclaveIdentity, RawConfig, UserData},
+};
+use quartz_tee_ra::intel_sgx::dcap::{verify_with_policy, TrustedIdentity};
+use serde::{Deserialize, Serialize};
+use serde_with::{hex::Hex, serde_as};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::proof_of_publication::ProofOfPublication;
+
+/// An in-progress hand-over (on the receiving enclave).
+pub(crate) struct PendingHandover {
+    /// The ephemeral key, which never leaves the enclave.
+    pub key: SigningKey,
+    /// The deployment that the offer was made for.
+    pub domain: UserDataDomain,
+}
+
+/// The in-progress hand-over, which is consumed by the import, so every offer can be used at most
+/// once.
+pub(crate) static PENDING_HANDOVER: Mutex<Option<PendingHandover>> = Mutex::const_new(None);
+
+/// An attestation that can be verified inside an enclave (e.g. the attestation of a successor
+/// enclave).
+pub trait VerifiableAttestation: Attestation + HasUserData + Send + Sync {
+    /// Verifies the attestation, making sure it was generated by an enclave that the config trusts
+    /// (on a platform that satisfies the config's TCB policy).
+    fn verify(&self, config: &Config) -> Result<(), String>;
+}
+
+fn ensure_trusted(
+    attestation: &impl Attestation,
+    identities: &[EnclaveIdentity],
+) -> Result<(), String> {
+    if identities
+        .iter()
+        .any(|identity| identity.matches(attestation))
+    {
+        Ok(())
+    } else {
+        Err("untrusted enclave identity".to_string())
+    }
+}
+
+impl VerifiableAttestation for DcapAttestation {
+    fn verify(&self, config: &Config) -> Result<(), String> {
+        let identities = config.trusted_identities();
+        ensure_trusted(self, &identities)?;
+
+        let (quote, collateral) = self.clone().into_tuple();
+        let identities: Vec<TrustedIdentity> = identities.into_iter().map(Into::into).collect();
+        // there's no trusted time inside the enclave, so the policy's grace period doesn't apply
+        verify_with_policy(quote, collateral, &identities, config.tcb_policy(), None)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl VerifiableAttestation for MockAttestation {
+    fn verify(&self, config: &Config) -> Result<(), String> {
+        ensure_trusted(self, &config.trusted_identities())
+    }
+}
+
+/// The (attested) offer of a new enclave to receive the secrets of the old one.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HandoverOffer {
+    #[serde_as(as = "Hex")]
+    pub_key: Vec<u8>,
+}
+
+impl HandoverOffer {
+    pub fn new(pub_key: Vec<u8>) -> Self {
+        Self { pub_key }
+    }
+
+    pub fn pub_key(&self) -> &[u8] {
+        &self.pub_key
+    }
+}
+
+impl HasUserData for HandoverOffer {
+    fn user_data(&self) -> UserData {
+        user_data_json(self)
+    }
+
+    fn msg_type(&self) -> &'static str {
+        "handover_offer"
+    }
+}
+
+/// A [`HandoverOffer`] along with the new enclave's (raw) attestation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttestedHandoverOffer<RA> {
+    pub offer: HandoverOffer,
+    pub attestation: RA,
+}
+
+/// The request that the old enclave handles to export its secrets.
+///
+/// The proof must be a proof of publication of the contract's config (i.e. `CONFIG_KEY`) that
+/// trusts the new enclave.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HandoverExportRequest<RA> {
+    pub offer: AttestedHandoverOffer<RA>,
+    pub config_proof: ProofOfPublication<Option<()>>,
+}
+
+/// The export of the old enclave, binding the encrypted [`HandoverPayload`] to the offer that it
+/// was encrypted for.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HandoverExport {
+    #[serde_as(as = "Hex")]
+    pub_key: Vec<u8>,
+    #[serde_as(as = "Hex")]
+    ciphertext_hash: [u8; 32],
+}
+
+impl HandoverExport {
+    pub fn new(offer: &HandoverOffer, ciphertext: &[u8]) -> Self {
+        Self {
+            pub_key: offer.pub_key.clone(),
+            ciphertext_hash: Sha256::digest(ciphertext).into(),
+        }
+    }
+}
+
+impl HasUserData for HandoverExport {
+    fn user_data(&self) -> UserData {
+        user_data_json(self)
+    }
+
+    fn msg_type(&self) -> &'static str {
+        "handover_export"
+    }
+}
+
+/// The encrypted [`HandoverPayload`] along with the old enclave's (raw) attestation to the
+/// corresponding [`HandoverExport`].
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttestedHandoverPayload<RA> {
+    #[serde_as(as = "Hex")]
+    pub ciphertext: Vec<u8>,
+    pub attestation: RA,
+}
+
+/// The secrets that are handed over (encrypted to the new enclave's ephemeral key).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HandoverPayload {
+    /// The proven on-chain config that trusts the new enclave.
+    pub config: RawConfig,
+    /// The exported key-manager.
+    pub key_manager: Vec<u8>,
+    /// The exported store.
+    pub store: Vec<u8>,
+}
+
+impl HandoverPayload {
+    pub fn encrypt(&self, pub_key: &[u8]) -> Result<Vec<u8>, String> {
+        let payload = serde_json::to_vec(self).expect("infallible serializer");
+        encrypt(pub_key, &payload).map_err(|e| e.to_string())
+    }
+
+    pub fn decrypt(sk: &SigningKey, ciphertext: &[u8]) -> Result<Self, String> {
+        let payload = decrypt(&sk.to_bytes(), ciphertext).map_err(|e| e.to_string())?;
+        serde_json::from_slice(&payload).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quartz_contract_core::state::LightClientOpts;
+
+    use super::*;
+
+    #[test]
+    fn test_handover_payload_roundtrip() {
+        let sk = SigningKey::random(&mut rand::thread_rng());
+        let offer = HandoverOffer::new(sk.verifying_key().to_sec1_bytes().into());
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts");
+        let payload = HandoverPayload {
+            config: Config::new([1; 32], light_client_opts, None, None).into(),
+            key_manager: vec![1, 2, 3],
+            store: vec![4, 5, 6],
+        };
+
+        let ciphertext = payload.encrypt(offer.pub_key()).expect("valid pub key");
+        let decrypted = HandoverPayload::decrypt(&sk, &ciphertext).expect("matching key");
+        assert_eq!(decrypted.key_manager, payload.key_manager);
+        assert_eq!(decrypted.store, payload.store);
+
+        let other_sk = SigningKey::random(&mut rand::thread_rng());
+        HandoverPayload::decrypt(&other_sk, &ciphertext).expect_err("wrong key");
+    }
+
+    #[test]
+    fn test_mock_attestation_mr_enclave_is_checked() {
+        let offer = HandoverOffer::new(vec![2; 33]);
+        let attestation = MockAttestation(offer.user_data());
+        let light_client_opts =
+            LightClientOpts::new("test-chain".to_string(), 1, [0; 32], (1, 3), 1, 1, 10)
+                .expect("valid light client opts");
+        let config = |mr_enclave| Config::new(mr_enclave, light_client_opts.clone(), None, None);
+
+        attestation
+            .verify(&config([0; 32]))
+            .expect("mock MRENCLAVE");
+        attestation
+            .verify(&config([1; 32]))
+            .expect_err("unapproved MRENCLAVE");
+        attestation
+            .verify(
+                &config([1; 32]).with_identities(vec![EnclaveIdentity::MrSigner {
+                    mr_signer: [0; 32],
+                    isv_prod_id: 0,
+                    min_isv_svn: 0,
+                }]),
+            )
+            .expect("trusted mock MRSIGNER");
+    }
+}
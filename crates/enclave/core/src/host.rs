@@ -12,12 +12,13 @@ use log::{error, info, trace, warn};
 use quartz_proto::quartz::core_server::{Core, CoreServer};
 use reqwest::Url;
 use serde::Serialize;
+use tendermint::{block::Height, Hash};
 use tendermint_rpc::{
     event::Event as TmEvent,
     query::{EventType, Query},
     SubscriptionClient, WebSocketClient,
 };
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{self, Receiver};
 use tonic::{transport::Server, Status};
 use tonic_health::server::health_reporter;
 
@@ -155,7 +156,7 @@ where
 impl<R, EV, GF, E, C> Host for DefaultHost<R, EV, GF, E, C>
 where
     E: Enclave + Backup<Config = PathBuf, Error = anyhow::Error> + Clone + Core,
-    <E as Enclave>::Store: Store<Contract = AccountId>,
+    <E as Enclave>::Store: Store<Contract = AccountId, Height = Height, Hash = Hash>,
     C: ChainClient<Contract = AccountId, Error = anyhow::Error>,
     <C as ChainClient>::TxOutput: Display,
     R: Handler<E, Error = Status> + Debug,
@@ -219,13 +220,33 @@ where
             info!("backup path not specified; skipping backup/restore operations");
         }
 
-        // wait for handshake
-        if let Some(Notification::HandshakeComplete) = self.notifier_rx.recv().await {
+        // wait for handshake (backing up on the way, e.g. if an attack was detected meanwhile)
+        while let Some(notification) = self.notifier_rx.recv().await {
             if let Some(ref backup_path) = self.backup_path {
                 self.enclave.backup(backup_path.clone()).await?;
             }
+            if let Notification::HandshakeComplete = notification {
+                break;
+            }
         }
 
+        // from now on, back up whenever the enclave notifies us of a state update (or a new handshake)
+        let (_, closed_rx) = mpsc::channel(1);
+        let mut notifier_rx = std::mem::replace(&mut self.notifier_rx, closed_rx);
+        let enclave = self.enclave.clone();
+        let backup_path = self.backup_path.clone();
+        tokio::spawn(async move {
+            while let Some(notification) = notifier_rx.recv().await {
+                let Some(ref backup_path) = backup_path else {
+                    continue;
+                };
+                trace!("Backing up after {notification:?}");
+                if let Err(e) = enclave.backup(backup_path.clone()).await {
+                    error!("failed to back up: {e}");
+                }
+            }
+        });
+
         // connect to the websocket client
         let (client, driver) = WebSocketClient::new(url.as_str()).await.unwrap();
         let driver_handle = tokio::spawn(async move { driver.run().await });
@@ -269,6 +290,18 @@ where
                 continue;
             }
 
+            // anchor the proofs generated for this event at the enclave's current trusted state
+            let (trusted_height, trusted_hash) = self
+                .enclave
+                .store()
+                .await
+                .get_trusted_height_hash()
+                .await
+                .map_err(|_| anyhow!("trusted height/hash read failure"))?;
+            self.chain_client
+                .set_trusted_anchor(trusted_height, trusted_hash)
+                .await?;
+
             // handle event (through event handler) and generate enclave request
             let request = match event.handle(&self.chain_client).await {
                 Ok(r) => r,
@@ -285,6 +318,19 @@ where
                 Ok(r) => r,
                 Err(e) => {
                     error!("request handler: {e}");
+
+                    // the request may have revealed a light client attack, in which case the
+                    // evidence must be persisted right away
+                    let evidence = self
+                        .enclave
+                        .store()
+                        .await
+                        .get_misbehaviour_evidence()
+                        .await
+                        .map_err(|_| anyhow!("misbehaviour evidence read failure"))?;
+                    if let (Some(_), Some(ref backup_path)) = (evidence, &self.backup_path) {
+                        self.enclave.backup(backup_path.clone()).await?;
+                    }
                     continue;
                 }
             };
@@ -19,3 +19,14 @@ pub trait KeyManager: Send + Sync + 'static {
     /// Returns the enclave public key.
     async fn pub_key(&self) -> Self::PubKey;
 }
+
+/// A key-manager that can decrypt data that was encrypted to its public key (e.g. the payload of an
+/// `EncryptedEnvelope`, see [`crate::envelope`]).
+#[async_trait::async_trait]
+pub trait Decrypt: KeyManager {
+    type Error: ToString;
+
+    /// Decrypts an ECIES (secp256k1) ciphertext, i.e. the ephemeral pub key followed by the
+    /// encrypted payload.
+    async fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
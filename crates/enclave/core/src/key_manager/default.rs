@@ -1,9 +1,10 @@
+use ecies::decrypt;
 use k256::ecdsa::{Error, SigningKey, VerifyingKey};
 use log::{debug, info};
 
 use crate::{
     backup_restore::{Export, Import},
-    key_manager::KeyManager,
+    key_manager::{Decrypt, KeyManager},
 };
 
 /// A default secp256k1 key-manager.
@@ -31,6 +32,15 @@ impl KeyManager for DefaultKeyManager {
     }
 }
 
+#[async_trait::async_trait]
+impl Decrypt for DefaultKeyManager {
+    type Error = String;
+
+    async fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        decrypt(&self.sk.to_bytes(), ciphertext).map_err(|e| e.to_string())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PubKey(VerifyingKey);
 
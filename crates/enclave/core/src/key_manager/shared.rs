@@ -4,7 +4,7 @@ use tokio::sync::{RwLock, RwLockReadGuard};
 
 use crate::{
     backup_restore::{Export, Import},
-    key_manager::KeyManager,
+    key_manager::{Decrypt, KeyManager},
 };
 
 /// A thread-safe wrapper for a key-manager.
@@ -34,6 +34,15 @@ impl<K: KeyManager> KeyManager for SharedKeyManager<K> {
     }
 }
 
+#[async_trait::async_trait]
+impl<K: Decrypt> Decrypt for SharedKeyManager<K> {
+    type Error = K::Error;
+
+    async fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.inner.read().await.decrypt(ciphertext).await
+    }
+}
+
 #[async_trait::async_trait]
 impl<K: KeyManager + Import + Send> Import for SharedKeyManager<K> {
     type Error = K::Error;
@@ -102,13 +102,20 @@ use crate::{
 pub mod attestor;
 pub mod backup_restore;
 pub mod chain_client;
+pub mod envelope;
 pub mod event;
 pub mod grpc;
 pub mod handler;
+pub mod handover;
 pub mod host;
 pub mod key_manager;
+pub mod misbehaviour;
 pub mod proof_of_publication;
+pub mod proven_request;
+pub mod replica;
 pub mod store;
+#[cfg(test)]
+mod testing;
 pub mod types;
 
 /// A type alias for a default, thread-safe enclave.
@@ -160,6 +167,12 @@ pub trait Enclave: Send + Sync + 'static {
 pub enum Notification {
     /// Fired once the enclave finishes its remote-attestation handshake.
     HandshakeComplete,
+    /// Fired when the enclave updated its trusted state outside of the regular request flow (e.g.
+    /// when it was re-anchored), which must be backed up right away to survive a restart.
+    StateUpdated,
+    /// Fired when the enclave detected a light client attack and halted. The evidence must be
+    /// backed up right away, otherwise a restart from an older backup would lift the halt.
+    MisbehaviourDetected,
 }
 
 /// The default generic implementation of the [`Enclave`] trait for convenience.
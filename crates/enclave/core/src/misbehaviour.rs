@@ -0,0 +1,270 @@
+//! Enclave-side light client attack detection.
+//!
+//! Witness cross-checking (see `tm-prover`) only happens on the host, so the enclave can't rely on
+//! it. Instead, the enclave remembers the headers it recently verified and if it is ever shown a
+//! validly signed header that doesn't match the one it verified for the same height, it persists
+//! both signed headers as evidence in the [`Store`] and refuses to handle any further requests. The
+//! evidence can be queried over gRPC so operators (and contracts) can react to the attack.
+//!
+//! Note that the enclave only ever sees the primary's traces (the witnesses' traces never leave the
+//! host), so a conflict is only detected at a height that the proofs of two requests both cover. A
+//! host that consistently feeds the enclave a forked chain isn't detected here - that is what the
+//! host-side witness cross-checking is for.
+//!
+//! A halted enclave can only be recovered by re-anchoring it to a trusted height above the attack
+//! that the contract admin published (see the `Reanchor` handler).
+
+use log::{error, trace};
+use serde::{Deserialize, Serialize};
+use tendermint::block::{signed_header::SignedHeader, Height};
+use tendermint_light_client::types::LightBlock;
+use tonic::Status;
+
+use crate::store::Store;
+
+/// The number of blocks below the trusted height for which verified headers are kept.
+///
+/// Light client proofs always start at the trusted height, so older headers can't be used to
+/// mislead the enclave anymore.
+pub const VERIFIED_HEADERS_WINDOW: u64 = 1000;
+
+/// Evidence of a light client attack - two validly signed headers that conflict at the same
+/// height.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MisbehaviourEvidence {
+    /// The signed header that the enclave had verified first.
+    known: SignedHeader,
+    /// The conflicting signed header that was verified later.
+    conflicting: SignedHeader,
+}
+
+impl MisbehaviourEvidence {
+    pub fn new(known: SignedHeader, conflicting: SignedHeader) -> Self {
+        Self { known, conflicting }
+    }
+
+    pub fn height(&self) -> Height {
+        self.conflicting.header.height
+    }
+
+    pub fn known(&self) -> &SignedHeader {
+        &self.known
+    }
+
+    pub fn conflicting(&self) -> &SignedHeader {
+        &self.conflicting
+    }
+}
+
+/// Fails if the enclave was halted because of a detected light client attack.
+#[allow(clippy::result_large_err)]
+pub async fn ensure_not_halted<S: Store>(store: &S) -> Result<(), Status> {
+    let evidence = store
+        .get_misbehaviour_evidence()
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+    match evidence {
+        Some(evidence) => Err(Status::unavailable(format!(
+            "enclave halted: light client attack detected at height {}",
+            evidence.height()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Records the signed headers of the given verified light blocks (ordered by height, starting with
+/// the trusted one), checking them against the ones verified previously. Headers that fall out of
+/// the [`VERIFIED_HEADERS_WINDOW`] are pruned.
+///
+/// On conflict the evidence is persisted (which halts the enclave) and an `Aborted` error is
+/// returned, upon which the host is expected to back up the enclave right away.
+#[allow(clippy::result_large_err)]
+pub async fn record_verified_headers<S>(
+    store: &S,
+    light_blocks: Vec<LightBlock>,
+) -> Result<(), Status>
+where
+    S: Store<Height = Height>,
+{
+    let Some(trusted_height) = light_blocks.first().map(LightBlock::height) else {
+        return Ok(());
+    };
+
+    for light_block in light_blocks {
+        let height = light_block.height();
+        let known = store
+            .get_verified_header(height)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        match known {
+            Some(known) if known.header.hash() != light_block.signed_header.header.hash() => {
+                error!("Light client attack detected - conflicting headers at height {height}");
+
+                store
+                    .set_misbehaviour_evidence(MisbehaviourEvidence::new(
+                        known,
+                        light_block.signed_header,
+                    ))
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?;
+
+                return Err(Status::aborted(format!(
+                    "light client attack detected at height {height}"
+                )));
+            }
+            Some(_) => trace!("Header at height {height} already verified"),
+            None => {
+                store
+                    .set_verified_header(height, light_block.signed_header)
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?;
+            }
+        }
+    }
+
+    let prune_height = trusted_height
+        .value()
+        .saturating_sub(VERIFIED_HEADERS_WINDOW);
+    if let Ok(prune_height) = Height::try_from(prune_height) {
+        store
+            .prune_verified_headers(prune_height)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use quartz_contract_core::state::Config;
+    use tendermint::Time;
+    use tendermint_testgen::{
+        light_block::TmLightBlock, Generator, LightBlock as TestgenLightBlock,
+    };
+
+    use super::*;
+    use crate::{
+        backup_restore::{Export, Import},
+        store::default::DefaultStore,
+        testing::light_client_opts,
+    };
+
+    fn light_block(height: u64, unix_time: i64) -> LightBlock {
+        let TmLightBlock {
+            signed_header,
+            validators,
+            next_validators,
+            provider,
+        } = TestgenLightBlock::new_default_with_time_and_chain_id(
+            "test-chain".to_string(),
+            Time::from_unix_timestamp(unix_time, 0).expect("valid time"),
+            height,
+        )
+        .generate()
+        .expect("valid light block");
+        LightBlock::new(signed_header, validators, next_validators, provider)
+    }
+
+    async fn verified_header(store: &DefaultStore, height: u64) -> Option<SignedHeader> {
+        store
+            .get_verified_header(Height::try_from(height).expect("valid height"))
+            .await
+            .expect("infallible store")
+    }
+
+    #[tokio::test]
+    async fn test_matching_headers_are_accepted() {
+        let store = DefaultStore::default();
+
+        record_verified_headers(&store, vec![light_block(1, 1), light_block(2, 2)])
+            .await
+            .expect("no conflict");
+        record_verified_headers(&store, vec![light_block(2, 2), light_block(3, 3)])
+            .await
+            .expect("no conflict");
+
+        ensure_not_halted(&store).await.expect("not halted");
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_headers_halt_the_enclave() {
+        let store = DefaultStore::default();
+        let known = light_block(2, 2);
+        let conflicting = light_block(2, 42);
+
+        record_verified_headers(&store, vec![known.clone()])
+            .await
+            .expect("no conflict");
+        record_verified_headers(&store, vec![conflicting.clone()])
+            .await
+            .expect_err("conflicting header");
+
+        let evidence = store
+            .get_misbehaviour_evidence()
+            .await
+            .expect("infallible store")
+            .expect("evidence persisted");
+        assert_eq!(
+            evidence,
+            MisbehaviourEvidence::new(known.signed_header, conflicting.signed_header)
+        );
+        ensure_not_halted(&store).await.expect_err("halted");
+    }
+
+    #[tokio::test]
+    async fn test_headers_outside_the_window_are_pruned() {
+        let store = DefaultStore::default();
+        let trusted_height = VERIFIED_HEADERS_WINDOW + 2;
+
+        record_verified_headers(&store, vec![light_block(1, 1), light_block(2, 2)])
+            .await
+            .expect("no conflict");
+        record_verified_headers(&store, vec![light_block(trusted_height, 3)])
+            .await
+            .expect("no conflict");
+
+        assert_eq!(verified_header(&store, 1).await, None);
+        assert!(verified_header(&store, 2).await.is_some());
+        assert!(verified_header(&store, trusted_height).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_evidence_survives_export_and_restore() {
+        let config = Config::new([0; 32], light_client_opts(&light_block(1, 1)), None, None);
+        let store = DefaultStore::new(config.clone());
+        let backup_before_attack = store.export().await.expect("infallible export");
+
+        record_verified_headers(&store, vec![light_block(2, 2)])
+            .await
+            .expect("no conflict");
+        record_verified_headers(&store, vec![light_block(2, 42)])
+            .await
+            .expect_err("conflicting header");
+        let backup = store.export().await.expect("infallible export");
+
+        // a restarted enclave restores the evidence and stays halted
+        let mut restored = DefaultStore::new(config);
+        restored.import(backup).await.expect("valid backup");
+        assert_eq!(
+            restored
+                .get_misbehaviour_evidence()
+                .await
+                .expect("infallible store"),
+            store
+                .get_misbehaviour_evidence()
+                .await
+                .expect("infallible store"),
+        );
+        ensure_not_halted(&restored).await.expect_err("halted");
+
+        // restoring a backup taken before the attack must not lift the halt
+        restored
+            .import(backup_before_attack)
+            .await
+            .expect_err("evidence dropped");
+        ensure_not_halted(&restored).await.expect_err("halted");
+    }
+}
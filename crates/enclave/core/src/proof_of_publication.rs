@@ -30,6 +30,19 @@ pub struct ProofOfPublication<M> {
 }
 
 impl<M> ProofOfPublication<M> {
+    #[cfg(test)]
+    pub(crate) fn new(
+        light_client_proof: Vec<LightBlock>,
+        merkle_proof: RawCwProof,
+        msg: M,
+    ) -> Self {
+        Self {
+            light_client_proof,
+            merkle_proof,
+            msg,
+        }
+    }
+
     pub fn verify(
         self,
         light_client_opts: &LightClientOpts,
@@ -39,6 +52,53 @@ impl<M> ProofOfPublication<M> {
         storage_key: String,
         storage_namespace: Option<String>,
     ) -> Result<(Vec<u8>, M), String> {
+        self.verify_with_headers(
+            light_client_opts,
+            trusted_height,
+            trusted_hash,
+            contract_address,
+            storage_key,
+            storage_namespace,
+        )
+        .map(|(value, msg, _)| (value, msg))
+    }
+
+    /// Same as [`ProofOfPublication::verify`] but also returns all the light blocks that were
+    /// verified along the way (including the trusted one), ordered by height.
+    pub fn verify_with_headers(
+        self,
+        light_client_opts: &LightClientOpts,
+        trusted_height: Height,
+        trusted_hash: Hash,
+        contract_address: AccountId,
+        storage_key: String,
+        storage_namespace: Option<String>,
+    ) -> Result<(Vec<u8>, M, Vec<LightBlock>), String> {
+        self.verify_via(
+            light_client_opts,
+            trusted_height,
+            trusted_hash,
+            None,
+            contract_address,
+            storage_key,
+            storage_namespace,
+        )
+    }
+
+    /// Same as [`ProofOfPublication::verify_with_headers`] but, if `via_height` is specified, the
+    /// light block at that height is verified on the way to the target (so that it's among the
+    /// returned light blocks).
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_via(
+        self,
+        light_client_opts: &LightClientOpts,
+        trusted_height: Height,
+        trusted_hash: Hash,
+        via_height: Option<Height>,
+        contract_address: AccountId,
+        storage_key: String,
+        storage_namespace: Option<String>,
+    ) -> Result<(Vec<u8>, M, Vec<LightBlock>), String> {
         let config_trust_threshold = light_client_opts.trust_threshold();
         let trust_threshold =
             TrustThreshold::new(config_trust_threshold.0, config_trust_threshold.1).unwrap();
@@ -55,16 +115,33 @@ impl<M> ProofOfPublication<M> {
         };
 
         let target_height = self.light_client_proof.last().unwrap().height();
+        // the stateless provider can't fetch missing blocks, so the trace must include the header
+        if let Some(via_height) = via_height {
+            if !self
+                .light_client_proof
+                .iter()
+                .any(|light_block| light_block.height() == via_height)
+            {
+                return Err(format!("missing light block at height {via_height}"));
+            }
+        }
 
-        let primary_block = make_provider(
+        let mut primary = make_provider(
             light_client_opts.chain_id(),
             trusted_height,
             trusted_hash,
             self.light_client_proof,
             options,
         )
-        .and_then(|mut primary| primary.verify_to_height(target_height))
         .map_err(|e| e.to_string())?;
+        if let Some(via_height) = via_height {
+            primary
+                .verify_to_height(via_height)
+                .map_err(|e| e.to_string())?;
+        }
+        let primary_block = primary
+            .verify_to_height(target_height)
+            .map_err(|e| e.to_string())?;
 
         let key = CwAbciKey::new(contract_address, storage_key, storage_namespace);
         if key.into_vec() != self.merkle_proof.key() {
@@ -83,7 +160,11 @@ impl<M> ProofOfPublication<M> {
             )
             .map_err(|e| e.to_string())?;
 
-        Ok((proof.value, self.msg))
+        Ok((proof.value, self.msg, primary.verified_blocks()))
+    }
+
+    pub fn msg(&self) -> &M {
+        &self.msg
     }
 
     pub fn target_height_hash(&self) -> (Height, Hash) {
@@ -0,0 +1,441 @@
+//! A generic request wrapper for messages that must be proven to have been published on-chain.
+//!
+//! Almost every app request handler starts the same way - load the paired contract, the config and
+//! the trusted height/hash from the [`Store`], verify the [`ProofOfPublication`], make sure the
+//! proven storage value matches the message, check the sequence number and update the trusted
+//! height/hash. [`ProvenRequest`] does all of that and hands the verified message to the app.
+//!
+//! ```ignore
+//! pub struct RequestsKey;
+//!
+//! impl StorageKey for RequestsKey {
+//!     const KEY: &'static str = REQUESTS_KEY;
+//! }
+//!
+//! type UpdateProvenRequest =
+//!     ProvenRequest<UpdateRequestMessage, RequestsKey, JsonValueMatch, SeqNumChecked>;
+//!
+//! let request: UpdateProvenRequest = serde_json::from_str(&self.message)
+//!     .map_err(|e| Status::invalid_argument(e.to_string()))?;
+//! let message = request.handle(ctx).await?.into_msg();
+//! ```
+
+use std::marker::PhantomData;
+
+use cosmrs::AccountId;
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+use tendermint::{block::Height, Hash};
+use tokio::sync::Mutex;
+use tonic::Status;
+
+use crate::{
+    handler::{ensure_seq_num_consistency, Handler},
+    misbehaviour::{ensure_not_halted, record_verified_headers},
+    proof_of_publication::ProofOfPublication,
+    store::Store,
+    Enclave,
+};
+
+/// Serializes the handling of all proven requests.
+///
+/// Handling a proven request is a read-verify-write sequence over the trusted height/hash and the
+/// sequence number in the store, so two requests must never interleave.
+pub(crate) static PROVEN_REQUEST_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// The contract storage item that a proven message is expected to be published under.
+pub trait StorageKey: Send + Sync + 'static {
+    /// The storage key of the item.
+    const KEY: &'static str;
+    /// The storage namespace of the item (only makes sense when dealing with maps).
+    const NAMESPACE: Option<&'static str> = None;
+}
+
+/// A strategy for checking that the value proven to be in contract storage matches the message.
+pub trait ValueMatcher<M>: Send + Sync + 'static {
+    fn matches(msg: &M, proof_value: &[u8]) -> bool;
+}
+
+/// A message that carries (a copy of) the value that is expected to be in contract storage.
+pub trait HasProvenValue {
+    type Value: Serialize;
+
+    fn proven_value(&self) -> &Self::Value;
+}
+
+/// Matches the JSON serialization of [`HasProvenValue::proven_value`] against the proven value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonValueMatch;
+
+impl<M: HasProvenValue> ValueMatcher<M> for JsonValueMatch {
+    fn matches(msg: &M, proof_value: &[u8]) -> bool {
+        serde_json::to_string(msg.proven_value()).is_ok_and(|s| s.as_bytes() == proof_value)
+    }
+}
+
+/// A message that contains requests sequenced by the contract (see the contract's `Sequenced`).
+pub trait SequencedMessage {
+    /// The sequence number the contract had when the message was published.
+    fn seq_num(&self) -> u64;
+
+    /// The number of sequenced requests in this message that the enclave hasn't processed yet.
+    fn pending_sequenced_requests(&self) -> usize;
+}
+
+/// A policy for checking and updating the enclave's sequence number for a proven message.
+#[async_trait::async_trait]
+pub trait SequencingPolicy<M>: Send + Sync + 'static {
+    async fn apply<T>(msg: &M, store: &T) -> Result<(), Status>
+    where
+        M: Sync,
+        T: Store;
+}
+
+/// The message is not sequenced, so the sequence number is neither checked nor updated.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unsequenced;
+
+#[async_trait::async_trait]
+impl<M> SequencingPolicy<M> for Unsequenced {
+    async fn apply<T>(_msg: &M, _store: &T) -> Result<(), Status>
+    where
+        M: Sync,
+        T: Store,
+    {
+        Ok(())
+    }
+}
+
+/// Checks that the on-chain sequence number is consistent with the enclave's sequence number and
+/// the number of pending sequenced requests, and then increments the enclave's sequence number.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SeqNumChecked;
+
+#[async_trait::async_trait]
+impl<M: SequencedMessage> SequencingPolicy<M> for SeqNumChecked {
+    async fn apply<T>(msg: &M, store: &T) -> Result<(), Status>
+    where
+        M: Sync,
+        T: Store,
+    {
+        let pending_sequenced_requests = msg.pending_sequenced_requests();
+        if pending_sequenced_requests == 0 {
+            return Ok(());
+        }
+
+        let seq_num = store
+            .get_seq_num()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        ensure_seq_num_consistency(seq_num, msg.seq_num(), pending_sequenced_requests)?;
+        store
+            .inc_seq_num(pending_sequenced_requests)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// A request containing a [`ProofOfPublication`] for a message of type `M`.
+///
+/// - `K` is the [`StorageKey`] that the message is published under.
+/// - `V` is the [`ValueMatcher`] used to match the proven value against the message.
+/// - `S` is the [`SequencingPolicy`] applied once the proof is verified.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProvenRequest<M, K, V = JsonValueMatch, S = Unsequenced> {
+    proof: ProofOfPublication<M>,
+    #[serde(skip)]
+    _phantom: PhantomData<(K, V, S)>,
+}
+
+impl<M, K, V, S> ProvenRequest<M, K, V, S> {
+    pub fn new(proof: ProofOfPublication<M>) -> Self {
+        Self {
+            proof,
+            _phantom: Default::default(),
+        }
+    }
+}
+
+/// A message that was successfully proven to have been published on-chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proven<M> {
+    msg: M,
+    height: Height,
+}
+
+impl<M> Proven<M> {
+    pub fn msg(&self) -> &M {
+        &self.msg
+    }
+
+    pub fn into_msg(self) -> M {
+        self.msg
+    }
+
+    /// The height of the light block that the message was proven against.
+    pub fn height(&self) -> Height {
+        self.height
+    }
+}
+
+#[async_trait::async_trait]
+impl<E, M, K, V, S> Handler<E> for ProvenRequest<M, K, V, S>
+where
+    E: Enclave,
+    E::Store: Store<Contract = AccountId, Height = Height, Hash = Hash>,
+    M: Send + Sync + 'static,
+    K: StorageKey,
+    V: ValueMatcher<M>,
+    S: SequencingPolicy<M>,
+{
+    type Error = Status;
+    type Response = Proven<M>;
+
+    async fn handle(self, ctx: &E) -> Result<Self::Response, Self::Error> {
+        let _guard = PROVEN_REQUEST_LOCK.lock().await;
+        let store = ctx.store().await;
+        ensure_not_halted(store).await?;
+
+        let contract = store
+            .get_contract()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("contract not found"))?;
+        let config = store
+            .get_config()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("config not found"))?;
+        let (trusted_height, trusted_hash) = store
+            .get_trusted_height_hash()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let (target_height, target_hash) = self.proof.target_height_hash();
+
+        debug!("Verifying proof of publication for storage key {}", K::KEY);
+        let (proof_value, msg, verified_headers) = self
+            .proof
+            .verify_with_headers(
+                config.light_client_opts(),
+                trusted_height,
+                trusted_hash,
+                contract,
+                K::KEY.to_string(),
+                K::NAMESPACE.map(ToString::to_string),
+            )
+            .map_err(Status::failed_precondition)?;
+        record_verified_headers(store, verified_headers).await?;
+
+        if !V::matches(&msg, &proof_value) {
+            error!("Proven value for storage key {} doesn't match msg", K::KEY);
+            return Err(Status::failed_precondition("proof verification"));
+        }
+
+        // ensure sequence number consistency
+        S::apply(&msg, store).await?;
+
+        // update trusted height and hash
+        store
+            .set_trusted_height_hash(target_height, target_hash)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Proven {
+            msg,
+            height: target_height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tendermint_light_client::types::LightBlock;
+
+    use super::*;
+    use crate::{
+        proof_of_publication::ProofOfPublication,
+        testing::{contract, enclave, light_blocks, merkle_proof, TestEnclave},
+    };
+
+    type TestRequest = ProvenRequest<TestMsg, TestKey, JsonValueMatch, SeqNumChecked>;
+
+    #[derive(Clone, Debug)]
+    struct TestKey;
+
+    impl StorageKey for TestKey {
+        const KEY: &'static str = "requests";
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestMsg {
+        requests: Vec<String>,
+        seq_num: u64,
+    }
+
+    impl HasProvenValue for TestMsg {
+        type Value = Vec<String>;
+
+        fn proven_value(&self) -> &Self::Value {
+            &self.requests
+        }
+    }
+
+    impl SequencedMessage for TestMsg {
+        fn seq_num(&self) -> u64 {
+            self.seq_num
+        }
+
+        fn pending_sequenced_requests(&self) -> usize {
+            self.requests.len()
+        }
+    }
+
+    fn requests(requests: &[&str]) -> Vec<String> {
+        requests.iter().map(ToString::to_string).collect()
+    }
+
+    /// Publishes `requests` in a chain of 3 light blocks, returning the chain and a request proving
+    /// that `msg` was published (from the first to the last block).
+    fn published(requests: Vec<String>, msg: TestMsg) -> (Vec<LightBlock>, TestRequest) {
+        let value = serde_json::to_vec(&requests).expect("infallible serializer");
+        let (merkle_proof, app_hash) = merkle_proof(contract(), TestKey::KEY, &value);
+        let light_blocks = light_blocks(&vec![app_hash; 3]);
+        let request = ProvenRequest::new(ProofOfPublication::new(
+            light_blocks.clone(),
+            merkle_proof,
+            msg,
+        ));
+        (light_blocks, request)
+    }
+
+    fn msg(requests: Vec<String>, seq_num: u64) -> TestMsg {
+        TestMsg { requests, seq_num }
+    }
+
+    async fn trusted_height_and_seq_num(enclave: &TestEnclave) -> (Height, u64) {
+        let store = enclave.store().await;
+        let (trusted_height, _) = store
+            .get_trusted_height_hash()
+            .await
+            .expect("infallible store");
+        let seq_num = store.get_seq_num().await.expect("infallible store");
+        (trusted_height, seq_num)
+    }
+
+    #[tokio::test]
+    async fn test_proven_request_updates_trusted_height_and_seq_num() {
+        let (light_blocks, request) =
+            published(requests(&["a", "b"]), msg(requests(&["a", "b"]), 2));
+        let enclave = enclave(&light_blocks[0]).await;
+
+        let proven = request.handle(&enclave).await.expect("valid proof");
+
+        let target = light_blocks.last().expect("non-empty chain");
+        assert_eq!(proven.height(), target.height());
+        assert_eq!(proven.msg().requests, requests(&["a", "b"]));
+        assert_eq!(
+            enclave
+                .store()
+                .await
+                .get_trusted_height_hash()
+                .await
+                .expect("infallible store"),
+            (target.height(), target.signed_header.header.hash())
+        );
+        assert_eq!(enclave.store().await.get_seq_num().await.ok(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_inconsistent_seq_num_is_rejected() {
+        let (light_blocks, request) = published(requests(&["a"]), msg(requests(&["a"]), 5));
+        let enclave = enclave(&light_blocks[0]).await;
+
+        request
+            .handle(&enclave)
+            .await
+            .expect_err("inconsistent seq num");
+        assert_eq!(
+            trusted_height_and_seq_num(&enclave).await,
+            (light_blocks[0].height(), 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_proof_leaves_state_unchanged() {
+        let (light_blocks, request) = published(requests(&["a"]), msg(requests(&["a"]), 1));
+        let enclave = enclave(&light_blocks[0]).await;
+
+        // the proven value doesn't match the message
+        let (_, forged) = published(requests(&["a"]), msg(requests(&["forged"]), 1));
+        forged.handle(&enclave).await.expect_err("value mismatch");
+        assert_eq!(
+            trusted_height_and_seq_num(&enclave).await,
+            (light_blocks[0].height(), 0)
+        );
+
+        // the lock is released after a rejected proof
+        request.handle(&enclave).await.expect("valid proof");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_proven_requests_are_serialized() {
+        let (light_blocks, request) = published(requests(&["a"]), msg(requests(&["a"]), 1));
+        let enclave = enclave(&light_blocks[0]).await;
+
+        // both requests are anchored at the same trusted height, so only the first one to take the
+        // lock can succeed
+        let (first, second) =
+            tokio::join!(request.clone().handle(&enclave), request.handle(&enclave));
+        assert!(first.is_ok() != second.is_ok());
+        assert_eq!(
+            trusted_height_and_seq_num(&enclave).await,
+            (light_blocks[2].height(), 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consecutive_proven_requests() {
+        let first_value = serde_json::to_vec(&requests(&["a"])).expect("infallible serializer");
+        let second_value = serde_json::to_vec(&requests(&["b"])).expect("infallible serializer");
+        let (first_proof, first_app_hash) = merkle_proof(contract(), TestKey::KEY, &first_value);
+        let (second_proof, second_app_hash) = merkle_proof(contract(), TestKey::KEY, &second_value);
+        let light_blocks = light_blocks(&[
+            first_app_hash.clone(),
+            first_app_hash.clone(),
+            first_app_hash,
+            second_app_hash.clone(),
+            second_app_hash,
+        ]);
+        let enclave = enclave(&light_blocks[0]).await;
+
+        let first: TestRequest = ProvenRequest::new(ProofOfPublication::new(
+            light_blocks[..3].to_vec(),
+            first_proof,
+            msg(requests(&["a"]), 1),
+        ));
+        first.handle(&enclave).await.expect("valid proof");
+
+        // a proof anchored at the initial trusted height is stale once the first one is consumed
+        let stale: TestRequest = ProvenRequest::new(ProofOfPublication::new(
+            light_blocks.clone(),
+            second_proof.clone(),
+            msg(requests(&["b"]), 2),
+        ));
+        stale.handle(&enclave).await.expect_err("stale anchor");
+
+        // the second proof must be anchored at the trusted height after the first one
+        let second: TestRequest = ProvenRequest::new(ProofOfPublication::new(
+            light_blocks[2..].to_vec(),
+            second_proof,
+            msg(requests(&["b"]), 2),
+        ));
+        second.handle(&enclave).await.expect("valid proof");
+        assert_eq!(
+            trusted_height_and_seq_num(&enclave).await,
+            (light_blocks[4].height(), 2)
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Support for running the enclave as one of several replicas, each run by a different operator
+//! (see the contract's `Quorum`).
+//!
+//! An app output only takes effect once a quorum of replicas submitted the exact same output, so
+//! replicas must compute deterministically over the same proven inputs. In particular, any
+//! randomness that ends up in an output (e.g. encryption nonces or ephemeral keys) must be drawn
+//! from a [`replica_rng`] instead of the OS RNG, e.g. by encrypting with [`encrypt_with_rng`]
+//! instead of [`ecies::encrypt`].
+
+use aes_gcm::{
+    aead::{consts::U16, AeadInPlace},
+    aes::Aes256,
+    AesGcm, KeyInit,
+};
+use ecies::{
+    config,
+    consts::{AEAD_TAG_LENGTH, EMPTY_BYTES, NONCE_LENGTH},
+};
+use hkdf::Hkdf;
+use k256::{elliptic_curve::sec1::ToEncodedPoint, PublicKey, SecretKey};
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tendermint::block::Height;
+
+use crate::proven_request::Proven;
+
+const SEED_TAG: &[u8] = b"quartz-replica-rng-v1";
+
+/// The RNG returned by [`replica_rng`].
+pub type ReplicaRng = ChaCha20Rng;
+
+/// Returns an RNG that is seeded from the session key and the proven inputs, so every replica that
+/// holds the session key derives the same RNG for the same inputs, while it remains unpredictable
+/// to anyone else.
+pub fn replica_rng(
+    session_key: &[u8],
+    height: Height,
+    inputs: &impl Serialize,
+) -> Result<ReplicaRng, serde_json::Error> {
+    let inputs = serde_json::to_vec(inputs)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(SEED_TAG);
+    for field in [session_key, &height.value().to_be_bytes(), &inputs] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
+    }
+
+    Ok(ReplicaRng::from_seed(hasher.finalize().into()))
+}
+
+/// Same as [`ecies::encrypt`] (with its current config, and decryptable with [`ecies::decrypt`]),
+/// but draws the ephemeral key and the nonce from the given RNG instead of the OS RNG.
+///
+/// `ecies` has no API that takes an RNG, so this follows its wire format: the ephemeral pub key,
+/// followed by the AES-256-GCM nonce, tag and ciphertext, the key being derived by HKDF-SHA256 from
+/// the ephemeral pub key and the shared point. The nonce and tag lengths are `ecies`' own, and the
+/// format is pinned by a fixed test vector that must decrypt with [`ecies::decrypt`].
+pub fn encrypt_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    receiver_pub: &[u8],
+    msg: &[u8],
+) -> Result<Vec<u8>, String> {
+    let receiver_pk = PublicKey::from_sec1_bytes(receiver_pub).map_err(|e| e.to_string())?;
+    let ephemeral_sk = SecretKey::random(rng);
+    let ephemeral_pk = ephemeral_sk.public_key();
+    let shared_point =
+        (receiver_pk.to_projective() * *ephemeral_sk.to_nonzero_scalar()).to_affine();
+
+    let hkdf_compressed = config::is_hkdf_key_compressed();
+    let mut sym_key = [0u8; 32];
+    Hkdf::<Sha256>::new(
+        None,
+        &[
+            ephemeral_pk.to_encoded_point(hkdf_compressed).as_bytes(),
+            shared_point.to_encoded_point(hkdf_compressed).as_bytes(),
+        ]
+        .concat(),
+    )
+    .expand(&[], &mut sym_key)
+    .expect("valid output length");
+
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rng.fill_bytes(&mut nonce);
+    let mut encrypted = msg.to_vec();
+    let tag = AesGcm::<Aes256, U16>::new(&sym_key.into())
+        .encrypt_in_place_detached(&nonce.into(), &EMPTY_BYTES, &mut encrypted)
+        .map_err(|e| e.to_string())?;
+    debug_assert_eq!(tag.len(), AEAD_TAG_LENGTH);
+
+    let ephemeral_pk = ephemeral_pk.to_encoded_point(config::is_ephemeral_key_compressed());
+    Ok([
+        ephemeral_pk.as_bytes(),
+        nonce.as_slice(),
+        tag.as_slice(),
+        encrypted.as_slice(),
+    ]
+    .concat())
+}
+
+impl<M: Serialize> Proven<M> {
+    /// The [`replica_rng`] for this proven message.
+    pub fn replica_rng(&self, session_key: &[u8]) -> Result<ReplicaRng, serde_json::Error> {
+        replica_rng(session_key, self.height(), self.msg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    fn draw(session_key: &[u8], height: u32, inputs: &[&str]) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        replica_rng(session_key, Height::from(height), &inputs)
+            .expect("serializable inputs")
+            .fill_bytes(&mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn test_replicas_encrypt_to_the_same_ciphertext_for_the_same_inputs() {
+        let sk = k256::ecdsa::SigningKey::random(&mut rand::thread_rng());
+        let pk = sk.verifying_key().to_sec1_bytes();
+        let encrypt = |session_key: &[u8]| {
+            let mut rng = replica_rng(session_key, Height::from(10u32), &["deposit"])
+                .expect("serializable inputs");
+            encrypt_with_rng(&mut rng, &pk, b"balance").expect("valid pub key")
+        };
+
+        let ciphertext = encrypt(&[1; 32]);
+        assert_eq!(ciphertext, encrypt(&[1; 32]));
+        assert_ne!(ciphertext, encrypt(&[2; 32]));
+        assert_eq!(
+            ecies::decrypt(&sk.to_bytes(), &ciphertext).expect("matching key"),
+            b"balance"
+        );
+    }
+
+    #[test]
+    fn test_encryption_matches_a_fixed_ecies_vector() {
+        let sk = SecretKey::from_slice(&[7; 32]).expect("valid secret key");
+        let pk = sk.public_key().to_sec1_bytes();
+        let mut rng = ReplicaRng::from_seed([1; 32]);
+
+        let ciphertext = encrypt_with_rng(&mut rng, &pk, b"balance").expect("valid pub key");
+        assert_eq!(
+            hex::encode(&ciphertext),
+            "04fa1f7356482c094a11420cba35bd3ff76d184b8002f06cfcc9e06867d359eee24c2445958377ddc5e9f9\
+             78178c01e3003d5c3322b77b6e251aa2699a35ba887e1ecc3686b60ee3b84b6c7d321d70d5c0f2640278d7\
+             e02ba727154c3d7a75403828403355acf2de"
+        );
+        assert_eq!(
+            ecies::decrypt(&sk.to_bytes(), &ciphertext).expect("matching key"),
+            b"balance"
+        );
+    }
+
+    #[test]
+    fn test_replicas_draw_the_same_randomness_for_the_same_inputs() {
+        let randomness = draw(&[1; 32], 10, &["deposit", "transfer"]);
+        assert_eq!(randomness, draw(&[1; 32], 10, &["deposit", "transfer"]));
+
+        assert_ne!(randomness, draw(&[2; 32], 10, &["deposit", "transfer"]));
+        assert_ne!(randomness, draw(&[1; 32], 11, &["deposit", "transfer"]));
+        assert_ne!(randomness, draw(&[1; 32], 10, &["transfer", "deposit"]));
+    }
+}
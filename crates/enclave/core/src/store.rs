@@ -1,4 +1,7 @@
 use quartz_contract_core::state::{Config, Nonce};
+use tendermint::block::signed_header::SignedHeader;
+
+use crate::misbehaviour::MisbehaviourEvidence;
 
 pub mod default;
 
@@ -53,4 +56,60 @@ pub trait Store: Send + Sync + 'static {
         height: Self::Height,
         hash: Self::Hash,
     ) -> Result<(Self::Height, Self::Hash), Self::Error>;
+
+    /// Retrieves the operator that this replica's outputs are attested for (if it was registered
+    /// as one of the contract's quorum operators).
+    async fn get_operator(&self) -> Result<Option<String>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Stores the operator that this replica's outputs are attested for.
+    async fn set_operator(&self, _operator: String) -> Result<Option<String>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Retrieves the signed header that was verified at the given height (if any).
+    ///
+    /// The default impl doesn't keep track of verified headers, which disables light client attack
+    /// detection (see [`crate::misbehaviour`]).
+    async fn get_verified_header(
+        &self,
+        _height: Self::Height,
+    ) -> Result<Option<SignedHeader>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Records a verified signed header, replacing the one at the same height (if any).
+    async fn set_verified_header(
+        &self,
+        _height: Self::Height,
+        _header: SignedHeader,
+    ) -> Result<Option<SignedHeader>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Forgets the headers verified below the given height.
+    async fn prune_verified_headers(&self, _below: Self::Height) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Retrieves the evidence of a light client attack (if one was detected).
+    async fn get_misbehaviour_evidence(&self) -> Result<Option<MisbehaviourEvidence>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Persists the evidence of a light client attack.
+    async fn set_misbehaviour_evidence(
+        &self,
+        _evidence: MisbehaviourEvidence,
+    ) -> Result<Option<MisbehaviourEvidence>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Removes the evidence of a light client attack, i.e. lifts the halt.
+    async fn clear_misbehaviour_evidence(
+        &self,
+    ) -> Result<Option<MisbehaviourEvidence>, Self::Error> {
+        Ok(None)
+    }
 }
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use cosmrs::AccountId;
 use displaydoc::Display;
@@ -6,11 +6,15 @@ use log::{debug, info, trace};
 use quartz_contract_core::state::{Config, Nonce};
 use serde::{Deserialize, Serialize};
 use serde_json::Error;
-use tendermint::{block::Height, Hash};
+use tendermint::{
+    block::{signed_header::SignedHeader, Height},
+    Hash,
+};
 use tokio::sync::RwLock;
 
 use crate::{
     backup_restore::{Export, Import},
+    misbehaviour::MisbehaviourEvidence,
     store::Store,
 };
 
@@ -19,10 +23,13 @@ use crate::{
 pub struct DefaultStore {
     config: Arc<RwLock<Option<Config>>>,
     contract: Arc<RwLock<Option<AccountId>>>,
+    operator: Arc<RwLock<Option<String>>>,
     nonce: Arc<RwLock<Option<Nonce>>>,
     seq_num: Arc<RwLock<u64>>,
     trusted_height: Arc<RwLock<Height>>,
     trusted_hash: Arc<RwLock<Hash>>,
+    verified_headers: Arc<RwLock<BTreeMap<Height, SignedHeader>>>,
+    misbehaviour_evidence: Arc<RwLock<Option<MisbehaviourEvidence>>>,
 }
 
 impl DefaultStore {
@@ -39,10 +46,13 @@ impl DefaultStore {
         DefaultStore {
             config: Arc::new(RwLock::new(Some(config))),
             contract: Default::default(),
+            operator: Default::default(),
             nonce: Default::default(),
             seq_num: Default::default(),
             trusted_height: Arc::new(RwLock::new(trusted_height.into())),
             trusted_hash: Arc::new(RwLock::new(Hash::Sha256(trusted_hash))),
+            verified_headers: Default::default(),
+            misbehaviour_evidence: Default::default(),
         }
     }
 }
@@ -50,6 +60,20 @@ impl DefaultStore {
 #[derive(Debug, Display)]
 pub enum StoreError {}
 
+#[derive(Debug, Display)]
+pub enum ImportError {
+    /// invalid store backup: {0}
+    Json(Error),
+    /// refusing to drop the misbehaviour evidence of the attack at height {0}
+    MisbehaviourEvidenceDropped(Height),
+}
+
+impl From<Error> for ImportError {
+    fn from(e: Error) -> Self {
+        Self::Json(e)
+    }
+}
+
 #[async_trait::async_trait]
 impl Store for DefaultStore {
     type Contract = AccountId;
@@ -129,31 +153,102 @@ impl Store for DefaultStore {
 
         Ok((prev_height, prev_hash))
     }
+
+    async fn get_verified_header(
+        &self,
+        height: Self::Height,
+    ) -> Result<Option<SignedHeader>, Self::Error> {
+        Ok(self.verified_headers.read().await.get(&height).cloned())
+    }
+
+    async fn set_verified_header(
+        &self,
+        height: Self::Height,
+        header: SignedHeader,
+    ) -> Result<Option<SignedHeader>, Self::Error> {
+        trace!("Recording verified header at height {height}");
+        Ok(self.verified_headers.write().await.insert(height, header))
+    }
+
+    async fn prune_verified_headers(&self, below: Self::Height) -> Result<(), Self::Error> {
+        let mut verified_headers = self.verified_headers.write().await;
+        *verified_headers = verified_headers.split_off(&below);
+        Ok(())
+    }
+
+    async fn get_operator(&self) -> Result<Option<String>, Self::Error> {
+        Ok(self.operator.read().await.clone())
+    }
+
+    async fn set_operator(&self, operator: String) -> Result<Option<String>, Self::Error> {
+        debug!("Setting enclave operator: {operator}");
+        Ok(self.operator.write().await.replace(operator))
+    }
+
+    async fn get_misbehaviour_evidence(&self) -> Result<Option<MisbehaviourEvidence>, Self::Error> {
+        Ok(self.misbehaviour_evidence.read().await.clone())
+    }
+
+    async fn set_misbehaviour_evidence(
+        &self,
+        evidence: MisbehaviourEvidence,
+    ) -> Result<Option<MisbehaviourEvidence>, Self::Error> {
+        debug!(
+            "Persisting misbehaviour evidence at height {}",
+            evidence.height()
+        );
+        Ok(self.misbehaviour_evidence.write().await.replace(evidence))
+    }
+
+    async fn clear_misbehaviour_evidence(
+        &self,
+    ) -> Result<Option<MisbehaviourEvidence>, Self::Error> {
+        debug!("Clearing misbehaviour evidence");
+        Ok(self.misbehaviour_evidence.write().await.take())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 struct StoreDTO {
     config: Option<Config>,
     contract: Option<AccountId>,
+    #[serde(default)]
+    operator: Option<String>,
     nonce: Option<Nonce>,
     seq_num: u64,
     height: Height,
     hash: Hash,
+    #[serde(default)]
+    verified_headers: BTreeMap<Height, SignedHeader>,
+    #[serde(default)]
+    misbehaviour_evidence: Option<MisbehaviourEvidence>,
 }
 
 #[async_trait::async_trait]
 impl Import for DefaultStore {
-    type Error = Error;
+    type Error = ImportError;
 
+    /// Fails if the enclave was halted but the backup has no misbehaviour evidence (e.g. because it
+    /// was taken before the attack), as restoring it would silently lift the halt.
     async fn import(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
         let dto: StoreDTO = serde_json::from_slice(&data)?;
 
+        if let (Some(evidence), None) = (
+            self.misbehaviour_evidence.read().await.as_ref(),
+            &dto.misbehaviour_evidence,
+        ) {
+            return Err(ImportError::MisbehaviourEvidenceDropped(evidence.height()));
+        }
+
         *self.config.write().await = dto.config;
         *self.contract.write().await = dto.contract;
+        *self.operator.write().await = dto.operator;
         *self.nonce.write().await = dto.nonce;
         *self.seq_num.write().await = dto.seq_num;
         *self.trusted_height.write().await = dto.height;
         *self.trusted_hash.write().await = dto.hash;
+        *self.verified_headers.write().await = dto.verified_headers;
+        *self.misbehaviour_evidence.write().await = dto.misbehaviour_evidence;
 
         Ok(())
     }
@@ -167,10 +262,13 @@ impl Export for DefaultStore {
         let dto = StoreDTO {
             config: self.config.read().await.clone(),
             contract: self.contract.read().await.clone(),
+            operator: self.operator.read().await.clone(),
             nonce: *self.nonce.read().await,
             seq_num: *self.seq_num.read().await,
             height: *self.trusted_height.read().await,
             hash: *self.trusted_hash.read().await,
+            verified_headers: self.verified_headers.read().await.clone(),
+            misbehaviour_evidence: self.misbehaviour_evidence.read().await.clone(),
         };
 
         Ok(serde_json::to_vec(&dto)?)
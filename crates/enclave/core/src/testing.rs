@@ -0,0 +1,179 @@
+//! Helpers for building enclaves, light client proofs and Merkle proofs of contract state in tests.
+
+use cosmrs::AccountId;
+use ics23::{
+    calculate_existence_root, commitment_proof::Proof as Ics23Proof, CommitmentProof,
+    ExistenceProof, HashOp, HostFunctionsManager, LeafOp, LengthOp,
+};
+use prost::Message;
+use quartz_contract_core::state::{Config, LightClientOpts};
+use quartz_cw_proof::proof::{cw::RawCwProof, key::CwAbciKey};
+use serde_json::json;
+use tendermint::{
+    merkle::proof::{ProofOp, ProofOps},
+    AppHash, Time,
+};
+use tendermint_light_client::types::LightBlock;
+use tendermint_testgen::{
+    light_block::TmLightBlock, Generator, Header, LightBlock as TestgenLightBlock, Validator,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    attestor::MockAttestor, key_manager::default::DefaultKeyManager, store::default::DefaultStore,
+    store::Store, DefaultEnclave,
+};
+
+pub(crate) const CHAIN_ID: &str = "test-chain";
+
+pub(crate) type TestEnclave = DefaultEnclave<(), MockAttestor, DefaultKeyManager, DefaultStore>;
+
+pub(crate) fn contract() -> AccountId {
+    AccountId::new("wasm", &[1; 20]).expect("valid account id")
+}
+
+/// Light client options that accept the testgen blocks (which are timestamped close to the unix
+/// epoch) anchored at the given light block.
+pub(crate) fn light_client_opts(anchor: &LightBlock) -> LightClientOpts {
+    LightClientOpts::new(
+        CHAIN_ID.to_string(),
+        anchor.height().value(),
+        anchor
+            .signed_header
+            .header
+            .hash()
+            .as_bytes()
+            .try_into()
+            .expect("sha256 hash"),
+        (1, 3),
+        100 * 365 * 24 * 60 * 60,
+        5,
+        10,
+    )
+    .expect("valid light client opts")
+}
+
+/// Creates an enclave paired with [`contract()`] that trusts the given light block.
+pub(crate) async fn enclave(anchor: &LightBlock) -> TestEnclave {
+    let config = Config::new([0; 32], light_client_opts(anchor), None, None);
+    let store = DefaultStore::new(config);
+    store
+        .set_contract(contract())
+        .await
+        .expect("infallible store");
+    let (notifier_tx, _) = mpsc::channel(1);
+
+    DefaultEnclave {
+        attestor: MockAttestor,
+        key_manager: DefaultKeyManager::default(),
+        store,
+        ctx: (),
+        notifier_tx,
+    }
+}
+
+/// Generates a chain of consecutive light blocks starting at height 1, the block at index `i`
+/// committing to `app_hashes[i]`.
+pub(crate) fn light_blocks(app_hashes: &[Vec<u8>]) -> Vec<LightBlock> {
+    light_blocks_at(1, 1, app_hashes)
+}
+
+/// Same as [`light_blocks`], but starting at the given height and (unix) time.
+pub(crate) fn light_blocks_at(
+    height: u64,
+    unix_time: i64,
+    app_hashes: &[Vec<u8>],
+) -> Vec<LightBlock> {
+    let validators = [
+        Validator::new("1").voting_power(50),
+        Validator::new("2").voting_power(50),
+    ];
+    let mut header = Header::new(&validators)
+        .chain_id(CHAIN_ID)
+        .next_validators(&validators)
+        .height(height)
+        .time(Time::from_unix_timestamp(unix_time, 0).expect("valid time"));
+
+    let mut light_blocks = vec![];
+    for (i, app_hash) in app_hashes.iter().enumerate() {
+        if i > 0 {
+            header = header.next();
+        }
+        header = header.app_hash(AppHash::try_from(app_hash.clone()).expect("valid app hash"));
+
+        let TmLightBlock {
+            signed_header,
+            validators,
+            next_validators,
+            provider,
+        } = TestgenLightBlock::new_default_with_header(header.clone())
+            .generate()
+            .expect("valid light block");
+        light_blocks.push(LightBlock::new(
+            signed_header,
+            validators,
+            next_validators,
+            provider,
+        ));
+    }
+    light_blocks
+}
+
+fn existence_proof(key: Vec<u8>, value: Vec<u8>, leaf_prefix: Vec<u8>) -> ExistenceProof {
+    ExistenceProof {
+        key,
+        value,
+        leaf: Some(LeafOp {
+            hash: HashOp::Sha256.into(),
+            prehash_key: HashOp::NoHash.into(),
+            prehash_value: HashOp::Sha256.into(),
+            length: LengthOp::VarProto.into(),
+            prefix: leaf_prefix,
+        }),
+        path: vec![],
+    }
+}
+
+fn proof_op(field_type: &str, proof: ExistenceProof) -> ProofOp {
+    ProofOp {
+        field_type: field_type.to_string(),
+        key: proof.key.clone(),
+        data: CommitmentProof {
+            proof: Some(Ics23Proof::Exist(proof)),
+        }
+        .encode_to_vec(),
+    }
+}
+
+/// Builds a Merkle proof of a contract storage item in a (single leaf) store, and returns it along
+/// with the app hash that it must be verified against.
+pub(crate) fn merkle_proof(
+    contract: AccountId,
+    storage_key: &str,
+    value: &[u8],
+) -> (RawCwProof, Vec<u8>) {
+    let key = CwAbciKey::new(contract, storage_key.to_string(), None).into_vec();
+
+    // a leaf at height 0 with size 1 and version 1 (as zig-zag varints)
+    let wasm_proof = existence_proof(key.clone(), value.to_vec(), vec![0, 2, 2]);
+    let wasm_root =
+        calculate_existence_root::<HostFunctionsManager>(&wasm_proof).expect("valid proof");
+    let app_proof = existence_proof(b"wasm".to_vec(), wasm_root, vec![0]);
+    let app_hash =
+        calculate_existence_root::<HostFunctionsManager>(&app_proof).expect("valid proof");
+
+    let proof = ProofOps {
+        ops: vec![
+            proof_op("ics23:iavl", wasm_proof),
+            proof_op("ics23:simple", app_proof),
+        ],
+    };
+    let raw_proof = serde_json::from_value(json!({
+        "key": hex::encode(key),
+        "value": hex::encode(value),
+        "proof": proof,
+    }))
+    .expect("valid raw proof");
+
+    (raw_proof, app_hash)
+}
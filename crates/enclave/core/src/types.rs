@@ -9,6 +9,7 @@ use hex::FromHexError;
 use quartz_contract_core::msg::{
     execute::{
         attested::{Attested, RawAttested},
+        register_operator::{RawRegisterOperator, RegisterOperator},
         session_create::{RawSessionCreate, SessionCreate},
         session_set_pub_key::{RawSessionSetPubKey, SessionSetPubKey},
     },
@@ -17,6 +18,7 @@ use quartz_contract_core::msg::{
 };
 use quartz_proto::quartz::{
     InstantiateResponse as RawInstantiateResponse,
+    RegisterOperatorResponse as RawRegisterOperatorResponse,
     SessionCreateResponse as RawSessionCreateResponse,
     SessionSetPubKeyResponse as RawSessionSetPubKeyResponse,
 };
@@ -114,6 +116,37 @@ where
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegisterOperatorResponse<A, RA> {
+    message: Attested<RegisterOperator, A>,
+    _phantom: PhantomData<RA>,
+}
+
+impl<A, RA> RegisterOperatorResponse<A, RA> {
+    pub fn new(message: Attested<RegisterOperator, A>) -> Self {
+        Self {
+            message,
+            _phantom: Default::default(),
+        }
+    }
+
+    pub fn into_message(self) -> Attested<RegisterOperator, A> {
+        self.message
+    }
+}
+
+impl<A, RA> From<RegisterOperatorResponse<A, RA>> for RawRegisterOperatorResponse
+where
+    RA: HasDomainType<DomainType = A> + Serialize,
+{
+    fn from(value: RegisterOperatorResponse<A, RA>) -> Self {
+        let raw_message: RawAttested<RawRegisterOperator, RA> = value.message.into();
+        Self {
+            message: serde_json::to_string(&raw_message).expect("infallible serializer"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Fmspc(pub [u8; 6]);
 
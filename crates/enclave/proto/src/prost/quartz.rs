@@ -26,6 +26,60 @@ pub struct SessionSetPubKeyResponse {
     #[prost(string, tag = "1")]
     pub message: ::prost::alloc::string::String,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReanchorRequest {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReanchorResponse {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegisterOperatorRequest {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegisterOperatorResponse {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct MisbehaviourRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MisbehaviourResponse {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HandoverOfferRequest {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HandoverOfferResponse {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HandoverExportRequest {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HandoverExportResponse {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HandoverImportRequest {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct HandoverImportResponse {}
 /// Generated client implementations.
 pub mod core_client {
     #![allow(
@@ -185,6 +239,143 @@ pub mod core_client {
                 .insert(GrpcMethod::new("quartz.Core", "SessionSetPubKey"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn reanchor(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReanchorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReanchorResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/quartz.Core/Reanchor");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("quartz.Core", "Reanchor"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn register_operator(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RegisterOperatorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RegisterOperatorResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quartz.Core/RegisterOperator",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("quartz.Core", "RegisterOperator"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn misbehaviour(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MisbehaviourRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::MisbehaviourResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/quartz.Core/Misbehaviour");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("quartz.Core", "Misbehaviour"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn handover_offer(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HandoverOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HandoverOfferResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quartz.Core/HandoverOffer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("quartz.Core", "HandoverOffer"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn handover_export(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HandoverExportRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HandoverExportResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quartz.Core/HandoverExport",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("quartz.Core", "HandoverExport"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn handover_import(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HandoverImportRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HandoverImportResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quartz.Core/HandoverImport",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("quartz.Core", "HandoverImport"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -221,6 +412,48 @@ pub mod core_server {
             tonic::Response<super::SessionSetPubKeyResponse>,
             tonic::Status,
         >;
+        async fn reanchor(
+            &self,
+            request: tonic::Request<super::ReanchorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReanchorResponse>,
+            tonic::Status,
+        >;
+        async fn register_operator(
+            &self,
+            request: tonic::Request<super::RegisterOperatorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RegisterOperatorResponse>,
+            tonic::Status,
+        >;
+        async fn misbehaviour(
+            &self,
+            request: tonic::Request<super::MisbehaviourRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::MisbehaviourResponse>,
+            tonic::Status,
+        >;
+        async fn handover_offer(
+            &self,
+            request: tonic::Request<super::HandoverOfferRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HandoverOfferResponse>,
+            tonic::Status,
+        >;
+        async fn handover_export(
+            &self,
+            request: tonic::Request<super::HandoverExportRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HandoverExportResponse>,
+            tonic::Status,
+        >;
+        async fn handover_import(
+            &self,
+            request: tonic::Request<super::HandoverImportRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HandoverImportResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct CoreServer<T> {
@@ -431,6 +664,272 @@ pub mod core_server {
                     };
                     Box::pin(fut)
                 }
+                "/quartz.Core/Reanchor" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReanchorSvc<T: Core>(pub Arc<T>);
+                    impl<T: Core> tonic::server::UnaryService<super::ReanchorRequest>
+                    for ReanchorSvc<T> {
+                        type Response = super::ReanchorResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReanchorRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Core>::reanchor(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ReanchorSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quartz.Core/RegisterOperator" => {
+                    #[allow(non_camel_case_types)]
+                    struct RegisterOperatorSvc<T: Core>(pub Arc<T>);
+                    impl<
+                        T: Core,
+                    > tonic::server::UnaryService<super::RegisterOperatorRequest>
+                    for RegisterOperatorSvc<T> {
+                        type Response = super::RegisterOperatorResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RegisterOperatorRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Core>::register_operator(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RegisterOperatorSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quartz.Core/Misbehaviour" => {
+                    #[allow(non_camel_case_types)]
+                    struct MisbehaviourSvc<T: Core>(pub Arc<T>);
+                    impl<T: Core> tonic::server::UnaryService<super::MisbehaviourRequest>
+                    for MisbehaviourSvc<T> {
+                        type Response = super::MisbehaviourResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MisbehaviourRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Core>::misbehaviour(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = MisbehaviourSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quartz.Core/HandoverOffer" => {
+                    #[allow(non_camel_case_types)]
+                    struct HandoverOfferSvc<T: Core>(pub Arc<T>);
+                    impl<
+                        T: Core,
+                    > tonic::server::UnaryService<super::HandoverOfferRequest>
+                    for HandoverOfferSvc<T> {
+                        type Response = super::HandoverOfferResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HandoverOfferRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Core>::handover_offer(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = HandoverOfferSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quartz.Core/HandoverExport" => {
+                    #[allow(non_camel_case_types)]
+                    struct HandoverExportSvc<T: Core>(pub Arc<T>);
+                    impl<
+                        T: Core,
+                    > tonic::server::UnaryService<super::HandoverExportRequest>
+                    for HandoverExportSvc<T> {
+                        type Response = super::HandoverExportResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HandoverExportRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Core>::handover_export(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = HandoverExportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quartz.Core/HandoverImport" => {
+                    #[allow(non_camel_case_types)]
+                    struct HandoverImportSvc<T: Core>(pub Arc<T>);
+                    impl<
+                        T: Core,
+                    > tonic::server::UnaryService<super::HandoverImportRequest>
+                    for HandoverImportSvc<T> {
+                        type Response = super::HandoverImportResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HandoverImportRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Core>::handover_import(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = HandoverImportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(
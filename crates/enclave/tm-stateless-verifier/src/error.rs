@@ -1,4 +1,4 @@
-use alloc::boxed::Box;
+use alloc::{boxed::Box, string::String};
 
 use displaydoc::Display;
 use tendermint::{block::Height, Hash};
@@ -10,10 +10,15 @@ use tendermint_light_client::{
 pub enum Error {
     /// empty trace
     EmptyTrace,
-    /// first block in trace does not match trusted (expected {expected:?}, found {found:?})
-    FirstTraceBlockNotTrusted {
-        expected: (Height, Hash),
-        found: (Height, Hash),
+    /// first block in trace is not at the trusted height (expected {expected}, found {found})
+    TrustedHeightMismatch { expected: Height, found: Height },
+    /// first block in trace does not match the trusted hash (expected {expected}, found {found})
+    TrustedHashMismatch { expected: Hash, found: Hash },
+    /// block at height {height} has an unexpected chain id (expected {expected}, found {found})
+    ChainIdMismatch {
+        height: Height,
+        expected: String,
+        found: String,
     },
     /// verification failure (`{0}`)
     VerificationFailure(Box<LightClientError>),
@@ -21,7 +21,6 @@ use crate::{error::Error, null_io::NullIo};
 /// A interface over a stateless light client instance.
 #[derive(Debug)]
 pub struct StatelessProvider {
-    #[allow(unused)]
     chain_id: String,
     instance: Instance,
 }
@@ -31,11 +30,32 @@ impl StatelessProvider {
         Self { chain_id, instance }
     }
 
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
     pub fn verify_to_height(&mut self, height: Height) -> Result<LightBlock, Error> {
-        self.instance
+        let light_block = self
+            .instance
             .light_client
             .verify_to_target(height, &mut self.instance.state)
-            .map_err(Into::<Error>::into)
+            .map_err(Into::<Error>::into)?;
+
+        // the trace was already checked in `make_provider()`, but be defensive about what we return
+        verify_chain_id(&light_block, &self.chain_id)?;
+
+        Ok(light_block)
+    }
+
+    /// Returns all light blocks that are trusted or were verified so far, ordered by height.
+    pub fn verified_blocks(&self) -> Vec<LightBlock> {
+        let light_store = &self.instance.state.light_store;
+        let mut blocks: Vec<_> = light_store
+            .all(Status::Trusted)
+            .chain(light_store.all(Status::Verified))
+            .collect();
+        blocks.sort_by_key(|lb| lb.height());
+        blocks
     }
 }
 
@@ -46,8 +66,12 @@ pub fn make_provider(
     trace: Vec<LightBlock>,
     options: Options,
 ) -> Result<StatelessProvider, Error> {
-    // Make sure the trace is not empty and that the first light block corresponds to trusted
+    // Make sure the trace is not empty, that the first light block corresponds to trusted and that
+    // all light blocks belong to the expected chain
     verify_trace_against_trusted(&trace, trusted_height, trusted_hash)?;
+    for light_block in &trace {
+        verify_chain_id(light_block, chain_id)?;
+    }
 
     let mut light_store = Box::new(MemoryStore::new());
 
@@ -85,14 +109,222 @@ fn verify_trace_against_trusted(
     };
 
     let first_height = first_block.signed_header.header.height;
+    if first_height != trusted_height {
+        return Err(Error::TrustedHeightMismatch {
+            expected: trusted_height,
+            found: first_height,
+        });
+    }
+
     let first_hash = first_block.signed_header.header.hash();
+    if first_hash != trusted_hash {
+        return Err(Error::TrustedHashMismatch {
+            expected: trusted_hash,
+            found: first_hash,
+        });
+    }
 
-    if first_height > trusted_height {
-        return Err(Error::FirstTraceBlockNotTrusted {
-            expected: (first_height, first_hash),
-            found: (trusted_height, trusted_hash),
+    Ok(())
+}
+
+fn verify_chain_id(light_block: &LightBlock, chain_id: &str) -> Result<(), Error> {
+    let header = &light_block.signed_header.header;
+    if header.chain_id.as_str() != chain_id {
+        return Err(Error::ChainIdMismatch {
+            height: header.height,
+            expected: chain_id.to_string(),
+            found: header.chain_id.to_string(),
         });
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec, vec::Vec};
+    use core::time::Duration;
+
+    use tendermint::Time;
+    use tendermint_light_client::types::TrustThreshold;
+    use tendermint_testgen::{
+        light_block::TmLightBlock, Generator, LightBlock as TestgenLightBlock,
+    };
+
+    use super::*;
+
+    const CHAIN_ID: &str = "test-chain";
+
+    fn options() -> Options {
+        Options {
+            trust_threshold: TrustThreshold::ONE_THIRD,
+            // testgen blocks are timestamped close to the unix epoch
+            trusting_period: Duration::from_secs(100 * 365 * 24 * 60 * 60),
+            clock_drift: Duration::from_secs(5),
+        }
+    }
+
+    fn into_light_block(tm_light_block: TmLightBlock) -> LightBlock {
+        LightBlock::new(
+            tm_light_block.signed_header,
+            tm_light_block.validators,
+            tm_light_block.next_validators,
+            tm_light_block.provider,
+        )
+    }
+
+    fn trace(len: u64) -> Vec<LightBlock> {
+        let mut testgen_block = TestgenLightBlock::new_default(1);
+        let mut trace = vec![];
+        for _ in 0..len {
+            trace.push(into_light_block(
+                testgen_block.generate().expect("valid light block"),
+            ));
+            testgen_block = testgen_block.next();
+        }
+        trace
+    }
+
+    fn anchor(trace: &[LightBlock]) -> (Height, Hash) {
+        let first_block = trace.first().expect("non-empty trace");
+        (
+            first_block.height(),
+            first_block.signed_header.header.hash(),
+        )
+    }
+
+    #[test]
+    fn test_valid_trace_verifies() {
+        let trace = trace(3);
+        let (trusted_height, trusted_hash) = anchor(&trace);
+        let target_height = trace.last().expect("non-empty trace").height();
+
+        let mut provider = make_provider(CHAIN_ID, trusted_height, trusted_hash, trace, options())
+            .expect("trace anchored at trusted");
+        let light_block = provider
+            .verify_to_height(target_height)
+            .expect("valid trace");
+        assert_eq!(light_block.height(), target_height);
+    }
+
+    #[test]
+    fn test_verified_blocks_include_anchor_and_target() {
+        let trace = trace(3);
+        let (trusted_height, trusted_hash) = anchor(&trace);
+        let target_height = trace.last().expect("non-empty trace").height();
+
+        let mut provider = make_provider(CHAIN_ID, trusted_height, trusted_hash, trace, options())
+            .expect("trace anchored at trusted");
+        provider
+            .verify_to_height(target_height)
+            .expect("valid trace");
+
+        let heights: Vec<_> = provider
+            .verified_blocks()
+            .iter()
+            .map(|lb| lb.height())
+            .collect();
+        assert_eq!(heights.first(), Some(&trusted_height));
+        assert_eq!(heights.last(), Some(&target_height));
+    }
+
+    #[test]
+    fn test_empty_trace_is_rejected() {
+        let (trusted_height, trusted_hash) = anchor(&trace(1));
+
+        let err = make_provider(CHAIN_ID, trusted_height, trusted_hash, vec![], options())
+            .expect_err("empty trace");
+        assert!(matches!(err, Error::EmptyTrace));
+    }
+
+    #[test]
+    fn test_trace_starting_below_trusted_height_is_rejected() {
+        let trace = trace(3);
+        let (trusted_height, trusted_hash) = anchor(&trace[1..]);
+
+        let err = make_provider(CHAIN_ID, trusted_height, trusted_hash, trace, options())
+            .expect_err("trace not anchored at trusted height");
+        assert!(matches!(
+            err,
+            Error::TrustedHeightMismatch { expected, found }
+                if expected == trusted_height && found.value() == 1
+        ));
+    }
+
+    #[test]
+    fn test_trace_starting_above_trusted_height_is_rejected() {
+        let trace = trace(3);
+        let (trusted_height, trusted_hash) = anchor(&trace);
+
+        let err = make_provider(
+            CHAIN_ID,
+            trusted_height,
+            trusted_hash,
+            trace[1..].to_vec(),
+            options(),
+        )
+        .expect_err("trace not anchored at trusted height");
+        assert!(matches!(err, Error::TrustedHeightMismatch { .. }));
+    }
+
+    #[test]
+    fn test_trace_with_forged_anchor_is_rejected() {
+        let mut trace = trace(3);
+        let (trusted_height, trusted_hash) = anchor(&trace);
+
+        // replace the anchor with a different block at the same height
+        let forged_anchor = TestgenLightBlock::new_default_with_time_and_chain_id(
+            CHAIN_ID.to_string(),
+            Time::from_unix_timestamp(42, 0).expect("valid time"),
+            trusted_height.value(),
+        );
+        trace[0] = into_light_block(forged_anchor.generate().expect("valid light block"));
+
+        let err = make_provider(CHAIN_ID, trusted_height, trusted_hash, trace, options())
+            .expect_err("trace anchor does not match trusted hash");
+        assert!(matches!(
+            err,
+            Error::TrustedHashMismatch { expected, .. } if expected == trusted_hash
+        ));
+    }
+
+    #[test]
+    fn test_trace_with_foreign_chain_id_is_rejected() {
+        let trace = trace(3);
+        let (trusted_height, trusted_hash) = anchor(&trace);
+
+        let err = make_provider(
+            "other-chain",
+            trusted_height,
+            trusted_hash,
+            trace,
+            options(),
+        )
+        .expect_err("trace belongs to a different chain");
+        assert!(matches!(err, Error::ChainIdMismatch { .. }));
+    }
+
+    #[test]
+    fn test_trace_with_forged_chain_id_block_is_rejected() {
+        let mut trace = trace(3);
+        let (trusted_height, trusted_hash) = anchor(&trace);
+
+        let last = trace.pop().expect("non-empty trace");
+        let forged_block = TestgenLightBlock::new_default_with_time_and_chain_id(
+            "other-chain".to_string(),
+            last.signed_header.header.time,
+            last.height().value(),
+        );
+        trace.push(into_light_block(
+            forged_block.generate().expect("valid light block"),
+        ));
+
+        let err = make_provider(CHAIN_ID, trusted_height, trusted_hash, trace, options())
+            .expect_err("trace contains a block from a different chain");
+        assert!(matches!(
+            err,
+            Error::ChainIdMismatch { height, ref found, .. }
+                if height == last.height() && found == "other-chain"
+        ));
+    }
+}
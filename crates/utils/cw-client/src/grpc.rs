@@ -34,6 +34,9 @@ use serde::de::DeserializeOwned;
 
 use crate::CwClient;
 
+/// The gRPC metadata key for querying the state at a given height (instead of the latest state).
+const BLOCK_HEIGHT_METADATA_KEY: &str = "x-cosmos-block-height";
+
 pub struct GrpcClient {
     sk: SigningKey,
     url: Url,
@@ -43,27 +46,43 @@ impl GrpcClient {
     pub fn new(sk: SigningKey, url: Url) -> Self {
         Self { sk, url }
     }
-}
 
-#[async_trait::async_trait]
-impl CwClient for GrpcClient {
-    type Address = AccountId;
-    type Query = serde_json::Value;
-    type RawQuery = String;
-    type ChainId = TmChainId;
-    type Error = anyhow::Error;
+    /// Same as [`CwClient::query_smart`], for the contract state at the given height.
+    pub async fn query_smart_at_height<R: DeserializeOwned + Send>(
+        &self,
+        contract: &AccountId,
+        query: serde_json::Value,
+        height: u64,
+    ) -> Result<R, anyhow::Error> {
+        self.smart_contract_state(contract, query, Some(height))
+            .await
+    }
 
-    async fn query_smart<R: DeserializeOwned + Send>(
+    /// Same as [`CwClient::query_raw`], for the contract state at the given height.
+    pub async fn query_raw_at_height<R: DeserializeOwned + Default>(
         &self,
-        contract: &Self::Address,
-        query: Self::Query,
-    ) -> Result<R, Self::Error> {
+        contract: &AccountId,
+        query: String,
+        height: u64,
+    ) -> Result<R, anyhow::Error> {
+        self.raw_contract_state(contract, query, Some(height)).await
+    }
+
+    async fn smart_contract_state<R: DeserializeOwned + Send>(
+        &self,
+        contract: &AccountId,
+        query: serde_json::Value,
+        height: Option<u64>,
+    ) -> Result<R, anyhow::Error> {
         let mut client = WasmdQueryClient::connect(self.url.to_string()).await?;
 
-        let raw_query_request = QuerySmartContractStateRequest {
-            address: contract.to_string(),
-            query_data: query.to_string().into_bytes(),
-        };
+        let raw_query_request = request_at_height(
+            QuerySmartContractStateRequest {
+                address: contract.to_string(),
+                query_data: query.to_string().into_bytes(),
+            },
+            height,
+        );
 
         let raw_query_response = client.smart_contract_state(raw_query_request).await?;
 
@@ -72,17 +91,21 @@ impl CwClient for GrpcClient {
             .map_err(|e| anyhow!("failed to deserialize JSON reponse: {}", e))
     }
 
-    async fn query_raw<R: DeserializeOwned + Default>(
+    async fn raw_contract_state<R: DeserializeOwned + Default>(
         &self,
-        contract: &Self::Address,
-        query: Self::RawQuery,
-    ) -> Result<R, Self::Error> {
+        contract: &AccountId,
+        query: String,
+        height: Option<u64>,
+    ) -> Result<R, anyhow::Error> {
         let mut client = WasmdQueryClient::connect(self.url.to_string()).await?;
 
-        let raw_query_request = QueryRawContractStateRequest {
-            address: contract.to_string(),
-            query_data: query.to_string().into_bytes(),
-        };
+        let raw_query_request = request_at_height(
+            QueryRawContractStateRequest {
+                address: contract.to_string(),
+                query_data: query.into_bytes(),
+            },
+            height,
+        );
 
         let raw_query_response = client.raw_contract_state(raw_query_request).await?;
 
@@ -90,6 +113,42 @@ impl CwClient for GrpcClient {
         serde_json::from_slice(&raw_value)
             .map_err(|e| anyhow!("failed to deserialize JSON reponse: {}", e))
     }
+}
+
+/// Wraps the query in a request for the state at `height` (or the latest state if `None`).
+fn request_at_height<T>(query: T, height: Option<u64>) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(query);
+    if let Some(height) = height {
+        request
+            .metadata_mut()
+            .insert(BLOCK_HEIGHT_METADATA_KEY, height.into());
+    }
+    request
+}
+
+#[async_trait::async_trait]
+impl CwClient for GrpcClient {
+    type Address = AccountId;
+    type Query = serde_json::Value;
+    type RawQuery = String;
+    type ChainId = TmChainId;
+    type Error = anyhow::Error;
+
+    async fn query_smart<R: DeserializeOwned + Send>(
+        &self,
+        contract: &Self::Address,
+        query: Self::Query,
+    ) -> Result<R, Self::Error> {
+        self.smart_contract_state(contract, query, None).await
+    }
+
+    async fn query_raw<R: DeserializeOwned + Default>(
+        &self,
+        contract: &Self::Address,
+        query: Self::RawQuery,
+    ) -> Result<R, Self::Error> {
+        self.raw_contract_state(contract, query, None).await
+    }
 
     fn query_tx<R: DeserializeOwned + Default>(&self, _txhash: &str) -> Result<R, Self::Error> {
         unimplemented!()
@@ -1,4 +1,8 @@
-use std::{num::ParseIntError, str::FromStr};
+use std::{
+    fmt::{Display, Formatter},
+    num::ParseIntError,
+    str::FromStr,
+};
 
 use clap::Parser;
 use color_eyre::eyre::{eyre, Result};
@@ -33,6 +37,26 @@ impl<E, T: FromStr<Err = E>> FromStr for List<T> {
     }
 }
 
+/// What to do when fewer than the required number of distinct witnesses are reachable
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WitnessPolicy {
+    /// Fail proof generation
+    Enforce,
+    /// Log a warning and generate the proof anyway
+    #[default]
+    Warn,
+}
+
+impl Display for WitnessPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WitnessPolicy::Enforce => write!(f, "enforce"),
+            WitnessPolicy::Warn => write!(f, "warn"),
+        }
+    }
+}
+
 #[derive(clap::Args, Debug, Clone)]
 pub struct Verbosity {
     /// Increase verbosity, can be repeated up to 2 times
@@ -76,6 +100,8 @@ impl Default for Config {
             chain_id: String::default(),
             primary: "http://127.0.0.1:26657".parse().unwrap(),
             witnesses: "http://127.0.0.1:26657".parse().unwrap(),
+            min_witnesses: 1,
+            witness_policy: WitnessPolicy::default(),
             trusted_height: Height::default(),
             trusted_hash: Hash::default(),
             trust_threshold: TrustThreshold::TWO_THIRDS,
@@ -88,6 +114,7 @@ impl Default for Config {
                 .unwrap(),
             storage_key: String::default(),
             storage_namespace: None,
+            proof_height: None,
         }
     }
 }
@@ -107,6 +134,14 @@ pub struct Config {
     #[clap(long)]
     pub witnesses: List<HttpClientUrl>,
 
+    /// Minimum number of distinct witnesses (i.e. other than the primary) that must be reachable
+    #[clap(long, default_value = "1")]
+    pub min_witnesses: usize,
+
+    /// What to do when fewer than `min_witnesses` distinct witnesses are reachable
+    #[clap(long, value_enum, default_value_t = WitnessPolicy::Warn)]
+    pub witness_policy: WitnessPolicy,
+
     /// Height of trusted header
     #[clap(long)]
     pub trusted_height: Height,
@@ -147,4 +182,8 @@ pub struct Config {
     /// (only makes sense when dealing with maps)
     #[clap(long)]
     pub storage_namespace: Option<String>,
+
+    /// Height at which the state must be proven (defaults to the latest height - 1)
+    #[clap(long)]
+    pub proof_height: Option<Height>,
 }
@@ -7,12 +7,13 @@
 )]
 #![forbid(unsafe_code)]
 
-use std::time::Duration;
+use std::{collections::HashSet, time::Duration};
 
 use color_eyre::{
     eyre::{eyre, Result},
     Report,
 };
+use cosmrs::AccountId;
 use futures::future::join_all;
 use quartz_cw_proof::{
     error::ProofError,
@@ -26,121 +27,342 @@ use tendermint_light_client::{
     types::{Height, LightBlock},
 };
 use tendermint_light_client_detector::{detect_divergence, Error, Provider, Trace};
-use tendermint_rpc::{client::HttpClient, Client, HttpClientUrl};
-use tracing::{error, info};
+use tendermint_rpc::{
+    client::{CompatMode, HttpClient},
+    endpoint::status,
+    Client, HttpClientUrl,
+};
+use tracing::{error, info, warn};
 
 const WASM_STORE_KEY: &str = "/store/wasm/key";
 
-use crate::config::{Config as TmProverConfig, ProofOutput};
-
-pub async fn prove(
-    TmProverConfig {
-        chain_id,
-        primary,
-        witnesses,
-        trusted_height,
-        trusted_hash,
-        trust_threshold,
-        trusting_period,
-        max_clock_drift,
-        max_block_lag,
-        verbose: _,
-        contract_address,
-        storage_key,
-        storage_namespace,
-    }: TmProverConfig,
-) -> Result<ProofOutput> {
-    let options = Options {
-        trust_threshold,
-        trusting_period: Duration::from_secs(trusting_period),
-        clock_drift: Duration::from_secs(max_clock_drift),
-    };
+use crate::config::{Config as TmProverConfig, ProofOutput, WitnessPolicy};
+
+/// Generates a one-off proof for the given config.
+///
+/// Use a [`Prover`] instead to generate multiple proofs without re-verifying from the trusted
+/// height every time.
+pub async fn prove(config: TmProverConfig) -> Result<ProofOutput> {
+    let mut prover = Prover::new(&config).await?;
+    prover
+        .prove(
+            config.trusted_height,
+            config.trusted_hash,
+            config.contract_address,
+            config.storage_key,
+            config.storage_namespace,
+            config.proof_height,
+        )
+        .await
+}
 
-    let mut provider = make_provider(
-        &chain_id,
-        primary.clone(),
-        trusted_height,
-        trusted_hash,
-        options,
-    )
-    .await?;
-
-    let client = HttpClient::builder(primary.clone()).build()?;
-
-    let trusted_block = provider
-        .latest_trusted()
-        .ok_or_else(|| eyre!("No trusted state found for primary"))?;
-
-    info!("Getting status of node");
-    let status = client.status().await?;
-    let latest_height = status.sync_info.latest_block_height;
-    let latest_app_hash = status.sync_info.latest_app_hash;
-
-    // `proof_height` is the height at which we want to query the blockchain's state
-    // This is one less than than the `latest_height` because we want to verify the merkle-proof for
-    // the state against the `app_hash` at `latest_height`.
-    // (because Tendermint commits to the latest `app_hash` in the subsequent block)
-    let proof_height = (latest_height.value() - 1)
-        .try_into()
-        .expect("infallible conversion");
-
-    info!("Verifying to latest height on primary...");
-
-    let primary_block = provider.verify_to_height(latest_height)?;
-
-    info!("Verified to height {} on primary", primary_block.height());
-    let mut primary_trace = provider.get_trace(primary_block.height());
-    let witnesses = join_all(witnesses.0.into_iter().map(|addr: HttpClientUrl| {
-        make_provider(
-            &chain_id,
-            addr,
-            trusted_block.height(),
-            trusted_block.signed_header.header.hash(),
+/// A long-lived prover that keeps the light blocks it verified across proofs.
+///
+/// Every proof is anchored at the trusted height and hash that the caller passes in, which must be
+/// the enclave's current trusted state. If that is the latest light block verified by the primary
+/// (i.e. the enclave consumed the previous proof), verification continues from there. Otherwise the
+/// primary (along with the witnesses) is re-created at the given anchor, so a proof that was never
+/// consumed doesn't prevent generating new ones.
+pub struct Prover {
+    chain_id: String,
+    primary_addr: HttpClientUrl,
+    /// Only `None` if verification on the primary panicked, in which case it's re-created for the
+    /// next proof.
+    primary: Option<Provider>,
+    latest: LightBlock,
+    client: HttpClient,
+    witness_addrs: Vec<HttpClientUrl>,
+    /// The distinct, reachable witnesses, trusted at the same light block as the primary.
+    witnesses: Vec<Provider>,
+    min_witnesses: usize,
+    witness_policy: WitnessPolicy,
+    options: Options,
+    max_clock_drift: Duration,
+    max_block_lag: Duration,
+}
+
+impl Prover {
+    /// Creates a new prover that trusts the primary at the configured trusted height and hash.
+    ///
+    /// Only the chain, node and light client options of the config are used, the storage item
+    /// to prove is specified per proof.
+    pub async fn new(config: &TmProverConfig) -> Result<Self> {
+        let options = Options {
+            trust_threshold: config.trust_threshold,
+            trusting_period: Duration::from_secs(config.trusting_period),
+            clock_drift: Duration::from_secs(config.max_clock_drift),
+        };
+
+        let primary = make_provider(
+            &config.chain_id,
+            config.primary.clone(),
+            config.trusted_height,
+            config.trusted_hash,
             options,
         )
-    }))
-    .await;
+        .await?;
 
-    let mut witnesses = witnesses.into_iter().collect::<Result<Vec<_>>>()?;
+        let latest = primary
+            .latest_trusted()
+            .ok_or_else(|| eyre!("No trusted state found for primary"))?;
+        let witnesses = make_witnesses(
+            &config.chain_id,
+            &primary,
+            &config.witnesses.0,
+            config.trusted_height,
+            config.trusted_hash,
+            options,
+        )
+        .await;
 
-    let max_clock_drift = Duration::from_secs(max_clock_drift);
-    let max_block_lag = Duration::from_secs(max_block_lag);
+        let client = make_client(config.primary.clone()).await?;
+
+        Ok(Self {
+            chain_id: config.chain_id.clone(),
+            primary_addr: config.primary.clone(),
+            primary: Some(primary),
+            latest,
+            client,
+            witness_addrs: config.witnesses.0.clone(),
+            witnesses,
+            min_witnesses: config.min_witnesses,
+            witness_policy: config.witness_policy,
+            options,
+            max_clock_drift: Duration::from_secs(config.max_clock_drift),
+            max_block_lag: Duration::from_secs(config.max_block_lag),
+        })
+    }
 
-    run_detector(
-        &mut provider,
-        witnesses.as_mut_slice(),
-        primary_trace.clone(),
-        max_clock_drift,
-        max_block_lag,
-    )
-    .await?;
+    /// Generates a proof for the given storage item at `proof_height` (defaults to the latest
+    /// height - 1), anchored at the given trusted height and hash.
+    pub async fn prove(
+        &mut self,
+        trusted_height: Height,
+        trusted_hash: Hash,
+        contract_address: AccountId,
+        storage_key: String,
+        storage_namespace: Option<String>,
+        proof_height: Option<Height>,
+    ) -> Result<ProofOutput> {
+        let trusted_block = self.anchor(trusted_height, trusted_hash).await?;
+
+        info!("Getting status of node");
+        let status = self.client.status().await?;
+        let latest_height = status.sync_info.latest_block_height;
+
+        // `proof_height` is the height at which we want to query the blockchain's state.
+        // Tendermint commits to the `app_hash` resulting from a block in the subsequent block, so
+        // the merkle-proof for the state at `proof_height` is verified against the `app_hash` in
+        // the header at `target_height` = `proof_height + 1`.
+        // By default, this is the latest state that can be proven (i.e. `latest_height - 1`).
+        let (proof_height, target_height) = match proof_height {
+            Some(proof_height) => (proof_height, proof_height.increment()),
+            None => (
+                (latest_height.value() - 1)
+                    .try_into()
+                    .expect("infallible conversion"),
+                latest_height,
+            ),
+        };
+
+        if target_height > latest_height {
+            return Err(eyre!(
+                "Cannot prove state at height {proof_height}, latest height is {latest_height}"
+            ));
+        }
+
+        if target_height < trusted_block.height() {
+            return Err(eyre!(
+                "Cannot prove state at height {proof_height}, trusted height is {}",
+                trusted_block.height()
+            ));
+        }
+
+        let (primary_block, primary_trace) =
+            self.verify_primary(&trusted_block, target_height).await?;
+        let target_app_hash = primary_block.signed_header.header.app_hash.clone();
+
+        ensure_min_witnesses(
+            self.witnesses.len(),
+            self.min_witnesses,
+            self.witness_policy,
+        )?;
+
+        if self.witnesses.is_empty() {
+            warn!("No witnesses available, skipping misbehavior detection");
+        } else {
+            run_detector(
+                self.primary.as_mut().expect("primary verified above"),
+                self.witnesses.as_mut_slice(),
+                primary_trace.clone(),
+                self.max_clock_drift,
+                self.max_block_lag,
+            )
+            .await?;
+        }
+
+        let path = WASM_STORE_KEY.to_owned();
+        let data = CwAbciKey::new(contract_address, storage_key, storage_namespace);
+        let result = self
+            .client
+            .abci_query(Some(path), data, Some(proof_height), true)
+            .await?;
+
+        let proof: CwProof = result
+            .clone()
+            .try_into()
+            .map_err(|_| eyre!("ABCI query response at height {proof_height} has no proof"))?;
+        proof
+            .verify(target_app_hash.into())
+            .map_err(|e: ProofError| eyre!(e))?;
+
+        let output = ProofOutput {
+            light_client_proof: primary_trace,
+            merkle_proof: proof.into(),
+        };
 
-    let path = WASM_STORE_KEY.to_owned();
-    let data = CwAbciKey::new(contract_address, storage_key, storage_namespace);
-    let result = client
-        .abci_query(Some(path), data, Some(proof_height), true)
+        Ok(output)
+    }
+
+    /// Verifies the primary from the trusted block (see [`Prover::anchor`]) to the target height and
+    /// returns the target light block along with the trace from the trusted block.
+    async fn verify_primary(
+        &mut self,
+        trusted_block: &LightBlock,
+        target_height: Height,
+    ) -> Result<(LightBlock, Vec<LightBlock>)> {
+        info!(
+            "Verifying from height {} to height {target_height} on primary...",
+            trusted_block.height()
+        );
+
+        // the light client fetches light blocks synchronously, so it must not block the runtime
+        let mut primary = self
+            .primary
+            .take()
+            .ok_or_else(|| eyre!("No primary to verify on"))?;
+        let (primary, verified) = tokio::task::spawn_blocking(move || {
+            let verified = primary.verify_to_height(target_height);
+            (primary, verified)
+        })
         .await?;
+        let primary = self.primary.insert(primary);
+        let primary_block = verified?;
 
-    let proof: CwProof = result
-        .clone()
-        .try_into()
-        .expect("result should contain proof");
-    proof
-        .verify(latest_app_hash.clone().into())
-        .map_err(|e: ProofError| eyre!(e))?;
-
-    // Replace the last block in the trace (i.e., the (latest - 1) block) with the latest block
-    // We don't actually verify the latest block because it will be verified on the other side
-    let latest_block = provider.fetch_light_block(latest_height)?;
-    let _ = primary_trace.pop();
-    primary_trace.push(latest_block);
-
-    let output = ProofOutput {
-        light_client_proof: primary_trace,
-        merkle_proof: proof.into(),
-    };
+        info!("Verified to height {} on primary", primary_block.height());
+        let mut primary_trace = primary.get_trace(primary_block.height());
+
+        // The trace might contain blocks verified for earlier proofs, but the stateless verifier
+        // requires it to be anchored at the trusted block
+        primary_trace.retain(|lb| lb.height() >= trusted_block.height());
+        if primary_trace.first().map(|lb| lb.height()) != Some(trusted_block.height()) {
+            primary_trace.insert(0, trusted_block.clone());
+        }
+
+        self.latest = primary_block.clone();
+
+        Ok((primary_block, primary_trace))
+    }
+
+    /// Returns the light block at the given trusted height and hash, re-creating the primary and the
+    /// witnesses there unless it is the latest light block the primary verified.
+    async fn anchor(&mut self, trusted_height: Height, trusted_hash: Hash) -> Result<LightBlock> {
+        if self.primary.is_some()
+            && self.latest.height() == trusted_height
+            && self.latest.signed_header.header.hash() == trusted_hash
+        {
+            return Ok(self.latest.clone());
+        }
+
+        info!("Re-anchoring primary at height {trusted_height}");
+        let primary = make_provider(
+            &self.chain_id,
+            self.primary_addr.clone(),
+            trusted_height,
+            trusted_hash,
+            self.options,
+        )
+        .await?;
+        self.latest = primary
+            .latest_trusted()
+            .ok_or_else(|| eyre!("No trusted state found for primary"))?;
+        self.witnesses = make_witnesses(
+            &self.chain_id,
+            &primary,
+            &self.witness_addrs,
+            trusted_height,
+            trusted_hash,
+            self.options,
+        )
+        .await;
+        self.primary = Some(primary);
+
+        Ok(self.latest.clone())
+    }
+}
+
+/// Creates the witnesses at the given trusted height and hash, keeping only the distinct and
+/// reachable ones (see [`distinct_witnesses`]).
+async fn make_witnesses(
+    chain_id: &str,
+    primary: &Provider,
+    addrs: &[HttpClientUrl],
+    trusted_height: Height,
+    trusted_hash: Hash,
+    options: Options,
+) -> Vec<Provider> {
+    let witnesses = join_all(
+        addrs
+            .iter()
+            .cloned()
+            .map(|addr| make_provider(chain_id, addr, trusted_height, trusted_hash, options)),
+    )
+    .await;
+
+    distinct_witnesses(primary, witnesses)
+}
+
+/// Drops the witnesses that are unreachable or that are the same node as the primary (or as another
+/// witness), since cross-checking a node against itself doesn't detect anything.
+fn distinct_witnesses(primary: &Provider, witnesses: Vec<Result<Provider>>) -> Vec<Provider> {
+    let mut seen = HashSet::from([*primary.peer_id()]);
+
+    witnesses
+        .into_iter()
+        .filter_map(|witness| match witness {
+            Ok(witness) if seen.insert(*witness.peer_id()) => Some(witness),
+            Ok(witness) => {
+                warn!("ignoring duplicate witness {}", witness.peer_id());
+                None
+            }
+            Err(e) => {
+                warn!("ignoring unreachable witness: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+fn ensure_min_witnesses(
+    available: usize,
+    min_witnesses: usize,
+    policy: WitnessPolicy,
+) -> Result<()> {
+    if available >= min_witnesses {
+        return Ok(());
+    }
 
-    Ok(output)
+    match policy {
+        WitnessPolicy::Enforce => Err(eyre!(
+            "only {available} distinct witnesses reachable, at least {min_witnesses} required"
+        )),
+        WitnessPolicy::Warn => {
+            warn!(
+                "only {available} distinct witnesses reachable, at least {min_witnesses} expected"
+            );
+            Ok(())
+        }
+    }
 }
 
 async fn run_detector(
@@ -216,11 +438,7 @@ async fn make_provider(
     trusted_hash: Hash,
     options: Options,
 ) -> Result<Provider> {
-    use tendermint_rpc::client::CompatMode;
-
-    let rpc_client = HttpClient::builder(rpc_addr)
-        .compat_mode(CompatMode::V0_34)
-        .build()?;
+    let rpc_client = make_client(rpc_addr).await?;
 
     let node_id = rpc_client.status().await?.node_info.id;
     let light_store = Box::new(MemoryStore::new());
@@ -232,3 +450,226 @@ async fn make_provider(
 
     Ok(Provider::new(chain_id.to_string(), instance, rpc_client))
 }
+
+/// Creates an RPC client for the given node, using the compat mode matching its CometBFT version.
+async fn make_client(rpc_addr: HttpClientUrl) -> Result<HttpClient> {
+    // the `/status` endpoint is the same across all supported versions
+    let status = HttpClient::builder(rpc_addr.clone())
+        .build()?
+        .status()
+        .await?;
+    let compat_mode = compat_mode_from_status(&status)?;
+    info!(
+        "Using compat mode {compat_mode} for node {}",
+        status.node_info.id
+    );
+
+    let rpc_client = HttpClient::builder(rpc_addr)
+        .compat_mode(compat_mode)
+        .build()?;
+    Ok(rpc_client)
+}
+
+fn compat_mode_from_status(status: &status::Response) -> Result<CompatMode> {
+    CompatMode::from_version(status.node_info.version.clone()).map_err(|e| {
+        eyre!(
+            "Unsupported CometBFT version {} on node {}: {e}",
+            status.node_info.version,
+            status.node_info.id
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tendermint::Time;
+    use tendermint_light_client::{
+        components::{
+            clock::SystemClock,
+            io::{AtHeight, IoError},
+            scheduler,
+        },
+        predicates::ProdPredicates,
+        store::LightStore,
+        types::{Status, TrustThreshold},
+        verifier::ProdVerifier,
+    };
+    use tendermint_testgen::{
+        light_block::TmLightBlock, Generator, Header, LightBlock as TestgenLightBlock, Validator,
+    };
+
+    use super::*;
+
+    /// A node that can't be reached, so that re-creating a provider for it fails.
+    const UNREACHABLE_NODE: &str = "http://127.0.0.1:1";
+
+    fn light_blocks(count: u64) -> Vec<LightBlock> {
+        let validators = [
+            Validator::new("1").voting_power(50),
+            Validator::new("2").voting_power(50),
+        ];
+        let mut header = Header::new(&validators)
+            .chain_id("test-chain")
+            .next_validators(&validators)
+            .height(1)
+            .time(Time::from_unix_timestamp(1, 0).expect("valid time"));
+
+        (0..count)
+            .map(|i| {
+                if i > 0 {
+                    header = header.next();
+                }
+                let TmLightBlock {
+                    signed_header,
+                    validators,
+                    next_validators,
+                    provider,
+                } = TestgenLightBlock::new_default_with_header(header.clone())
+                    .generate()
+                    .expect("valid light block");
+                LightBlock::new(signed_header, validators, next_validators, provider)
+            })
+            .collect()
+    }
+
+    /// A prover whose primary trusts the first of the given light blocks and can only verify the
+    /// others from its light store (i.e. without fetching anything from the node).
+    fn prover(light_blocks: &[LightBlock]) -> Prover {
+        let options = Options {
+            trust_threshold: TrustThreshold::ONE_THIRD,
+            trusting_period: Duration::from_secs(100 * 365 * 24 * 60 * 60),
+            clock_drift: Duration::from_secs(5),
+        };
+
+        let mut light_store = Box::new(MemoryStore::new());
+        for light_block in light_blocks {
+            light_store.insert(light_block.clone(), Status::Unverified);
+        }
+        let io = |_: AtHeight| Err(IoError::invalid_height());
+        let instance = LightClientBuilder::custom(
+            light_blocks[0].provider,
+            options,
+            light_store,
+            Box::new(io),
+            Box::new(SystemClock),
+            #[allow(clippy::box_default)]
+            Box::new(ProdVerifier::default()),
+            Box::new(scheduler::basic_bisecting_schedule),
+            Box::new(ProdPredicates),
+        )
+        .trust_light_block(light_blocks[0].clone())
+        .expect("trusted light block")
+        .build();
+        let client = HttpClient::new(UNREACHABLE_NODE).expect("valid url");
+
+        Prover {
+            chain_id: "test-chain".to_string(),
+            primary_addr: UNREACHABLE_NODE.parse().expect("valid url"),
+            primary: Some(Provider::new(
+                "test-chain".to_string(),
+                instance,
+                client.clone(),
+            )),
+            latest: light_blocks[0].clone(),
+            client,
+            witness_addrs: vec![],
+            witnesses: vec![],
+            min_witnesses: 0,
+            witness_policy: WitnessPolicy::Warn,
+            options,
+            max_clock_drift: Duration::from_secs(5),
+            max_block_lag: Duration::from_secs(5),
+        }
+    }
+
+    fn height_hash(light_block: &LightBlock) -> (Height, Hash) {
+        (
+            light_block.height(),
+            light_block.signed_header.header.hash(),
+        )
+    }
+
+    fn recorded_status(response: &str) -> status::Response {
+        <status::Response as tendermint_rpc::Response>::from_string(response)
+            .expect("valid recorded status response")
+    }
+
+    #[test]
+    fn test_compat_mode_v0_34() {
+        let status = recorded_status(include_str!("../data/status_v0_34.json"));
+        assert_eq!(
+            compat_mode_from_status(&status).expect("supported version"),
+            CompatMode::V0_34
+        );
+    }
+
+    #[test]
+    fn test_compat_mode_v0_37() {
+        let status = recorded_status(include_str!("../data/status_v0_37.json"));
+        assert_eq!(
+            compat_mode_from_status(&status).expect("supported version"),
+            CompatMode::V0_37
+        );
+    }
+
+    #[test]
+    fn test_compat_mode_v0_38() {
+        let status = recorded_status(include_str!("../data/status_v0_38.json"));
+        assert_eq!(
+            compat_mode_from_status(&status).expect("supported version"),
+            CompatMode::V0_38
+        );
+    }
+
+    #[test]
+    fn test_compat_mode_unsupported_version() {
+        let status = recorded_status(include_str!("../data/status_v1_0.json"));
+        assert!(compat_mode_from_status(&status).is_err());
+    }
+
+    #[test]
+    fn test_enough_witnesses() {
+        assert!(ensure_min_witnesses(2, 2, WitnessPolicy::Enforce).is_ok());
+        assert!(ensure_min_witnesses(3, 2, WitnessPolicy::Enforce).is_ok());
+    }
+
+    #[test]
+    fn test_too_few_witnesses_by_policy() {
+        assert!(ensure_min_witnesses(1, 2, WitnessPolicy::Enforce).is_err());
+        assert!(ensure_min_witnesses(0, 1, WitnessPolicy::Warn).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_second_proof_reuses_the_light_store() {
+        let light_blocks = light_blocks(5);
+        let mut prover = prover(&light_blocks);
+
+        let (height, hash) = height_hash(&light_blocks[0]);
+        let trusted_block = prover.anchor(height, hash).await.expect("trusted block");
+        let (verified, trace) = prover
+            .verify_primary(&trusted_block, light_blocks[2].height())
+            .await
+            .expect("verified from the light store");
+        assert_eq!(height_hash(&verified), height_hash(&light_blocks[2]));
+        assert_eq!(trace.first().map(height_hash), Some((height, hash)));
+
+        // once the caller consumed the first proof, the second one continues from its target,
+        // without re-creating the primary (which would require reaching the node)
+        let (height, hash) = height_hash(&verified);
+        let trusted_block = prover.anchor(height, hash).await.expect("same primary");
+        let (verified, trace) = prover
+            .verify_primary(&trusted_block, light_blocks[4].height())
+            .await
+            .expect("verified from the light store");
+        assert_eq!(height_hash(&verified), height_hash(&light_blocks[4]));
+        assert_eq!(trace.first().map(height_hash), Some((height, hash)));
+        assert_eq!(trace.last().map(height_hash), Some(height_hash(&verified)));
+
+        // anchoring anywhere else re-creates the primary
+        let (height, hash) = height_hash(&light_blocks[1]);
+        assert!(
+            prover.anchor(height, hash).await.is_err(),
+            "node unreachable"
+        );
+    }
+}
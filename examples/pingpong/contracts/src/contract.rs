@@ -2,11 +2,11 @@ use cosmwasm_std::{
     entry_point, to_json_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Response,
     StdResult,
 };
-use quartz_contract_core::handler::RawHandler;
+use quartz_contract_core::prelude::{handle_migrate, handle_query, AttestedOutcome, RawHandler};
 
 use crate::{
     error::ContractError,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
 };
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -25,6 +25,12 @@ pub fn instantiate(
         .add_attribute("owner", info.sender))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // enclave upgrades only, all app state is preserved
+    handle_migrate(deps, &env, msg.quartz).map_err(Into::into)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     mut deps: DepsMut,
@@ -39,11 +45,10 @@ pub fn execute(
         // User messages
         ExecuteMsg::Ping(ping) => execute::ping(deps, env, info, ping),
         ExecuteMsg::Pong(attested_msg) => {
-            let _ = attested_msg
-                .clone()
-                .handle_raw(deps.branch(), &env, &info)?;
-
-            execute::pong(deps, env, info, attested_msg.msg.0)
+            match attested_msg.handle_output(deps.branch(), &env, &info)? {
+                AttestedOutcome::Applied(_, pong) => execute::pong(deps, env, info, pong),
+                AttestedOutcome::Pending(res) => Ok(res),
+            }
         }
     }
 }
@@ -90,8 +95,9 @@ pub mod execute {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
+        QueryMsg::Quartz(msg) => handle_query(deps, &env, msg),
         QueryMsg::GetAllMessages {} => to_json_binary(&query::get_all_messages(deps)?),
     }
 }
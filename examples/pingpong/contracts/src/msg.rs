@@ -38,10 +38,20 @@ pub mod execute {
     pub struct Pong {
         pub pubkey: HexBinary,
         pub response: HexBinary,
+        #[user_data(committed_height)]
+        pub height: u64,
     }
 }
 
+#[cw_serde]
+pub struct MigrateMsg {
+    pub quartz: QuartzMigrateMsg,
+}
+
 #[cw_serde]
 pub enum QueryMsg {
+    // Quartz queries
+    Quartz(QuartzQueryMsg),
+    // User queries
     GetAllMessages {},
 }
@@ -3,7 +3,11 @@ use std::{env, net::SocketAddr, path::PathBuf};
 use clap::Parser;
 use color_eyre::eyre::{eyre, Result};
 use cosmrs::AccountId;
-use quartz_common::enclave::types::Fmspc;
+use cosmwasm_std::HexBinary;
+use quartz_common::{
+    contract::state::DEFAULT_MAX_HEIGHT_LAG,
+    enclave::{chain_client::default::WitnessPolicy, types::Fmspc},
+};
 use reqwest::Url;
 use tendermint::{chain::Id, Hash};
 use tendermint_light_client::types::{Height, TrustThreshold};
@@ -18,6 +22,10 @@ fn parse_trust_threshold(s: &str) -> Result<TrustThreshold> {
     }
 }
 
+fn parse_anchor_signer(s: &str) -> Result<HexBinary> {
+    HexBinary::from_hex(s).map_err(Into::into)
+}
+
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
@@ -53,6 +61,11 @@ pub struct Cli {
     #[clap(long)]
     pub trusted_hash: Hash,
 
+    /// Compressed secp256k1 pub key (hex) that may sign trusted anchors for re-anchoring the enclave
+    /// once its trusted state is past the trusting period
+    #[clap(long, value_parser = parse_anchor_signer)]
+    pub anchor_signer: Option<HexBinary>,
+
     /// Trust threshold
     #[clap(long, value_parser = parse_trust_threshold, default_value_t = TrustThreshold::TWO_THIRDS)]
     pub trust_threshold: TrustThreshold,
@@ -69,9 +82,25 @@ pub struct Cli {
     #[clap(long, default_value = "5")]
     pub max_block_lag: u64,
 
+    /// Maximum age, in blocks, of the chain state that attested messages may be produced from
+    #[clap(long, default_value_t = DEFAULT_MAX_HEIGHT_LAG)]
+    pub max_height_lag: u64,
+
     #[clap(long, default_value = "http://127.0.0.1:26657")]
     pub node_url: Url,
 
+    /// Comma-separated list of tendermint RPC URLs of independent witness nodes
+    #[clap(long, value_delimiter = ',')]
+    pub witnesses: Vec<Url>,
+
+    /// Minimum number of distinct witnesses that must be reachable when generating proofs
+    #[clap(long, default_value = "1")]
+    pub min_witnesses: usize,
+
+    /// What to do when fewer than `min_witnesses` distinct witnesses are reachable
+    #[clap(long, value_enum, default_value_t = WitnessPolicy::Warn)]
+    pub witness_policy: WitnessPolicy,
+
     #[clap(long, default_value = "ws://127.0.0.1/websocket")]
     pub ws_url: Url,
 
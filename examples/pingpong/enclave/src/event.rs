@@ -6,7 +6,7 @@ use quartz_common::enclave::{
     handler::Handler,
 };
 use serde_json::json;
-use tendermint_rpc::event::Event as TmEvent;
+use tendermint_rpc::event::{Event as TmEvent, EventData};
 use tracing::info;
 
 use crate::{proto::PingRequest, request::EnclaveRequest};
@@ -47,6 +47,8 @@ where
 pub struct PingEvent {
     pub contract: AccountId,
     pub ping: Ping,
+    /// The height of the block that included the ping tx
+    pub height: u64,
 }
 
 impl TryFrom<TmEvent> for PingEvent {
@@ -77,7 +79,19 @@ impl TryFrom<TmEvent> for PingEvent {
             serde_json::from_str(ping_str)?
         };
 
-        Ok(Self { contract, ping })
+        let EventData::Tx { tx_result } = &event.data else {
+            return Err(anyhow!("not a tx event"));
+        };
+        let height = tx_result
+            .height
+            .try_into()
+            .map_err(|e| anyhow!("invalid tx height: {}", e))?;
+
+        Ok(Self {
+            contract,
+            ping,
+            height,
+        })
     }
 }
 
@@ -98,9 +112,9 @@ where
             .await
             .map_err(|e| anyhow!("Problem waiting for proof: {}", e))?;
 
-        // Call tm prover with trusted hash and height
+        // Call tm prover to prove the state that resulted from the ping tx
         let proof = ctx
-            .existence_proof(&contract, PINGS_KEY)
+            .existence_proof_at_height(&contract, PINGS_KEY, self.height)
             .await
             .map_err(|e| anyhow!("Problem getting existence proof: {}", e))?;
 
@@ -1,17 +1,17 @@
 use std::vec::IntoIter;
 
-use ecies::{decrypt, encrypt};
+use ecies::decrypt;
 use ping_pong_contract::{
     msg::{execute, execute::Ping, AttestedMsg, ExecuteMsg},
     state::PINGS_KEY,
 };
 use quartz_common::{
-    contract::msg::execute::attested::{HasUserData, RawNoop},
+    contract::msg::execute::attested::{HasUserData, RawNoop, UserDataDomain},
     enclave::{
-        attestor::{Attestor, DefaultAttestor},
+        attestor::{output_user_data_domain, Attestor, DefaultAttestor},
         handler::Handler,
-        proof_of_publication::ProofOfPublication,
-        store::Store,
+        proven_request::{ProvenRequest, StorageKey, ValueMatcher},
+        replica::encrypt_with_rng,
         DefaultSharedEnclave, Enclave,
     },
 };
@@ -30,9 +30,10 @@ pub enum EnclaveRequest {
 fn attested_msg<T: HasUserData + Clone, A: Attestor>(
     msg: T,
     attestor: A,
+    domain: &UserDataDomain,
 ) -> Result<AttestedMsg<T, A::RawAttestation>, Status> {
     let attestation = attestor
-        .attestation(msg.clone())
+        .attestation(msg.user_data_for(domain))
         .map_err(|e| Status::internal(e.to_string()))?;
 
     Ok(AttestedMsg {
@@ -48,79 +49,57 @@ impl Handler<DefaultSharedEnclave<()>> for EnclaveRequest {
 
     async fn handle(self, ctx: &DefaultSharedEnclave<()>) -> Result<Self::Response, Self::Error> {
         let attestor = ctx.attestor().await;
+        let domain = output_user_data_domain(ctx.store().await).await?;
         match self {
             EnclaveRequest::Ping(request) => request
                 .handle(ctx)
                 .await
-                .map(|msg| attested_msg(msg, attestor))?
+                .map(|msg| attested_msg(msg, attestor, &domain))?
                 .map(ExecuteMsg::Pong),
         }
         .map(|msg| vec![msg].into_iter())
     }
 }
 
+pub struct PingsKey;
+
+impl StorageKey for PingsKey {
+    const KEY: &'static str = PINGS_KEY;
+}
+
+/// Matches the ping's (encrypted) message against the proven value.
+pub struct PingMessageMatch;
+
+impl ValueMatcher<Ping> for PingMessageMatch {
+    fn matches(ping: &Ping, proof_value: &[u8]) -> bool {
+        serde_json::to_string(&ping.message).is_ok_and(|s| s.as_bytes() == proof_value)
+    }
+}
+
+pub type PingProvenRequest = ProvenRequest<Ping, PingsKey, PingMessageMatch>;
+
 #[async_trait::async_trait]
 impl Handler<DefaultSharedEnclave<()>> for PingRequest {
     type Error = Status;
     type Response = execute::Pong;
 
     async fn handle(self, ctx: &DefaultSharedEnclave<()>) -> Result<Self::Response, Self::Error> {
-        // verify proof
-        let proof: ProofOfPublication<Ping> = {
-            let message = self.message;
-            serde_json::from_str(&message).map_err(|e| Status::invalid_argument(e.to_string()))?
-        };
-        let contract = ctx
-            .store()
-            .await
-            .get_contract()
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?
-            .ok_or_else(|| Status::not_found("contract not found"))?;
-        let config = ctx
-            .store()
-            .await
-            .get_config()
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?
-            .ok_or_else(|| Status::not_found("config not found"))?;
-        let (trusted_height, trusted_hash) = ctx
-            .store()
-            .await
-            .get_trusted_height_hash()
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?;
-        let (target_height, target_hash) = proof.target_height_hash();
-
-        let (proof_value, ping) = proof
-            .verify(
-                config.light_client_opts(),
-                trusted_height,
-                trusted_hash,
-                contract,
-                PINGS_KEY.to_string(),
-                None,
-            )
-            .map_err(Status::failed_precondition)?;
-
-        let proof_value_matches_msg =
-            serde_json::to_string(&ping.message).is_ok_and(|s| s.as_bytes() == proof_value);
-        if !proof_value_matches_msg {
-            return Err(Status::failed_precondition("proof verification"));
-        }
-
-        // update trusted height and hash
-        ctx.store()
-            .await
-            .set_trusted_height_hash(target_height, target_hash)
-            .await
+        // verify proof and update trusted height/hash
+        let request: PingProvenRequest = serde_json::from_str(&self.message)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let proven = request.handle(ctx).await?;
+        let height = proven.height().value();
+        let sk = ctx.key_manager().await.read_lock().await.sk.clone();
+        // replicas must produce identical outputs, so encrypt with randomness derived from the
+        // proven inputs instead of the OS RNG
+        let mut rng = proven
+            .replica_rng(&sk.to_bytes())
             .map_err(|e| Status::internal(e.to_string()))?;
+        let ping = proven.into_msg();
 
         // Perform enclave logic
         // Decrypt the ciphertext using enclave private key
         let decrypted_message: String = {
-            let sk = ctx.key_manager().await.read_lock().await.sk.clone();
-
             let msg_bytes = decrypt(&sk.to_bytes(), &ping.message)
                 .map_err(|_| Status::invalid_argument("decryption failed"))?;
 
@@ -133,7 +112,7 @@ impl Handler<DefaultSharedEnclave<()>> for PingRequest {
         // Encrypt enclave response to the user's provided pubkey
         let response: Vec<u8> = {
             let response = format!("Enclave responded to {}", decrypted_message);
-            encrypt(&ping.pubkey, response.as_bytes())
+            encrypt_with_rng(&mut rng, &ping.pubkey, response.as_bytes())
                 .map_err(|e| Status::invalid_argument(e.to_string()))?
         };
 
@@ -141,6 +120,7 @@ impl Handler<DefaultSharedEnclave<()>> for PingRequest {
         let msg = execute::Pong {
             pubkey: ping.pubkey,
             response: response.into(),
+            height,
         };
 
         Ok(msg)
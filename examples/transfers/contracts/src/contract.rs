@@ -2,14 +2,11 @@ use cosmwasm_std::{
     entry_point, to_json_binary, Binary, Deps, DepsMut, Env, HexBinary, MessageInfo, Response,
     StdResult,
 };
-use quartz_contract_core::handler::RawHandler;
+use quartz_contract_core::prelude::{handle_migrate, handle_query, AttestedOutcome, RawHandler};
 
 use crate::{
     error::ContractError,
-    msg::{
-        execute::{QueryResponseMsg, Request, UpdateMsg},
-        ExecuteMsg, InstantiateMsg, QueryMsg,
-    },
+    msg::{execute::Request, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
     state::{BALANCES, DENOM, REQUESTS, STATE},
 };
 
@@ -36,6 +33,12 @@ pub fn instantiate(
         .add_attribute("owner", info.sender))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // enclave upgrades only, all app state is preserved
+    handle_migrate(deps, &env, msg.quartz).map_err(Into::into)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     mut deps: DepsMut,
@@ -63,43 +66,17 @@ pub fn execute(
 
         // Enclave msgs
         ExecuteMsg::Update(attested_msg) => {
-            let _ = attested_msg
-                .clone()
-                .handle_raw(deps.branch(), &env, &info)?;
-            let UpdateMsg {
-                ciphertext,
-                quantity,
-                withdrawals,
-            } = attested_msg.msg.0;
-            update(
-                deps,
-                env,
-                info,
-                UpdateMsg {
-                    ciphertext,
-                    quantity,
-                    withdrawals,
-                },
-            )
+            match attested_msg.handle_output(deps.branch(), &env, &info)? {
+                AttestedOutcome::Applied(_, msg) => update(deps, env, info, msg),
+                AttestedOutcome::Pending(res) => Ok(res),
+            }
         }
 
         ExecuteMsg::QueryResponse(attested_msg) => {
-            let _ = attested_msg
-                .clone()
-                .handle_raw(deps.branch(), &env, &info)?;
-            let QueryResponseMsg {
-                address,
-                encrypted_bal,
-            } = attested_msg.msg.0;
-            store_balance(
-                deps,
-                env,
-                info,
-                QueryResponseMsg {
-                    address,
-                    encrypted_bal,
-                },
-            )
+            match attested_msg.handle_output(deps.branch(), &env, &info)? {
+                AttestedOutcome::Applied(_, msg) => store_balance(deps, env, info, msg),
+                AttestedOutcome::Pending(res) => Ok(res),
+            }
         }
     }
 }
@@ -230,8 +207,9 @@ pub mod execute {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
+        QueryMsg::Quartz(msg) => handle_query(deps, &env, msg),
         QueryMsg::GetBalance { address } => to_json_binary(&query::get_balance(deps, address)?),
         QueryMsg::GetRequests {} => to_json_binary(&query::get_requests(deps)?),
         QueryMsg::GetState {} => to_json_binary(&query::get_state(deps)?),
@@ -16,8 +16,17 @@ pub struct InstantiateMsg<RA = RawDefaultAttestation> {
     pub denom: String,
 }
 
+#[cw_serde]
+pub struct MigrateMsg {
+    pub quartz: QuartzMigrateMsg,
+}
+
 #[cw_serde]
 pub enum QueryMsg {
+    // quartz queries
+    Quartz(QuartzQueryMsg),
+
+    // User queries
     GetBalance { address: String },
     GetRequests {},
     GetState {},
@@ -81,6 +90,8 @@ pub mod execute {
         pub ciphertext: HexBinary,
         pub quantity: u32,
         pub withdrawals: Vec<(Addr, Uint128)>,
+        #[user_data(committed_height)]
+        pub height: u64,
         // pub proof: π
     }
 
@@ -9,11 +9,11 @@ use quartz_common::{
     },
 };
 use serde_json::json;
-use tendermint_rpc::event::Event as TmEvent;
+use tendermint_rpc::event::{Event as TmEvent, EventData};
 use tracing::info;
-use transfers_contract::msg::{
-    execute::Request as TransferRequest,
-    QueryMsg::{GetRequests, GetState},
+use transfers_contract::{
+    msg::{execute::Request as TransferRequest, QueryMsg::GetState},
+    state::REQUESTS_KEY,
 };
 
 use crate::{
@@ -23,6 +23,7 @@ use crate::{
 #[derive(Clone, Debug)]
 pub struct TransferEvent {
     pub contract: AccountId,
+    pub height: u64,
 }
 
 impl TryFrom<TmEvent> for TransferEvent {
@@ -41,7 +42,15 @@ impl TryFrom<TmEvent> for TransferEvent {
             .parse::<AccountId>()
             .map_err(|e| anyhow!("failed to parse contract address: {}", e))?;
 
-        Ok(TransferEvent { contract })
+        let EventData::Tx { tx_result } = &event.data else {
+            return Err(anyhow!("not a tx event"));
+        };
+        let height = tx_result
+            .height
+            .try_into()
+            .map_err(|e| anyhow!("invalid tx height: {}", e))?;
+
+        Ok(TransferEvent { contract, height })
     }
 }
 
@@ -56,41 +65,47 @@ where
     async fn handle(self, ctx: &C) -> Result<Self::Response, Self::Error> {
         let contract = self.contract;
 
-        // Query contract state
-        let requests: Vec<TransferRequest> = ctx
-            .query_contract(&contract, json!(GetRequests {}))
-            .await
-            .map_err(|e| anyhow!("Problem querying contract state: {}", e))?;
+        // Query contract state at the height that the requests are proven at, so that the state
+        // and seq num match the proven requests even if other txs landed since
         let state: HexBinary = ctx
-            .query_contract(&contract, json!(GetState {}))
+            .query_contract_at_height(&contract, json!(GetState {}), self.height)
             .await
             .map_err(|e| anyhow!("Problem querying contract state: {}", e))?;
         let seq_num: Uint64 = ctx
-            .query_contract(&contract, SEQUENCE_NUM_KEY.to_string())
+            .query_contract_at_height(&contract, SEQUENCE_NUM_KEY.to_string(), self.height)
             .await
             .map_err(|e| anyhow!("Problem querying contract state: {}", e))?;
 
-        // Request body contents
-        let update_contents = UpdateRequestMessage {
-            state,
-            requests,
-            seq_num: seq_num.into(),
-        };
-
         // Wait 2 blocks
         info!("Waiting 2 blocks for light client proof");
         ctx.wait_for_blocks(2)
             .await
             .map_err(|e| anyhow!("Problem waiting for proof: {}", e))?;
 
-        // Call tm prover with trusted hash and height
+        // Call tm prover to prove the requests that resulted from the transfer tx
         let proof = ctx
-            .existence_proof(&contract, "requests")
+            .existence_proof_at_height(&contract, REQUESTS_KEY, self.height)
             .await
             .map_err(|e| anyhow!("Problem getting existence proof: {}", e))?;
+        let mut proof_json = serde_json::to_value(proof)?;
+
+        // The requests must match the proven ones, so take them from the proof (instead of
+        // querying the latest ones)
+        let requests: Vec<TransferRequest> = {
+            let value = proof_json["merkle_proof"]["value"]
+                .as_str()
+                .ok_or_else(|| anyhow!("missing proven value"))?;
+            serde_json::from_slice(&hex::decode(value)?)?
+        };
+
+        // Request body contents
+        let update_contents = UpdateRequestMessage {
+            state,
+            requests,
+            seq_num: seq_num.into(),
+        };
 
         // Merge the UpdateRequestMessage with the proof
-        let mut proof_json = serde_json::to_value(proof)?;
         proof_json["msg"] = serde_json::to_value(&update_contents)?;
 
         // Build final request object
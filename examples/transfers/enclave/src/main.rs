@@ -70,7 +70,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         args.trusting_period,
         args.max_clock_drift,
         args.max_block_lag,
-    )?;
+    )?
+    .with_max_height_lag(args.max_height_lag);
 
     #[cfg(not(feature = "mock-sgx"))]
     let attestor = attestor::DcapAttestor {
@@ -81,21 +82,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(feature = "mock-sgx")]
     let attestor = attestor::MockAttestor::default();
 
-    let config = Config::new(
+    let mut config = Config::new(
         attestor.mr_enclave()?,
         light_client_opts,
         args.tcbinfo_contract.map(|c| c.to_string()),
         args.dcap_verifier_contract.map(|c| c.to_string()),
     );
+    if let Some(anchor_signer) = args.anchor_signer {
+        config = config.with_anchor_signer(anchor_signer);
+    }
     let chain_client = DefaultChainClient::new(
         args.chain_id,
         sk,
         args.grpc_url,
         args.node_url,
+        args.witnesses,
         args.ws_url.clone(),
         args.trusted_height,
         args.trusted_hash,
-    );
+    )
+    .with_witness_policy(args.min_witnesses, args.witness_policy);
 
     let app_ctx = AppCtx {
         backup_path: args.backup_path.clone(),
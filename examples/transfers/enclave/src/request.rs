@@ -1,13 +1,14 @@
 use std::vec::IntoIter;
 
 use cosmwasm_std::HexBinary;
-use ecies::{decrypt, encrypt};
+use ecies::decrypt;
 use k256::ecdsa::{SigningKey, VerifyingKey};
 use quartz_common::{
-    contract::msg::execute::attested::{HasUserData, RawNoop},
+    contract::msg::execute::attested::{HasUserData, RawNoop, UserDataDomain},
     enclave::{
-        attestor::{Attestor, DefaultAttestor},
+        attestor::{output_user_data_domain, Attestor, DefaultAttestor},
         handler::Handler,
+        replica::{encrypt_with_rng, ReplicaRng},
         Enclave,
     },
 };
@@ -34,9 +35,10 @@ pub enum EnclaveRequest {
 fn attested_msg<T: HasUserData + Clone, A: Attestor>(
     msg: T,
     attestor: A,
+    domain: &UserDataDomain,
 ) -> Result<AttestedMsg<T, A::RawAttestation>, Status> {
     let attestation = attestor
-        .attestation(msg.clone())
+        .attestation(msg.user_data_for(domain))
         .map_err(|e| Status::internal(e.to_string()))?;
 
     Ok(AttestedMsg {
@@ -52,16 +54,17 @@ impl Handler<AppEnclave> for EnclaveRequest {
 
     async fn handle(self, ctx: &AppEnclave) -> Result<Self::Response, Self::Error> {
         let attestor = ctx.attestor().await;
+        let domain = output_user_data_domain(ctx.store().await).await?;
         match self {
             EnclaveRequest::Update(request) => request
                 .handle(ctx)
                 .await
-                .map(|msg| attested_msg(msg, attestor))?
+                .map(|msg| attested_msg(msg, attestor, &domain))?
                 .map(ExecuteMsg::Update),
             EnclaveRequest::Query(request) => request
                 .handle(ctx)
                 .await
-                .map(|msg| attested_msg(msg, attestor))?
+                .map(|msg| attested_msg(msg, attestor, &domain))?
                 .map(ExecuteMsg::QueryResponse),
         }
         .map(|msg| vec![msg].into_iter())
@@ -85,19 +88,35 @@ fn decrypt_state(sk: &SigningKey, ciphertext: &[u8]) -> Result<State, Status> {
     serde_json::from_slice(&o).map_err(|e| Status::invalid_argument(e.to_string()))
 }
 
-fn encrypt_state(state: State, enclave_pk: VerifyingKey) -> Result<HexBinary, Status> {
+fn encrypt_state(
+    state: State,
+    enclave_pk: VerifyingKey,
+    rng: &mut ReplicaRng,
+) -> Result<HexBinary, Status> {
     let serialized_state = serde_json::to_string(&state).expect("infallible serializer");
 
-    match encrypt(&enclave_pk.to_sec1_bytes(), serialized_state.as_bytes()) {
+    match encrypt_with_rng(
+        rng,
+        &enclave_pk.to_sec1_bytes(),
+        serialized_state.as_bytes(),
+    ) {
         Ok(encrypted_state) => Ok(encrypted_state.into()),
         Err(e) => Err(Status::internal(format!("Encryption error: {}", e))),
     }
 }
 
-fn encrypt_balance(balance: Balance, ephemeral_pk: VerifyingKey) -> Result<HexBinary, Status> {
+fn encrypt_balance(
+    balance: Balance,
+    ephemeral_pk: VerifyingKey,
+    rng: &mut ReplicaRng,
+) -> Result<HexBinary, Status> {
     let serialized_balance = serde_json::to_string(&balance).expect("infallible serializer");
 
-    match encrypt(&ephemeral_pk.to_sec1_bytes(), serialized_balance.as_bytes()) {
+    match encrypt_with_rng(
+        rng,
+        &ephemeral_pk.to_sec1_bytes(),
+        serialized_balance.as_bytes(),
+    ) {
         Ok(encrypted_balance) => Ok(encrypted_balance.into()),
         Err(e) => Err(Status::internal(format!("Encryption error: {}", e))),
     }
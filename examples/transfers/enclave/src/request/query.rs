@@ -1,7 +1,8 @@
 use cosmwasm_std::{Addr, HexBinary, Uint128};
 use k256::ecdsa::VerifyingKey;
-use quartz_common::enclave::handler::Handler;
+use quartz_common::enclave::{handler::Handler, replica::replica_rng};
 use serde::{Deserialize, Serialize};
+use tendermint::block::Height;
 use tonic::Status;
 use transfers_contract::msg::execute;
 
@@ -51,8 +52,14 @@ impl Handler<AppEnclave> for QueryRequest {
                 Status::invalid_argument(format!("Invalid ephemeral public key: {}", e))
             })?;
 
-        // Encrypt the balance using the ephemeral public key
-        let bal_enc = encrypt_balance(bal, ephemeral_pubkey)
+        // Encrypt the balance using the ephemeral public key (with randomness derived from the
+        // query, so that replicas produce identical responses)
+        let mut rng = {
+            let sk = ctx.key_manager.read_lock().await.sk.clone();
+            replica_rng(&sk.to_bytes(), Height::default(), &message)
+                .map_err(|e| Status::internal(e.to_string()))?
+        };
+        let bal_enc = encrypt_balance(bal, ephemeral_pubkey, &mut rng)
             .map_err(|e| Status::internal(format!("Encryption error: {}", e)))?;
 
         // Prepare message to chain
@@ -3,10 +3,11 @@ use std::collections::btree_map::Entry;
 use cosmwasm_std::{Addr, HexBinary, Uint128};
 use quartz_common::enclave::{
     backup_restore::Backup,
-    handler::{ensure_seq_num_consistency, Handler},
+    handler::Handler,
     key_manager::KeyManager,
-    proof_of_publication::ProofOfPublication,
-    store::Store,
+    proven_request::{
+        HasProvenValue, JsonValueMatch, ProvenRequest, SeqNumChecked, SequencedMessage, StorageKey,
+    },
     Enclave,
 };
 use serde::{Deserialize, Serialize};
@@ -29,84 +30,56 @@ pub struct UpdateRequestMessage {
     pub seq_num: u64,
 }
 
+impl HasProvenValue for UpdateRequestMessage {
+    type Value = Vec<TransferRequest>;
+
+    fn proven_value(&self) -> &Self::Value {
+        &self.requests
+    }
+}
+
+impl SequencedMessage for UpdateRequestMessage {
+    fn seq_num(&self) -> u64 {
+        self.seq_num
+    }
+
+    fn pending_sequenced_requests(&self) -> usize {
+        self.requests
+            .iter()
+            .filter(|req| matches!(req, TransferRequest::Transfer(_)))
+            .count()
+    }
+}
+
+pub struct RequestsKey;
+
+impl StorageKey for RequestsKey {
+    const KEY: &'static str = REQUESTS_KEY;
+}
+
+pub type UpdateProvenRequest =
+    ProvenRequest<UpdateRequestMessage, RequestsKey, JsonValueMatch, SeqNumChecked>;
+
 #[async_trait::async_trait]
 impl Handler<AppEnclave> for UpdateRequest {
     type Error = Status;
     type Response = UpdateMsg;
 
     async fn handle(self, ctx: &AppEnclave) -> Result<Self::Response, Self::Error> {
-        // verify proof
-        let proof: ProofOfPublication<UpdateRequestMessage> = {
-            let message = self.message;
-            serde_json::from_str(&message).map_err(|e| Status::invalid_argument(e.to_string()))?
+        // verify proof, update trusted height/hash and ensure sequence number consistency
+        let request: UpdateProvenRequest = serde_json::from_str(&self.message)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let proven = request.handle(ctx).await?;
+        let height = proven.height().value();
+        // replicas must produce identical outputs, so encrypt with randomness derived from the
+        // proven inputs instead of the OS RNG
+        let mut rng = {
+            let sk = ctx.key_manager.read_lock().await.sk.clone();
+            proven
+                .replica_rng(&sk.to_bytes())
+                .map_err(|e| Status::internal(e.to_string()))?
         };
-        let contract = ctx
-            .store()
-            .await
-            .get_contract()
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?
-            .ok_or_else(|| Status::not_found("contract not found"))?;
-        let config = ctx
-            .store()
-            .await
-            .get_config()
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?
-            .ok_or_else(|| Status::not_found("config not found"))?;
-        let (trusted_height, trusted_hash) = ctx
-            .store()
-            .await
-            .get_trusted_height_hash()
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?;
-        let (target_height, target_hash) = proof.target_height_hash();
-
-        let (proof_value, message) = proof
-            .verify(
-                config.light_client_opts(),
-                trusted_height,
-                trusted_hash,
-                contract,
-                REQUESTS_KEY.to_string(),
-                None,
-            )
-            .map_err(Status::failed_precondition)?;
-
-        let proof_value_matches_msg =
-            serde_json::to_string(&message.requests).is_ok_and(|s| s.as_bytes() == proof_value);
-        if !proof_value_matches_msg {
-            return Err(Status::failed_precondition("proof verification"));
-        }
-
-        // update trusted height and hash
-        ctx.store()
-            .await
-            .set_trusted_height_hash(target_height, target_hash)
-            .await
-            .map_err(|e| Status::internal(e.to_string()))?;
-
-        // ensure sequence number consistency
-        // TODO: move this into the core?
-        let pending_sequenced_requests = message
-            .requests
-            .iter()
-            .filter(|req| matches!(req, TransferRequest::Transfer(_)))
-            .count();
-        if pending_sequenced_requests > 0 {
-            let seq_num = ctx
-                .store()
-                .await
-                .get_seq_num()
-                .await
-                .map_err(|_| Status::internal("store read error"))?;
-            ensure_seq_num_consistency(seq_num, message.seq_num, pending_sequenced_requests)?;
-            ctx.store()
-                .await
-                .inc_seq_num(pending_sequenced_requests)
-                .await
-                .map_err(|_| Status::internal("store read error"))?;
-        }
+        let message = proven.into_msg();
 
         // update backup to write latest state (i.e. sequence num and trusted height/hash)
         ctx.backup(ctx.ctx.backup_path.clone())
@@ -171,7 +144,8 @@ impl Handler<AppEnclave> for UpdateRequest {
         let state_enc = {
             let pk = ctx.key_manager().await.pub_key().await;
 
-            encrypt_state(state, pk.into()).map_err(|e| Status::invalid_argument(e.to_string()))?
+            encrypt_state(state, pk.into(), &mut rng)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?
         };
 
         // Prepare message to chain
@@ -179,6 +153,7 @@ impl Handler<AppEnclave> for UpdateRequest {
             ciphertext: state_enc,
             quantity: requests_len,
             withdrawals: withdrawals_response,
+            height,
         };
 
         Ok(msg)